@@ -0,0 +1,25 @@
+-- 片段表；向量维度在首次启动时按 EMBEDDING_VECTOR_SIZE 固定，见 index_meta
+CREATE EXTENSION IF NOT EXISTS vector WITH SCHEMA public;
+
+CREATE TABLE IF NOT EXISTS knowledge_chunks (
+    point_id TEXT PRIMARY KEY,
+    doc_id TEXT NOT NULL,
+    chunk_id TEXT NOT NULL,
+    path TEXT NOT NULL,
+    title_path TEXT NOT NULL DEFAULT '',
+    section TEXT NOT NULL DEFAULT '',
+    text TEXT NOT NULL,
+    hash TEXT NOT NULL,
+    doc_hash TEXT NOT NULL DEFAULT '',
+    indexed_at TEXT NOT NULL DEFAULT '',
+    embedding public.vector NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS knowledge_chunks_doc_id_idx ON knowledge_chunks (doc_id);
+CREATE INDEX IF NOT EXISTS knowledge_chunks_path_idx ON knowledge_chunks (path text_pattern_ops);
+
+-- 索引生成参数：embed_model / vector_size / distance
+CREATE TABLE IF NOT EXISTS index_meta (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
@@ -0,0 +1,232 @@
+use axum::{
+    Json,
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::{
+    AppState,
+    api::{
+        error_code::ErrorCode,
+        error_response::{ApiError, ApiJson},
+    },
+    vector_store::{
+        IndexVersion, MAX_PRUNE_OLDER_THAN_SECS, MaintenanceOptions, MaintenanceReport,
+        VectorStoreError, maintenance::MaintenanceError,
+    },
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum AdminError {
+    #[error("Vector store error: {0}")]
+    VectorStoreError(#[from] VectorStoreError),
+
+    #[error("No previous index version to roll back to")]
+    NoPreviousVersion,
+
+    #[error("A reindex job is in progress")]
+    JobInProgress,
+
+    #[error("Table maintenance is already running")]
+    MaintenanceRunning,
+
+    #[error("{message}")]
+    InvalidRequest {
+        field: &'static str,
+        message: String,
+    },
+}
+
+impl From<MaintenanceError> for AdminError {
+    fn from(error: MaintenanceError) -> Self {
+        match error {
+            MaintenanceError::AlreadyRunning => AdminError::MaintenanceRunning,
+            MaintenanceError::JobInProgress => AdminError::JobInProgress,
+            MaintenanceError::VectorStore(e) => AdminError::VectorStoreError(e),
+        }
+    }
+}
+
+impl From<AdminError> for ApiError {
+    fn from(error: AdminError) -> Self {
+        match error {
+            AdminError::VectorStoreError(VectorStoreError::VersionNotFound(ref version)) => {
+                let details = serde_json::json!({ "version": version });
+                ApiError::not_found(error.to_string()).with_details(details)
+            }
+            AdminError::VectorStoreError(VectorStoreError::VersionSpecMismatch {
+                ref version,
+                ref stored,
+                ..
+            }) => {
+                let details = serde_json::json!({
+                    "version": version,
+                    "stored_spec": stored.to_string(),
+                });
+                ApiError::conflict(error.to_string()).with_details(details)
+            }
+            AdminError::VectorStoreError(VectorStoreError::Unsupported(_)) => {
+                ApiError::invalid_request(error.to_string())
+            }
+            AdminError::VectorStoreError(_) => ApiError::new(
+                StatusCode::SERVICE_UNAVAILABLE,
+                ErrorCode::RetrievalFailed,
+                error.to_string(),
+            ),
+            AdminError::InvalidRequest { field, message } => ApiError::invalid_request(message)
+                .with_details(serde_json::json!({ "field": field })),
+            AdminError::NoPreviousVersion
+            | AdminError::JobInProgress
+            | AdminError::MaintenanceRunning => ApiError::conflict(error.to_string()),
+        }
+    }
+}
+
+impl IntoResponse for AdminError {
+    fn into_response(self) -> Response {
+        ApiError::from(self).into_response()
+    }
+}
+
+/// GET /api/admin/index/versions 响应
+#[derive(Debug, Serialize)]
+pub struct IndexVersionsResponse {
+    pub versions: Vec<IndexVersion>,
+}
+
+/// POST /api/admin/index/rollback 请求
+#[derive(Debug, Default, Deserialize)]
+pub struct RollbackRequest {
+    /// 目标版本，缺省为当前版本之前最近的一个
+    #[serde(default)]
+    pub version: Option<String>,
+}
+
+/// POST /api/admin/index/rollback 响应
+#[derive(Debug, Serialize)]
+pub struct RollbackResponse {
+    pub previous: String,
+    pub active: String,
+}
+
+/// POST /api/admin/index/maintenance 请求，未指定的项按默认执行
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct MaintenanceRequest {
+    pub compact: bool,
+    pub prune: bool,
+    /// 历史版本保留时长，缺省为 INDEX_PRUNE_OLDER_THAN_SECS
+    pub prune_older_than_secs: Option<u64>,
+    pub optimize_index: bool,
+    pub rebuild_index: bool,
+}
+
+impl Default for MaintenanceRequest {
+    fn default() -> Self {
+        Self {
+            compact: true,
+            prune: true,
+            prune_older_than_secs: None,
+            optimize_index: true,
+            rebuild_index: false,
+        }
+    }
+}
+
+/// GET /api/admin/index/maintenance 响应
+#[derive(Debug, Serialize)]
+pub struct MaintenanceStatusResponse {
+    pub running: bool,
+    /// 后台维护间隔，0 表示仅手动触发
+    pub interval_secs: u64,
+    pub last_report: Option<MaintenanceReport>,
+}
+
+/// 处理 /api/admin/index/versions GET 请求
+pub async fn handle_list_versions(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<IndexVersionsResponse>, AdminError> {
+    let versions = state.vector_store.list_versions().await?;
+    Ok(Json(IndexVersionsResponse { versions }))
+}
+
+/// 处理 /api/admin/index/rollback POST 请求：将查询切回之前的索引版本
+pub async fn handle_rollback(
+    State(state): State<Arc<AppState>>,
+    req: ApiJson<RollbackRequest>,
+) -> Result<Json<RollbackResponse>, AdminError> {
+    // 运行中的全量重建完成后会切换版本，覆盖回滚结果
+    if state.job_manager.is_running().await {
+        return Err(AdminError::JobInProgress);
+    }
+
+    let versions = state.vector_store.list_versions().await?;
+    let active_idx = versions
+        .iter()
+        .position(|version| version.active)
+        .ok_or(AdminError::NoPreviousVersion)?;
+    let previous = versions[active_idx].name.clone();
+
+    let target = match &req.version {
+        Some(version) => version.clone(),
+        None => active_idx
+            .checked_sub(1)
+            .map(|idx| versions[idx].name.clone())
+            .ok_or(AdminError::NoPreviousVersion)?,
+    };
+
+    state.vector_store.activate_version(&target).await?;
+    state.job_manager.mark_index_changed();
+    tracing::info!(from = %previous, to = %target, "index version rolled back");
+
+    Ok(Json(RollbackResponse {
+        previous,
+        active: target,
+    }))
+}
+
+/// 处理 /api/admin/index/maintenance POST 请求：立即对当前版本执行表维护
+pub async fn handle_maintenance(
+    State(state): State<Arc<AppState>>,
+    req: ApiJson<MaintenanceRequest>,
+) -> Result<Json<MaintenanceReport>, AdminError> {
+    if let Some(secs) = req.prune_older_than_secs
+        && secs > MAX_PRUNE_OLDER_THAN_SECS
+    {
+        return Err(AdminError::InvalidRequest {
+            field: "prune_older_than_secs",
+            message: format!("prune_older_than_secs must be at most {MAX_PRUNE_OLDER_THAN_SECS}"),
+        });
+    }
+
+    let options = MaintenanceOptions {
+        compact: req.compact,
+        prune_older_than_secs: req.prune.then(|| {
+            req.prune_older_than_secs
+                .unwrap_or(state.maintainer.prune_older_than_secs())
+        }),
+        optimize_index: req.optimize_index,
+        rebuild_index: req.rebuild_index,
+    };
+    // 合并文件会与索引任务的写入提交冲突，维护期间占用任务槽
+    let report = state.maintainer.run(&state, &options).await?;
+    if report.index_rebuilt {
+        state.job_manager.mark_index_changed();
+    }
+
+    Ok(Json(report))
+}
+
+/// 处理 /api/admin/index/maintenance GET 请求：返回最近一次维护结果
+pub async fn handle_maintenance_status(
+    State(state): State<Arc<AppState>>,
+) -> Json<MaintenanceStatusResponse> {
+    Json(MaintenanceStatusResponse {
+        running: state.maintainer.is_running(),
+        interval_secs: state.maintainer.interval_secs(),
+        last_report: state.maintainer.last_report().await,
+    })
+}
@@ -0,0 +1,252 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use std::{fs, sync::Arc, time::Instant};
+
+use crate::{
+    AppState,
+    api::{
+        error_code::ErrorCode,
+        error_response::{ApiError, ApiJson},
+    },
+    indexer::{DocumentIndexResult, IndexControl, IndexerError},
+    vector_store::{ChunkRecord, DocumentSummary, VectorStoreError},
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum DocumentError {
+    #[error("Vector store error: {0}")]
+    VectorStoreError(#[from] VectorStoreError),
+
+    #[error("Document not found: {0}")]
+    NotFound(String),
+
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
+
+    #[error("Document already exists: {0}")]
+    AlreadyExists(String),
+
+    #[error("A reindex job is in progress, retry after it finishes")]
+    ReindexInProgress,
+
+    #[error("Upload failed: {0}")]
+    UploadFailed(String),
+
+    #[error("Indexing failed: {0}")]
+    IndexingFailed(#[from] IndexerError),
+}
+
+impl From<DocumentError> for ApiError {
+    fn from(error: DocumentError) -> Self {
+        match error {
+            DocumentError::VectorStoreError(_) => ApiError::new(
+                StatusCode::SERVICE_UNAVAILABLE,
+                ErrorCode::RetrievalFailed,
+                error.to_string(),
+            ),
+            DocumentError::NotFound(ref doc_id) => {
+                let details = serde_json::json!({ "doc_id": doc_id });
+                ApiError::not_found(error.to_string()).with_details(details)
+            }
+            DocumentError::InvalidInput(_) => ApiError::invalid_request(error.to_string()),
+            DocumentError::AlreadyExists(ref path) => {
+                let details = serde_json::json!({ "path": path });
+                ApiError::conflict(error.to_string()).with_details(details)
+            }
+            DocumentError::ReindexInProgress => ApiError::conflict(error.to_string()),
+            DocumentError::UploadFailed(_) => ApiError::internal(error.to_string()),
+            DocumentError::IndexingFailed(IndexerError::InvalidPath(ref path)) => {
+                let details = serde_json::json!({ "field": "path", "path": path });
+                ApiError::invalid_request(error.to_string()).with_details(details)
+            }
+            DocumentError::IndexingFailed(_) => ApiError::internal(error.to_string()),
+        }
+    }
+}
+
+impl IntoResponse for DocumentError {
+    fn into_response(self) -> Response {
+        ApiError::from(self).into_response()
+    }
+}
+
+/// POST /api/documents 请求
+#[derive(Debug, Deserialize)]
+pub struct UploadDocumentRequest {
+    /// 知识库目录内的相对路径，例如 `runbooks/cpm.md`
+    pub path: String,
+    /// Markdown 正文
+    pub content: String,
+    /// 同名文件已存在时是否覆盖
+    #[serde(default)]
+    pub overwrite: bool,
+}
+
+/// POST /api/documents 响应
+#[derive(Debug, Serialize)]
+pub struct UploadDocumentResponse {
+    #[serde(flatten)]
+    pub result: DocumentIndexResult,
+    /// 是否为新建文件（false 表示覆盖了已有文件）
+    pub created: bool,
+}
+
+/// GET /api/documents 响应
+#[derive(Debug, Serialize)]
+pub struct DocumentListResponse {
+    pub total: usize,
+    pub documents: Vec<DocumentSummary>,
+}
+
+/// GET /api/documents/{doc_id}/chunks 响应
+#[derive(Debug, Serialize)]
+pub struct DocumentChunksResponse {
+    pub doc_id: String,
+    pub chunks: Vec<ChunkRecord>,
+}
+
+/// DELETE /api/documents/{doc_id} 响应
+#[derive(Debug, Serialize)]
+pub struct DeleteDocumentResponse {
+    pub doc_id: String,
+    pub deleted_chunks: usize,
+}
+
+/// 处理 /api/documents GET 请求
+pub async fn handle_list_documents(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<DocumentListResponse>, DocumentError> {
+    let documents = state.vector_store.list_documents().await?;
+
+    Ok(Json(DocumentListResponse {
+        total: documents.len(),
+        documents,
+    }))
+}
+
+/// 处理 /api/documents POST 请求
+///
+/// 将 Markdown 写入知识库目录，并立即只对该文档分块、向量化入库。
+/// 写入与索引作为一个索引任务登记在 JobManager 中：已有任务运行时返回 409，
+/// 上传期间也不会启动其他任务。索引失败时恢复原文件（新建的文件被删除）。
+pub async fn handle_upload_document(
+    State(state): State<Arc<AppState>>,
+    req: ApiJson<UploadDocumentRequest>,
+) -> Result<Json<UploadDocumentResponse>, DocumentError> {
+    if req.content.trim().is_empty() {
+        return Err(DocumentError::InvalidInput(
+            "content cannot be empty".to_string(),
+        ));
+    }
+
+    let path = state.indexer.resolve_document_path(&req.path)?;
+    if path.exists() && !req.overwrite {
+        return Err(DocumentError::AlreadyExists(req.path.clone()));
+    }
+
+    let (job_id, control) = state
+        .job_manager
+        .start_job()
+        .await
+        .map_err(|_| DocumentError::ReindexInProgress)?;
+    let start = Instant::now();
+
+    let previous = match fs::read(&path) {
+        Ok(previous) => Some(previous),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => {
+            let reason = e.to_string();
+            state.job_manager.finish_job(&job_id, Err(e.into())).await;
+            return Err(DocumentError::UploadFailed(reason));
+        }
+    };
+    let outcome = write_and_index(&state, &path, &req.content, &control).await;
+
+    match outcome {
+        Ok(result) => {
+            let job_result = result.to_index_result(start.elapsed().as_millis());
+            state.job_manager.finish_job(&job_id, Ok(job_result)).await;
+            Ok(Json(UploadDocumentResponse {
+                result,
+                created: previous.is_none(),
+            }))
+        }
+        Err(e) => {
+            // 先恢复文件再释放任务槽位，避免后续任务索引到失败的内容
+            let reason = e.to_string();
+            let restored = match &previous {
+                Some(previous) => fs::write(&path, previous),
+                None => fs::remove_file(&path),
+            };
+            let file_state = match restored {
+                Ok(()) => "uploaded file reverted",
+                Err(restore_err) => {
+                    tracing::error!(
+                        path = %req.path,
+                        error = %restore_err,
+                        "failed to revert uploaded document"
+                    );
+                    "uploaded file kept in knowledge directory"
+                }
+            };
+            state.job_manager.finish_job(&job_id, Err(e)).await;
+            tracing::warn!(path = %req.path, error = %reason, file_state, "document upload failed");
+            Err(DocumentError::UploadFailed(format!(
+                "{reason}; {file_state}"
+            )))
+        }
+    }
+}
+
+async fn write_and_index(
+    state: &AppState,
+    path: &std::path::Path,
+    content: &str,
+    control: &IndexControl,
+) -> Result<DocumentIndexResult, IndexerError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, content)?;
+    state.indexer.index_document(path, control).await
+}
+
+/// 处理 /api/documents/{doc_id}/chunks GET 请求
+pub async fn handle_document_chunks(
+    State(state): State<Arc<AppState>>,
+    Path(doc_id): Path<String>,
+) -> Result<Json<DocumentChunksResponse>, DocumentError> {
+    let chunks = state.vector_store.list_chunks(&doc_id).await?;
+    if chunks.is_empty() {
+        return Err(DocumentError::NotFound(doc_id));
+    }
+
+    Ok(Json(DocumentChunksResponse { doc_id, chunks }))
+}
+
+/// 处理 /api/documents/{doc_id} DELETE 请求
+///
+/// 只删除索引中的片段；源文件仍在知识库目录中时，下次重建索引会重新写入。
+pub async fn handle_delete_document(
+    State(state): State<Arc<AppState>>,
+    Path(doc_id): Path<String>,
+) -> Result<Json<DeleteDocumentResponse>, DocumentError> {
+    let deleted_chunks = state.vector_store.delete_by_doc_id(&doc_id).await?;
+    state.vector_store.flush().await?;
+    if deleted_chunks == 0 {
+        return Err(DocumentError::NotFound(doc_id));
+    }
+
+    state.job_manager.mark_index_changed();
+    tracing::info!(doc_id = %doc_id, deleted_chunks, "document deleted from index");
+
+    Ok(Json(DeleteDocumentResponse {
+        doc_id,
+        deleted_chunks,
+    }))
+}
@@ -15,8 +15,18 @@ pub enum ErrorCode {
     UpstreamError,
     /// 检索失败
     RetrievalFailed,
+    /// 索引维度与当前 embedding 配置不一致，重建完成前无法检索
+    IndexSpecMismatch,
     /// 没有匹配的结果
     NoMatch,
+    /// 答案缺乏参考资料依据
+    UngroundedAnswer,
+    /// 请求参数不合法
+    InvalidRequest,
+    /// 资源不存在
+    NotFound,
+    /// 与当前状态冲突（如已有任务在运行）
+    Conflict,
     /// 内部错误
     InternalError,
 }
@@ -30,7 +40,12 @@ impl ErrorCode {
             ErrorCode::UpstreamUnavailable => "UPSTREAM_UNAVAILABLE",
             ErrorCode::UpstreamError => "UPSTREAM_ERROR",
             ErrorCode::RetrievalFailed => "RETRIEVAL_FAILED",
+            ErrorCode::IndexSpecMismatch => "INDEX_SPEC_MISMATCH",
             ErrorCode::NoMatch => "NO_MATCH",
+            ErrorCode::UngroundedAnswer => "UNGROUNDED_ANSWER",
+            ErrorCode::InvalidRequest => "INVALID_REQUEST",
+            ErrorCode::NotFound => "NOT_FOUND",
+            ErrorCode::Conflict => "CONFLICT",
             ErrorCode::InternalError => "INTERNAL_ERROR",
         }
     }
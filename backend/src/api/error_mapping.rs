@@ -13,7 +13,7 @@ pub fn map_provider_error(error: &ProviderError) -> ErrorCode {
                 ErrorCode::UpstreamUnavailable
             }
         }
-        ProviderError::ApiError { status, message: _ } => map_status_code(*status),
+        ProviderError::ApiError { status, .. } => map_status_code(*status),
         ProviderError::Timeout => ErrorCode::UpstreamTimeout,
         ProviderError::SerializationError(_) => ErrorCode::InternalError,
     }
@@ -51,7 +51,12 @@ pub fn get_error_description(code: ErrorCode) -> &'static str {
         ErrorCode::UpstreamUnavailable => "上游服务不可用，请稍后重试",
         ErrorCode::UpstreamError => "上游服务返回错误",
         ErrorCode::RetrievalFailed => "检索服务失败，请检查向量存储连接",
+        ErrorCode::IndexSpecMismatch => "索引正在按新的 embedding 配置重建，完成前暂时无法检索",
         ErrorCode::NoMatch => "未找到相关资料，请尝试其他问题",
+        ErrorCode::UngroundedAnswer => "答案中部分内容缺乏参考资料依据，请谨慎参考",
+        ErrorCode::InvalidRequest => "请求参数不合法",
+        ErrorCode::NotFound => "请求的资源不存在",
+        ErrorCode::Conflict => "请求与当前状态冲突",
         ErrorCode::InternalError => "内部服务错误，请联系技术团队",
     }
 }
@@ -68,6 +73,14 @@ pub fn should_degrade(code: ErrorCode) -> bool {
     )
 }
 
+/// 是否为瞬时错误（可通过重试恢复）
+pub fn is_transient(code: ErrorCode) -> bool {
+    matches!(
+        code,
+        ErrorCode::UpstreamTimeout | ErrorCode::UpstreamRateLimit | ErrorCode::UpstreamUnavailable
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -119,4 +132,13 @@ mod tests {
         assert!(!should_degrade(ErrorCode::NoMatch));
         assert!(!should_degrade(ErrorCode::RetrievalFailed));
     }
+
+    #[test]
+    fn test_is_transient() {
+        assert!(is_transient(ErrorCode::UpstreamTimeout));
+        assert!(is_transient(ErrorCode::UpstreamRateLimit));
+        assert!(is_transient(ErrorCode::UpstreamUnavailable));
+        assert!(!is_transient(ErrorCode::UpstreamAuth));
+        assert!(!is_transient(ErrorCode::UpstreamError));
+    }
 }
@@ -0,0 +1,237 @@
+use axum::{
+    Json,
+    extract::{FromRequest, Request, rejection::JsonRejection},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::{Value, json};
+use std::ops::{Deref, DerefMut};
+use uuid::Uuid;
+
+use crate::api::error_code::ErrorCode;
+
+/// 所有接口统一的错误响应体
+#[derive(Debug, Serialize)]
+pub struct ErrorBody {
+    pub error_code: String,
+    pub message: String,
+    pub trace_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<Value>,
+}
+
+/// 接口错误：渲染为 `{error_code, message, trace_id, details}` JSON
+#[derive(Debug)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub code: ErrorCode,
+    pub message: String,
+    pub trace_id: Option<String>,
+    pub details: Option<Value>,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            code,
+            message: message.into(),
+            trace_id: None,
+            details: None,
+        }
+    }
+
+    pub fn invalid_request(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, ErrorCode::InvalidRequest, message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::NOT_FOUND, ErrorCode::NotFound, message)
+    }
+
+    pub fn conflict(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::CONFLICT, ErrorCode::Conflict, message)
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            ErrorCode::InternalError,
+            message,
+        )
+    }
+
+    /// 沿用请求已分配的 trace_id，未设置时渲染时生成新的
+    pub fn with_trace_id(mut self, trace_id: &str) -> Self {
+        self.trace_id = Some(trace_id.to_string());
+        self
+    }
+
+    pub fn with_details(mut self, details: Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let trace_id = self.trace_id.unwrap_or_else(|| Uuid::new_v4().to_string());
+
+        if self.status.is_server_error() {
+            tracing::error!(
+                trace_id = %trace_id,
+                error_code = %self.code,
+                message = %self.message,
+                "request failed"
+            );
+        } else {
+            tracing::debug!(
+                trace_id = %trace_id,
+                error_code = %self.code,
+                message = %self.message,
+                "request rejected"
+            );
+        }
+
+        let body = ErrorBody {
+            error_code: self.code.to_string(),
+            message: self.message,
+            trace_id,
+            details: self.details,
+        };
+        (self.status, Json(body)).into_response()
+    }
+}
+
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        let kind = match &rejection {
+            JsonRejection::JsonDataError(_) => "invalid_data",
+            JsonRejection::JsonSyntaxError(_) => "invalid_syntax",
+            JsonRejection::MissingJsonContentType(_) => "missing_content_type",
+            JsonRejection::BytesRejection(_) => "invalid_body",
+            _ => "unknown",
+        };
+
+        Self::new(
+            rejection.status(),
+            ErrorCode::InvalidRequest,
+            rejection.body_text(),
+        )
+        .with_details(json!({ "rejection": kind }))
+    }
+}
+
+/// `Json` 提取器的包装，解析失败时返回统一错误响应
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ApiJson<T>(pub T);
+
+impl<T, S> FromRequest<S> for ApiJson<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<T>::from_request(req, state).await?;
+        Ok(Self(value))
+    }
+}
+
+impl<T> Deref for ApiJson<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for ApiJson<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+/// 未匹配任何路由时的统一 404 响应
+pub async fn handle_not_found() -> ApiError {
+    ApiError::not_found("route not found")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, body::Body, routing::post};
+    use serde::Deserialize;
+    use tower::ServiceExt;
+
+    #[derive(Deserialize)]
+    struct Payload {
+        #[allow(dead_code)]
+        question: String,
+    }
+
+    async fn echo(_payload: ApiJson<Payload>) -> &'static str {
+        "ok"
+    }
+
+    async fn body_json(response: Response) -> Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn renders_envelope_with_trace_id() {
+        let response = ApiError::conflict("busy")
+            .with_trace_id("t-1")
+            .with_details(json!({ "job_id": "j" }))
+            .into_response();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
+        let body = body_json(response).await;
+        assert_eq!(body["error_code"], "CONFLICT");
+        assert_eq!(body["message"], "busy");
+        assert_eq!(body["trace_id"], "t-1");
+        assert_eq!(body["details"]["job_id"], "j");
+    }
+
+    #[tokio::test]
+    async fn json_rejection_uses_envelope() {
+        let app = Router::new()
+            .route("/", post(echo))
+            .fallback(handle_not_found);
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/")
+                    .header("content-type", "application/json")
+                    .body(Body::from("{\"question\": 1}"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = body_json(response).await;
+        assert_eq!(body["error_code"], "INVALID_REQUEST");
+        assert_eq!(body["details"]["rejection"], "invalid_data");
+        assert!(!body["trace_id"].as_str().unwrap().is_empty());
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/missing")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(body_json(response).await["error_code"], "NOT_FOUND");
+    }
+}
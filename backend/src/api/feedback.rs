@@ -1,7 +1,6 @@
 use axum::{
     Json,
     extract::State,
-    http::StatusCode,
     response::{IntoResponse, Response},
 };
 use chrono::Utc;
@@ -10,7 +9,10 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
-use crate::AppState;
+use crate::{
+    AppState,
+    api::error_response::{ApiError, ApiJson},
+};
 
 /// 反馈评分
 #[derive(Debug, Clone, Copy, Deserialize, Serialize)]
@@ -140,23 +142,25 @@ pub enum FeedbackError {
     InvalidInput(String),
 }
 
+impl From<FeedbackError> for ApiError {
+    fn from(error: FeedbackError) -> Self {
+        match error {
+            FeedbackError::InvalidInput(message) => ApiError::invalid_request(message),
+            FeedbackError::StorageError(_) => ApiError::internal(error.to_string()),
+        }
+    }
+}
+
 impl IntoResponse for FeedbackError {
     fn into_response(self) -> Response {
-        match self {
-            FeedbackError::InvalidInput(_) => {
-                (StatusCode::BAD_REQUEST, self.to_string()).into_response()
-            }
-            FeedbackError::StorageError(_) => {
-                (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()).into_response()
-            }
-        }
+        ApiError::from(self).into_response()
     }
 }
 
 /// 处理 /api/feedback POST 请求
 pub async fn handle_feedback(
     State(state): State<Arc<AppState>>,
-    req: Json<FeedbackRequest>,
+    req: ApiJson<FeedbackRequest>,
 ) -> Result<Json<FeedbackResponse>, FeedbackError> {
     tracing::info!(
         trace_id = %req.trace_id,
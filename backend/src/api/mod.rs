@@ -1,6 +1,10 @@
+pub mod admin;
+pub mod documents;
 pub mod error_code;
 pub mod error_mapping;
+pub mod error_response;
 pub mod feedback;
+pub mod prompts;
 pub mod query;
 pub mod reindex;
 pub mod status;
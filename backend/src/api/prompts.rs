@@ -0,0 +1,24 @@
+use axum::{Json, extract::State};
+use serde::Serialize;
+use std::sync::Arc;
+
+use crate::{AppState, prompt::PromptTemplateInfo};
+
+/// GET /api/prompts 响应
+#[derive(Debug, Serialize)]
+pub struct PromptListResponse {
+    /// 未指定模板时使用的模板
+    pub default_template: String,
+    pub templates: Vec<PromptTemplateInfo>,
+}
+
+/// 处理 /api/prompts GET 请求
+pub async fn handle_list_prompts(State(state): State<Arc<AppState>>) -> Json<PromptListResponse> {
+    // 列表请求顺带触发一次热加载检查，便于编辑模板后立即确认
+    state.prompts.reload_if_changed().await;
+
+    Json(PromptListResponse {
+        default_template: state.config.prompt.default_template.clone(),
+        templates: state.prompts.list().await,
+    })
+}
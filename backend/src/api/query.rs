@@ -1,18 +1,32 @@
 use axum::{
     Json,
     extract::State,
-    http::StatusCode,
     response::{IntoResponse, Response},
 };
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use std::sync::Arc;
 use uuid::Uuid;
 
 use crate::{
     AppState,
-    api::{error_code::ErrorCode, error_mapping},
-    provider::{ChatMessage, InferenceProvider},
-    rag::RetrievedChunk,
+    api::{
+        error_code::ErrorCode,
+        error_mapping,
+        error_response::{ApiError, ApiJson},
+    },
+    cache::{answer::CachedAnswer, embedding::embed_cached},
+    config::QueryLimitsConfig,
+    prompt::PromptTemplate,
+    provider::ServedBy,
+    rag::{
+        RetrievalMode, RetrievalOptions, RetrievedChunk, RetrieverError, RetrieverResult,
+        citation::{Citation, extract_citations},
+        grounding::GroundingReport,
+        limit_per_doc,
+        rewrite::{QueryKind, fuse_results},
+    },
+    vector_store::{ChunkFilter, VectorStoreError},
 };
 
 #[derive(Debug, Deserialize)]
@@ -20,27 +34,128 @@ pub struct QueryRequest {
     pub question: String,
     #[serde(default = "default_top_k")]
     pub top_k: u64,
+    /// 覆盖全局相似度阈值，取值 [0, 1]
+    #[serde(default)]
+    pub score_threshold: Option<f32>,
+    /// 同一文档最多返回的片段数
+    #[serde(default)]
+    pub max_per_doc: Option<usize>,
+    /// 检索方式，缺省时按是否启用查询改写决定
+    #[serde(default)]
+    pub retrieval_mode: Option<RetrievalMode>,
+    /// 跳过语义答案缓存，强制重新生成
+    #[serde(default)]
+    pub bypass_cache: bool,
+    /// 使用的提示词模板名，缺省为配置的默认模板
+    #[serde(default)]
+    pub prompt_template: Option<String>,
+    /// 在响应中返回检索过程说明（改写后的查询、各路命中数）
+    #[serde(default)]
+    pub explain: bool,
+    /// 只在这些文档中检索
+    #[serde(default)]
+    pub doc_ids: Vec<String>,
+    /// 只检索该路径前缀下的文档，如 `runbooks/`
+    #[serde(default)]
+    pub path_prefix: Option<String>,
 }
 
 fn default_top_k() -> u64 {
     6
 }
 
-#[derive(Debug, Serialize)]
+/// `doc_ids` 过滤条件的最大数量
+const MAX_FILTER_DOC_IDS: usize = 100;
+
+impl QueryRequest {
+    /// 校验请求参数是否在服务端允许的范围内
+    pub fn validate(&self, limits: &QueryLimitsConfig) -> QueryResult<()> {
+        let invalid = |field: &'static str, message: String| {
+            Err(QueryError::InvalidRequest { field, message })
+        };
+
+        if self.question.trim().is_empty() {
+            return invalid("question", "question must not be empty".to_string());
+        }
+        let question_chars = self.question.chars().count();
+        if question_chars > limits.max_question_chars {
+            return invalid(
+                "question",
+                format!(
+                    "question is {} characters, maximum is {}",
+                    question_chars, limits.max_question_chars
+                ),
+            );
+        }
+        if !(1..=limits.max_top_k).contains(&self.top_k) {
+            return invalid(
+                "top_k",
+                format!("top_k must be between 1 and {}", limits.max_top_k),
+            );
+        }
+        if let Some(threshold) = self.score_threshold
+            && !(0.0..=1.0).contains(&threshold)
+        {
+            return invalid(
+                "score_threshold",
+                "score_threshold must be between 0 and 1".to_string(),
+            );
+        }
+        if let Some(max_per_doc) = self.max_per_doc
+            && !(1..=limits.max_top_k as usize).contains(&max_per_doc)
+        {
+            return invalid(
+                "max_per_doc",
+                format!("max_per_doc must be between 1 and {}", limits.max_top_k),
+            );
+        }
+        if self.doc_ids.len() > MAX_FILTER_DOC_IDS {
+            return invalid(
+                "doc_ids",
+                format!("doc_ids must contain at most {MAX_FILTER_DOC_IDS} entries"),
+            );
+        }
+        if self.path_prefix.as_deref().is_some_and(str::is_empty) {
+            return invalid("path_prefix", "path_prefix must not be empty".to_string());
+        }
+
+        Ok(())
+    }
+
+    fn retrieval_options(&self) -> RetrievalOptions {
+        RetrievalOptions {
+            top_k: Some(self.top_k),
+            score_threshold: self.score_threshold,
+            max_per_doc: self.max_per_doc,
+            filter: ChunkFilter {
+                doc_ids: self.doc_ids.clone(),
+                path_prefix: self.path_prefix.clone(),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct QuerySource {
+    /// 对应的知识片段 ID
+    pub chunk_id: String,
     pub title: String,
     pub path: String,
     pub snippet: String,
     pub score: f32,
+    /// 答案中是否通过 `[来源N]` 引用了该来源
+    pub cited: bool,
 }
 
 impl From<RetrievedChunk> for QuerySource {
     fn from(chunk: RetrievedChunk) -> Self {
         Self {
+            chunk_id: chunk.metadata.chunk_id,
             title: chunk.metadata.title_path.clone(),
             path: chunk.metadata.path,
             snippet: chunk.snippet,
             score: chunk.score,
+            cited: false,
         }
     }
 }
@@ -49,12 +164,45 @@ impl From<RetrievedChunk> for QuerySource {
 pub struct QueryResponse {
     pub answer: String,
     pub sources: Vec<QuerySource>,
+    /// 答案中的引用，逐条对应到具体知识片段
+    pub citations: Vec<Citation>,
+    /// 答案引用了但检索结果中不存在的来源编号
+    pub invalid_citations: Vec<usize>,
+    /// 答案事实性校验结果（启用校验时提供）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grounding: Option<GroundingReport>,
     pub degraded: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error_code: Option<String>,
+    /// 检索过程说明（请求 `explain: true` 且未命中缓存时提供）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explain: Option<QueryExplain>,
+    /// 实际生成答案的提供方与模型
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub served_by: Option<ServedBy>,
+    /// 是否命中语义答案缓存
+    pub cached: bool,
     pub trace_id: String,
 }
 
+/// 一路检索查询及其命中数
+#[derive(Debug, Serialize)]
+pub struct ExplainedQuery {
+    pub kind: QueryKind,
+    pub text: String,
+    pub hits: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QueryExplain {
+    /// 实际用于检索的查询，第一条为原始问题
+    pub queries: Vec<ExplainedQuery>,
+    /// 查询改写失败，仅使用原始问题检索
+    pub rewrite_fallback: bool,
+    /// 融合后的片段数
+    pub fused_hits: usize,
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum QueryError {
     #[error("Provider error: {0}")]
@@ -62,118 +210,198 @@ pub enum QueryError {
     #[error("Retrieval error: {0}")]
     RetrievalError(#[from] crate::rag::RetrieverError),
 
+    #[error("Invalid request field `{field}`: {message}")]
+    InvalidRequest {
+        field: &'static str,
+        message: String,
+    },
+
     #[error("Internal error: {0}")]
     InternalError(String),
 }
 
 pub type QueryResult<T> = Result<T, QueryError>;
 
+impl From<QueryError> for ApiError {
+    fn from(error: QueryError) -> Self {
+        match error {
+            QueryError::InvalidRequest { field, message } => {
+                ApiError::invalid_request(message).with_details(json!({ "field": field }))
+            }
+            other => ApiError::internal(other.to_string()),
+        }
+    }
+}
+
 impl IntoResponse for QueryError {
     fn into_response(self) -> Response {
-        (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()).into_response()
+        ApiError::from(self).into_response()
     }
 }
 
 const CHAT_TEMPERATURE: f32 = 0.2;
 const MAX_TOKENS: u32 = 65535;
 
-const SYSTEM_PROMPT: &str = r#"
-你是一个广告引擎维优专家的智能助手。
-
-## 重要规则
-
-1. **仅基于提供的参考资料回答问题**
-   - 如果参考资料中没有足够的信息，请明确说明"根据现有资料，我不确定"
-   - **绝对不要编造或推测答案**
-
-2. **提供可操作的排查建议**
-   - 针对故障问题，给出步骤化的排查建议
-   - 每个建议应基于参考资料中的实际内容
-
-3. **答案结构清晰**
-   - 直接回答问题
-   - 如有多个解决方案，分别说明
-   - 引用来源时要准确
-
-4. **语言风格**
-   - 使用专业但易懂的中文
-   - 避免冗长，保持简洁
-   - 技术术语保持一致
-
-## 回答格式
-
-根据参考资料，问题的答案是：
-[答案内容]
-
-相关参考：
-- [来源1的标题]
-- [来源2的标题]
-"#;
-
 pub async fn handle_query(
     State(state): State<Arc<AppState>>,
-    req: Json<QueryRequest>,
-) -> QueryResult<Json<QueryResponse>> {
+    req: ApiJson<QueryRequest>,
+) -> Result<Json<QueryResponse>, ApiError> {
     let trace_id = Uuid::new_v4().to_string();
+    req.validate(&state.config.query_limits)
+        .map_err(|e| ApiError::from(e).with_trace_id(&trace_id))?;
+    if req.retrieval_mode == Some(RetrievalMode::MultiQuery) && !state.rewriter.is_enabled() {
+        return Err(ApiError::from(QueryError::InvalidRequest {
+            field: "retrieval_mode",
+            message: "multi_query requires QUERY_REWRITE_ENABLED=true".to_string(),
+        })
+        .with_trace_id(&trace_id));
+    }
     let question = &req.question;
+    let retrieval_options = req.retrieval_options();
+    let retrieval_mode = req
+        .retrieval_mode
+        .unwrap_or(if state.rewriter.is_enabled() {
+            RetrievalMode::MultiQuery
+        } else {
+            RetrievalMode::Vector
+        });
+    let template = state
+        .prompts
+        .get(req.prompt_template.as_deref())
+        .await
+        .map_err(|e| {
+            ApiError::from(QueryError::InvalidRequest {
+                field: "prompt_template",
+                message: e.to_string(),
+            })
+            .with_trace_id(&trace_id)
+        })?;
 
     tracing::info!(
         trace_id = %trace_id,
         question = %question,
         top_k = req.top_k,
+        score_threshold = ?req.score_threshold,
+        max_per_doc = ?req.max_per_doc,
+        retrieval_mode = retrieval_mode.as_str(),
+        prompt_template = %template.name,
+        prompt_version = %template.version,
         "received query request"
     );
 
     // Step 1: Embed query
-    let query_vector = match state.provider.embed(question).await {
-        Ok(vec) => vec,
-        Err(e) => {
-            let error_code = error_mapping::map_provider_error(&e);
-            tracing::warn!(
-                trace_id = %trace_id,
-                error_code = %error_code,
-                error = %e,
-                "embedding failed"
-            );
-            return Ok(build_degraded_response(&trace_id, error_code, vec![]));
-        }
-    };
+    let query_vector =
+        match embed_cached(state.provider.as_ref(), &state.embedding_cache, question).await {
+            Ok(vec) => vec,
+            Err(e) => {
+                let error_code = error_mapping::map_provider_error(&e);
+                tracing::warn!(
+                    trace_id = %trace_id,
+                    error_code = %error_code,
+                    error = %e,
+                    "embedding failed"
+                );
+                return Ok(build_degraded_response(
+                    &template,
+                    &trace_id,
+                    error_code,
+                    vec![],
+                ));
+            }
+        };
+
+    // Step 2: Reuse a cached answer for a semantically identical question
+    let index_version = state.job_manager.index_version();
+    let use_answer_cache = state.answer_cache.is_enabled() && !req.bypass_cache;
+    let cache_variant = format!(
+        "top_k={}|threshold={:?}|max_per_doc={:?}|mode={}|prompt={}@{}|doc_ids={:?}|path_prefix={:?}",
+        req.top_k,
+        req.score_threshold,
+        req.max_per_doc,
+        retrieval_mode.as_str(),
+        template.name,
+        template.version,
+        req.doc_ids,
+        req.path_prefix
+    );
+    if use_answer_cache
+        && let Some((cached, similarity)) = state
+            .answer_cache
+            .lookup(&query_vector, &cache_variant, index_version)
+            .await
+    {
+        tracing::info!(
+            trace_id = %trace_id,
+            similarity,
+            cached_question = %cached.question,
+            "answer cache hit"
+        );
+
+        let (sources, citations, invalid_citations) =
+            annotate_citations(&cached.answer, cached.sources);
+
+        return Ok(Json(QueryResponse {
+            answer: cached.answer,
+            sources,
+            citations,
+            invalid_citations,
+            grounding: cached.grounding,
+            degraded: false,
+            error_code: None,
+            explain: None,
+            served_by: cached.served_by,
+            cached: true,
+            trace_id,
+        }));
+    }
 
-    // Step 2: Retrieve relevant chunks
-    let retrieved_chunks = state
-        .retriever
-        .retrieve(query_vector, Some(req.top_k))
-        .await;
+    // Step 3: Retrieve relevant chunks (with optional query rewriting)
+    let (retrieved_chunks, explain) = retrieve_chunks(
+        &state,
+        question,
+        query_vector.clone(),
+        &retrieval_options,
+        retrieval_mode,
+        &trace_id,
+    )
+    .await;
+    let explain = req.explain.then_some(explain);
 
     let chunks = match retrieved_chunks {
         Ok(chunks) if !chunks.is_empty() => chunks,
-        Ok(_) => return Ok(build_no_match_response(&trace_id)),
+        Ok(_) => {
+            let mut response = build_no_match_response(&template, &trace_id);
+            response.explain = explain;
+            return Ok(response);
+        }
         Err(e) => {
             tracing::warn!(
                 trace_id = %trace_id,
                 error = %e,
                 "retrieval failed"
             );
-            return Ok(build_degraded_response(
-                &trace_id,
-                ErrorCode::RetrievalFailed,
-                vec![],
-            ));
+            let error_code = match e {
+                RetrieverError::VectorStoreError(VectorStoreError::DimensionMismatch {
+                    ..
+                }) => ErrorCode::IndexSpecMismatch,
+                _ => ErrorCode::RetrievalFailed,
+            };
+            let mut response = build_degraded_response(&template, &trace_id, error_code, vec![]);
+            response.explain = explain;
+            return Ok(response);
         }
     };
 
-    // Step 3: Build context from chunks
-    let context = build_context(&chunks);
+    // Step 4: Build prompt from template and chunks
+    let messages = template.build_messages(question, &chunks);
 
-    // Step 4: Generate answer using chat
-    let messages = build_messages(question, &context);
-
-    let answer = match state
+    // Step 5: Generate answer using chat
+    let (answer, served_by) = match state
         .provider
-        .chat(messages, CHAT_TEMPERATURE, MAX_TOKENS)
+        .chat_routed(messages, CHAT_TEMPERATURE, MAX_TOKENS)
         .await
     {
-        Ok(answer) => answer,
+        Ok(result) => result,
         Err(e) => {
             let error_code = error_mapping::map_provider_error(&e);
             tracing::error!(
@@ -188,70 +416,229 @@ pub async fn handle_query(
 
             if error_mapping::should_degrade(error_code) {
                 return Ok(build_degraded_with_sources_response(
-                    &trace_id, error_code, sources,
+                    &template, &trace_id, error_code, sources,
                 ));
             } else {
-                return Ok(build_degraded_response(&trace_id, error_code, vec![]));
+                return Ok(build_degraded_response(
+                    &template,
+                    &trace_id,
+                    error_code,
+                    vec![],
+                ));
             }
         }
     };
 
+    // Step 6: Verify the answer is grounded in the retrieved chunks
+    let grounding = if state.grounding.is_enabled() {
+        match state.grounding.check(&answer, &chunks).await {
+            Ok(report) => Some(report),
+            Err(e) => {
+                tracing::warn!(
+                    trace_id = %trace_id,
+                    error = %e,
+                    "grounding check failed, skipping"
+                );
+                None
+            }
+        }
+    } else {
+        None
+    };
+    let ungrounded = grounding
+        .as_ref()
+        .is_some_and(|report| state.grounding.should_degrade(report));
+
+    if let Some(report) = &grounding {
+        tracing::info!(
+            trace_id = %trace_id,
+            score = report.score,
+            unsupported = report.unsupported.len(),
+            ungrounded,
+            "grounding check completed"
+        );
+    }
+
+    let sources: Vec<QuerySource> = chunks.into_iter().map(QuerySource::from).collect();
+    let (sources, citations, invalid_citations) = annotate_citations(&answer, sources);
+
+    if !invalid_citations.is_empty() {
+        tracing::warn!(
+            trace_id = %trace_id,
+            invalid_citations = ?invalid_citations,
+            "answer cites nonexistent sources"
+        );
+    }
+
     tracing::info!(
         trace_id = %trace_id,
-        sources_count = chunks.len(),
+        sources_count = sources.len(),
+        citations_count = citations.len(),
+        provider = %served_by.provider,
+        model = %served_by.model,
+        prompt_template = %template.name,
+        prompt_version = %template.version,
         "query completed successfully"
     );
 
+    if use_answer_cache && !ungrounded {
+        state
+            .answer_cache
+            .insert(
+                query_vector,
+                &cache_variant,
+                index_version,
+                CachedAnswer {
+                    question: question.clone(),
+                    answer: answer.clone(),
+                    sources: sources.clone(),
+                    served_by: Some(served_by.clone()),
+                    grounding: grounding.clone(),
+                },
+            )
+            .await;
+    }
+
     Ok(Json(QueryResponse {
         answer,
-        sources: chunks.into_iter().map(QuerySource::from).collect(),
-        degraded: false,
-        error_code: None,
+        sources,
+        citations,
+        invalid_citations,
+        grounding,
+        degraded: ungrounded,
+        error_code: ungrounded.then(|| ErrorCode::UngroundedAnswer.to_string()),
+        explain,
+        served_by: Some(served_by),
+        cached: false,
         trace_id,
     }))
 }
 
-fn build_context(chunks: &[RetrievedChunk]) -> String {
-    chunks
-        .iter()
-        .enumerate()
-        .map(|(i, chunk)| {
-            format!(
-                "[来源{}] {}\n路径: {}\n内容: {}\n",
-                i + 1,
-                chunk.metadata.title_path,
-                chunk.metadata.path,
-                chunk.snippet
-            )
-        })
-        .collect::<Vec<_>>()
-        .join("\n\n")
+/// 检索相关片段；多路检索模式下对原始问题与各改写查询分别检索并融合，
+/// 改写失败时退回原始问题
+async fn retrieve_chunks(
+    state: &AppState,
+    question: &str,
+    query_vector: Vec<f32>,
+    options: &RetrievalOptions,
+    mode: RetrievalMode,
+    trace_id: &str,
+) -> (RetrieverResult<Vec<RetrievedChunk>>, QueryExplain) {
+    let original = state.retriever.retrieve(query_vector, options).await;
+    let original_hits = original.as_ref().map_or(0, Vec::len);
+    let mut explain = QueryExplain {
+        queries: vec![ExplainedQuery {
+            kind: QueryKind::Original,
+            text: question.to_string(),
+            hits: original_hits,
+        }],
+        rewrite_fallback: false,
+        fused_hits: original_hits,
+    };
+
+    if mode == RetrievalMode::Vector {
+        return (original, explain);
+    }
+
+    let rewrites = match state.rewriter.rewrite(question).await {
+        Ok(rewrites) => rewrites,
+        Err(e) => {
+            tracing::warn!(
+                trace_id = %trace_id,
+                error = %e,
+                "query rewrite failed, falling back to raw question"
+            );
+            explain.rewrite_fallback = true;
+            return (original, explain);
+        }
+    };
+
+    let (mut result_lists, original_error) = match original {
+        Ok(chunks) => (vec![chunks], None),
+        Err(e) => (vec![], Some(e)),
+    };
+
+    for query in rewrites {
+        let hits = match embed_cached(state.provider.as_ref(), &state.embedding_cache, &query.text)
+            .await
+        {
+            Ok(vector) => state
+                .retriever
+                .retrieve(vector, options)
+                .await
+                .unwrap_or_else(|e| {
+                    tracing::debug!(trace_id = %trace_id, error = %e, "rewritten query retrieval failed");
+                    vec![]
+                }),
+            Err(e) => {
+                tracing::warn!(trace_id = %trace_id, error = %e, "rewritten query embedding failed");
+                vec![]
+            }
+        };
+
+        explain.queries.push(ExplainedQuery {
+            kind: query.kind,
+            text: query.text,
+            hits: hits.len(),
+        });
+        result_lists.push(hits);
+    }
+
+    let top_k = options.top_k.unwrap_or(default_top_k()) as usize;
+    let fused = fuse_results(result_lists, state.rewriter.rrf_k(), usize::MAX);
+    let mut fused = match options.max_per_doc {
+        Some(max_per_doc) => limit_per_doc(fused, max_per_doc),
+        None => fused,
+    };
+    fused.truncate(top_k);
+    explain.fused_hits = fused.len();
+
+    tracing::info!(
+        trace_id = %trace_id,
+        queries = explain.queries.len(),
+        fused_hits = explain.fused_hits,
+        "multi-query retrieval completed"
+    );
+
+    match original_error {
+        Some(e) if fused.is_empty() => (Err(e), explain),
+        _ => (Ok(fused), explain),
+    }
 }
 
-fn build_messages(question: &str, context: &str) -> Vec<ChatMessage> {
-    vec![
-        ChatMessage {
-            role: "system".to_string(),
-            content: SYSTEM_PROMPT.to_string(),
-        },
-        ChatMessage {
-            role: "user".to_string(),
-            content: format!("问题: {}\n\n参考资料:\n{}", question, context),
-        },
-    ]
+/// 解析答案中的引用并标记被引用的来源
+fn annotate_citations(
+    answer: &str,
+    mut sources: Vec<QuerySource>,
+) -> (Vec<QuerySource>, Vec<Citation>, Vec<usize>) {
+    let chunk_ids: Vec<&str> = sources.iter().map(|s| s.chunk_id.as_str()).collect();
+    let report = extract_citations(answer, &chunk_ids);
+
+    for (idx, source) in sources.iter_mut().enumerate() {
+        source.cited = report.cited_sources.contains(&idx);
+    }
+
+    (sources, report.citations, report.invalid_numbers)
 }
 
-fn build_no_match_response(trace_id: &str) -> Json<QueryResponse> {
+fn build_no_match_response(template: &PromptTemplate, trace_id: &str) -> Json<QueryResponse> {
     Json(QueryResponse {
-        answer: "根据现有知识库，我没有找到相关的参考资料来回答这个问题。请尝试更具体的问题描述，或者联系技术团队获取更多帮助。".to_string(),
+        answer: template.no_match_answer.clone(),
         sources: vec![],
+        citations: vec![],
+        invalid_citations: vec![],
+        grounding: None,
         degraded: true,
         error_code: Some(ErrorCode::NoMatch.to_string()),
+        explain: None,
+        served_by: None,
+        cached: false,
         trace_id: trace_id.to_string(),
     })
 }
 
 fn build_degraded_response(
+    template: &PromptTemplate,
     trace_id: &str,
     error_code: ErrorCode,
     sources: Vec<QuerySource>,
@@ -259,41 +646,138 @@ fn build_degraded_response(
     let description = error_mapping::get_error_description(error_code);
 
     Json(QueryResponse {
-        answer: format!("服务暂时不可用：{}。", description),
+        answer: template.degraded_answer(description),
         sources,
+        citations: vec![],
+        invalid_citations: vec![],
+        grounding: None,
         degraded: true,
         error_code: Some(error_code.to_string()),
+        explain: None,
+        served_by: None,
+        cached: false,
         trace_id: trace_id.to_string(),
     })
 }
 
 fn build_degraded_with_sources_response(
+    template: &PromptTemplate,
     trace_id: &str,
     error_code: ErrorCode,
     sources: Vec<QuerySource>,
 ) -> Json<QueryResponse> {
     let description = error_mapping::get_error_description(error_code);
-    let sources_text = if sources.is_empty() {
-        "没有找到相关的参考文档。".to_string()
-    } else {
-        format!(
-            "以下是一些相关的参考文档，您可以自行查阅：\n{}",
-            sources
-                .iter()
-                .map(|s| format!("- [{}] {}", s.title, s.path))
-                .collect::<Vec<_>>()
-                .join("\n")
-        )
-    };
+    let source_refs: Vec<(&str, &str)> = sources
+        .iter()
+        .map(|s| (s.title.as_str(), s.path.as_str()))
+        .collect();
+    let answer = template.degraded_with_sources_answer(description, &source_refs);
 
     Json(QueryResponse {
-        answer: format!(
-            "AI 生成服务暂时不可用：{}。\n\n{}",
-            description, sources_text
-        ),
+        answer,
         sources,
+        citations: vec![],
+        invalid_citations: vec![],
+        grounding: None,
         degraded: true,
         error_code: Some(error_code.to_string()),
+        explain: None,
+        served_by: None,
+        cached: false,
         trace_id: trace_id.to_string(),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+
+    fn limits() -> QueryLimitsConfig {
+        QueryLimitsConfig {
+            max_top_k: 20,
+            max_question_chars: 10,
+        }
+    }
+
+    fn request(body: serde_json::Value) -> QueryRequest {
+        serde_json::from_value(body).expect("request should deserialize")
+    }
+
+    fn invalid_field(req: &QueryRequest) -> Option<&'static str> {
+        match req.validate(&limits()) {
+            Err(QueryError::InvalidRequest { field, .. }) => Some(field),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn accepts_defaults_and_in_range_values() {
+        let req = request(json!({
+            "question": "CPM 暴涨",
+            "top_k": 20,
+            "score_threshold": 0.5,
+            "max_per_doc": 2,
+            "retrieval_mode": "multi_query",
+            "path_prefix": "runbooks/"
+        }));
+        assert!(req.validate(&limits()).is_ok());
+        assert_eq!(req.retrieval_mode, Some(RetrievalMode::MultiQuery));
+        assert_eq!(
+            req.retrieval_options().filter.path_prefix.as_deref(),
+            Some("runbooks/")
+        );
+        assert!(
+            request(json!({ "question": "q" }))
+                .validate(&limits())
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn rejects_out_of_range_values() {
+        assert_eq!(
+            invalid_field(&request(json!({ "question": "  " }))),
+            Some("question")
+        );
+        assert_eq!(
+            invalid_field(&request(json!({ "question": "一二三四五六七八九十十一" }))),
+            Some("question")
+        );
+        assert_eq!(
+            invalid_field(&request(json!({ "question": "q", "top_k": 10000 }))),
+            Some("top_k")
+        );
+        assert_eq!(
+            invalid_field(&request(json!({ "question": "q", "top_k": 0 }))),
+            Some("top_k")
+        );
+        assert_eq!(
+            invalid_field(&request(json!({ "question": "q", "score_threshold": 1.5 }))),
+            Some("score_threshold")
+        );
+        assert_eq!(
+            invalid_field(&request(json!({ "question": "q", "max_per_doc": 0 }))),
+            Some("max_per_doc")
+        );
+        assert_eq!(
+            invalid_field(&request(json!({ "question": "q", "path_prefix": "" }))),
+            Some("path_prefix")
+        );
+        let doc_ids: Vec<String> = (0..=MAX_FILTER_DOC_IDS).map(|n| n.to_string()).collect();
+        assert_eq!(
+            invalid_field(&request(json!({ "question": "q", "doc_ids": doc_ids }))),
+            Some("doc_ids")
+        );
+    }
+
+    #[test]
+    fn invalid_request_renders_400_with_structured_body() {
+        let response = QueryError::InvalidRequest {
+            field: "top_k",
+            message: "too large".to_string(),
+        }
+        .into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
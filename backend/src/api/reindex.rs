@@ -1,20 +1,31 @@
 use crate::{
     AppState,
-    indexer::{IndexResult, IndexerError},
+    api::error_response::{ApiError, ApiJson},
+    config::JobHistoryConfig,
+    indexer::{IndexControl, IndexProgress, IndexResult, IndexScope, IndexerError},
 };
 use axum::{
     Json,
-    extract::State,
-    http::StatusCode,
+    extract::{Path, State},
     response::{IntoResponse, Response},
 };
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::{
+    fs,
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+};
+use tokio::sync::{Mutex, RwLock, RwLockWriteGuard};
 use uuid::Uuid;
 
+/// 未配置时保留的历史任务数
+const DEFAULT_MAX_HISTORY: usize = 50;
+
 /// 索引任务状态
-#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum JobStatus {
     /// 任务运行中
@@ -23,10 +34,12 @@ pub enum JobStatus {
     Completed,
     /// 任务失败
     Failed,
+    /// 任务被取消
+    Cancelled,
 }
 
 /// 索引任务信息
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JobInfo {
     /// 任务 ID
     pub job_id: String,
@@ -36,30 +49,160 @@ pub struct JobInfo {
     pub started_at: String,
     /// 结束时间
     pub ended_at: Option<String>,
+    /// 进度（运行中为实时快照，结束后为最终值）
+    #[serde(default)]
+    pub progress: Option<IndexProgress>,
+    /// 是否已请求取消
+    #[serde(default)]
+    pub cancel_requested: bool,
     /// 索引结果
     pub result: Option<IndexResult>,
     /// 错误信息
     pub error: Option<String>,
 }
 
-/// 索引任务管理器
+/// 正在运行的任务及其控制句柄
+#[derive(Clone)]
+struct RunningJob {
+    job_id: String,
+    control: IndexControl,
+}
+
+/// 表维护占用的写入槽，释放后才能开始新的索引任务
+pub(crate) struct MaintenanceSlot {
+    maintenance: Arc<AtomicBool>,
+}
+
+impl Drop for MaintenanceSlot {
+    fn drop(&mut self) {
+        self.maintenance.store(false, Ordering::Release);
+    }
+}
+
+/// 索引任务管理器：同一时间只允许一个任务运行，历史任务持久化到 JSON 文件
 #[derive(Clone)]
 pub struct JobManager {
-    current_job: Arc<RwLock<Option<JobInfo>>>,
+    /// 按开始时间升序
+    jobs: Arc<RwLock<Vec<JobInfo>>>,
+    running: Arc<RwLock<Option<RunningJob>>>,
+    /// 表维护正在进行，与索引任务互斥
+    maintenance: Arc<AtomicBool>,
     last_index_time: Arc<RwLock<Option<String>>>,
+    index_version: Arc<AtomicU64>,
+    history_path: Option<PathBuf>,
+    /// 串行化历史文件写入，保证快照按修改顺序落盘
+    persist_lock: Arc<Mutex<()>>,
+    max_history: usize,
 }
 
 impl JobManager {
+    /// 仅在内存中保留历史
     pub fn new() -> Self {
+        Self::from_jobs(Vec::new(), None, DEFAULT_MAX_HISTORY)
+    }
+
+    /// 从历史文件恢复；上次进程退出时仍在运行的任务标记为失败
+    pub fn with_history(config: &JobHistoryConfig) -> Self {
+        let path = PathBuf::from(&config.path);
+        let mut jobs = load_history(&path);
+
+        for job in jobs
+            .iter_mut()
+            .filter(|job| job.status == JobStatus::Running)
+        {
+            job.status = JobStatus::Failed;
+            job.error = Some("interrupted by service restart".to_string());
+        }
+
+        Self::from_jobs(jobs, Some(path), config.max_entries.max(1))
+    }
+
+    fn from_jobs(
+        mut jobs: Vec<JobInfo>,
+        history_path: Option<PathBuf>,
+        max_history: usize,
+    ) -> Self {
+        let excess = jobs.len().saturating_sub(max_history);
+        jobs.drain(..excess);
+
+        let last_index_time = jobs
+            .iter()
+            .rev()
+            .find(|job| job.status == JobStatus::Completed)
+            .and_then(|job| job.ended_at.clone());
+
         Self {
-            current_job: Arc::new(RwLock::new(None)),
-            last_index_time: Arc::new(RwLock::new(None)),
+            jobs: Arc::new(RwLock::new(jobs)),
+            running: Arc::new(RwLock::new(None)),
+            maintenance: Arc::new(AtomicBool::new(false)),
+            last_index_time: Arc::new(RwLock::new(last_index_time)),
+            index_version: Arc::new(AtomicU64::new(0)),
+            history_path,
+            persist_lock: Arc::new(Mutex::new(())),
+            max_history,
         }
     }
 
-    /// 获取当前任务信息
+    /// 当前索引版本，每次索引内容变化后递增
+    pub fn index_version(&self) -> u64 {
+        self.index_version.load(Ordering::Relaxed)
+    }
+
+    /// 标记索引内容已变化
+    pub fn mark_index_changed(&self) {
+        self.index_version.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 获取最近一次任务信息
     pub async fn get_current_job(&self) -> Option<JobInfo> {
-        self.current_job.read().await.as_ref().cloned()
+        let job = self.jobs.read().await.last().cloned()?;
+        Some(self.with_live_progress(job).await)
+    }
+
+    /// 历史任务，最新的在前
+    pub async fn list_jobs(&self) -> Vec<JobInfo> {
+        let jobs: Vec<JobInfo> = self.jobs.read().await.iter().rev().cloned().collect();
+        let mut listed = Vec::with_capacity(jobs.len());
+        for job in jobs {
+            listed.push(self.with_live_progress(job).await);
+        }
+        listed
+    }
+
+    pub async fn get_job(&self, job_id: &str) -> Option<JobInfo> {
+        let job = self
+            .jobs
+            .read()
+            .await
+            .iter()
+            .find(|job| job.job_id == job_id)
+            .cloned()?;
+        Some(self.with_live_progress(job).await)
+    }
+
+    /// 请求取消运行中的任务，索引在处理下一个文件前停止
+    pub async fn cancel_job(&self, job_id: &str) -> Result<JobInfo, ReindexError> {
+        let running = self.running.read().await.clone();
+        match running {
+            Some(running) if running.job_id == job_id => {
+                running.control.cancel();
+                let job = self
+                    .update_job(job_id, |job| job.cancel_requested = true)
+                    .await
+                    .ok_or_else(|| ReindexError::JobNotFound(job_id.to_string()))?;
+                tracing::info!(job_id = %job_id, "reindex job cancellation requested");
+                Ok(self.with_live_progress(job).await)
+            }
+            _ if self.get_job(job_id).await.is_some() => {
+                Err(ReindexError::JobNotRunning(job_id.to_string()))
+            }
+            _ => Err(ReindexError::JobNotFound(job_id.to_string())),
+        }
+    }
+
+    /// 是否有任务正在运行
+    pub async fn is_running(&self) -> bool {
+        self.running.read().await.is_some()
     }
 
     /// 获取最近一次成功索引时间
@@ -67,66 +210,175 @@ impl JobManager {
         self.last_index_time.read().await.clone()
     }
 
-    /// 开始新任务
-    async fn start_job(&self) -> Result<String, ReindexError> {
-        let mut job = self.current_job.write().await;
-
-        if matches!(
-            job.as_ref(),
-            Some(JobInfo {
-                status: JobStatus::Running,
-                ..
-            })
-        ) {
+    /// 开始新任务，返回任务 ID 与进度/取消句柄
+    pub(crate) async fn start_job(&self) -> Result<(String, IndexControl), ReindexError> {
+        let mut running = self.running.write().await;
+        if running.is_some() {
             return Err(ReindexError::JobInProgress);
         }
+        if self.maintenance.load(Ordering::Acquire) {
+            return Err(ReindexError::MaintenanceInProgress);
+        }
 
         let job_id = Uuid::new_v4().to_string();
-        let started_at = chrono::Utc::now().to_rfc3339();
+        let control = IndexControl::default();
+        *running = Some(RunningJob {
+            job_id: job_id.clone(),
+            control: control.clone(),
+        });
 
-        *job = Some(JobInfo {
+        let mut jobs = self.jobs.write().await;
+        jobs.push(JobInfo {
             job_id: job_id.clone(),
             status: JobStatus::Running,
-            started_at,
+            started_at: chrono::Utc::now().to_rfc3339(),
             ended_at: None,
+            progress: Some(IndexProgress::default()),
+            cancel_requested: false,
             result: None,
             error: None,
         });
+        let excess = jobs.len().saturating_sub(self.max_history);
+        jobs.drain(..excess);
+        drop(running);
+        self.persist(jobs).await;
 
-        Ok(job_id)
+        Ok((job_id, control))
     }
 
-    /// 完成任务
-    async fn complete_job(&self, result: IndexResult) {
+    /// 为表维护占用写入槽：有索引任务运行时失败，持有期间 `start_job` 拒绝新任务
+    pub(crate) async fn reserve_maintenance(&self) -> Result<MaintenanceSlot, ReindexError> {
+        // 持有读锁完成检查与置位，`start_job` 在写锁下检查标记，两者不会交错
+        let running = self.running.read().await;
+        if running.is_some() {
+            return Err(ReindexError::JobInProgress);
+        }
+        self.maintenance
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .map_err(|_| ReindexError::MaintenanceInProgress)?;
+        Ok(MaintenanceSlot {
+            maintenance: self.maintenance.clone(),
+        })
+    }
+
+    /// 记录任务结束（完成、失败或取消）；部分文件可能已写入，均视为索引变化。
+    /// 被取消的任务同样保留已处理文件的结果
+    pub(crate) async fn finish_job(
+        &self,
+        job_id: &str,
+        outcome: Result<IndexResult, IndexerError>,
+    ) {
+        let progress = self
+            .running
+            .write()
+            .await
+            .take()
+            .map(|running| running.control.progress());
         let ended_at = chrono::Utc::now().to_rfc3339();
-        let mut job = self.current_job.write().await;
 
-        if let Some(info) = job.as_mut() {
-            info.status = JobStatus::Completed;
-            info.ended_at = Some(ended_at.clone());
-            info.result = Some(result);
-            info.error = None;
+        let completed = outcome.as_ref().is_ok_and(|result| !result.cancelled);
+        self.update_job(job_id, |job| {
+            job.ended_at = Some(ended_at.clone());
+            if let Some(mut progress) = progress {
+                progress.current_file = None;
+                progress.eta_secs = None;
+                job.progress = Some(progress);
+            }
+            match outcome {
+                Ok(result) => {
+                    job.status = if result.cancelled {
+                        JobStatus::Cancelled
+                    } else {
+                        JobStatus::Completed
+                    };
+                    job.result = Some(result);
+                    job.error = None;
+                }
+                Err(e) => {
+                    job.status = JobStatus::Failed;
+                    job.error = Some(e.to_string());
+                }
+            }
+        })
+        .await;
+
+        if completed {
             *self.last_index_time.write().await = Some(ended_at);
         }
+        self.mark_index_changed();
     }
 
-    /// 任务失败
-    async fn fail_job(&self, error: String) {
-        let mut job = self.current_job.write().await;
+    async fn update_job(&self, job_id: &str, f: impl FnOnce(&mut JobInfo)) -> Option<JobInfo> {
+        let mut jobs = self.jobs.write().await;
+        let job = jobs.iter_mut().find(|job| job.job_id == job_id)?;
+        f(job);
+        let updated = job.clone();
+        self.persist(jobs).await;
+        Some(updated)
+    }
 
-        if let Some(info) = job.as_mut() {
-            info.status = JobStatus::Failed;
-            info.ended_at = Some(chrono::Utc::now().to_rfc3339());
-            info.error = Some(error);
+    async fn with_live_progress(&self, mut job: JobInfo) -> JobInfo {
+        if job.status == JobStatus::Running
+            && let Some(running) = self.running.read().await.as_ref()
+            && running.job_id == job.job_id
+        {
+            job.progress = Some(running.control.progress());
         }
+        job
     }
 
-    /// 清除任务
-    pub async fn clear_job(&self) {
-        *self.current_job.write().await = None;
+    /// 在持有任务列表写锁时序列化历史，释放锁后再在阻塞线程中写盘
+    async fn persist(&self, jobs: RwLockWriteGuard<'_, Vec<JobInfo>>) {
+        let Some(path) = self.history_path.clone() else {
+            return;
+        };
+        let bytes = match serde_json::to_vec(&*jobs) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                tracing::warn!(error = %e, "failed to serialize reindex job history");
+                return;
+            }
+        };
+        let _write = self.persist_lock.lock().await;
+        drop(jobs);
+
+        let result = tokio::task::spawn_blocking({
+            let path = path.clone();
+            move || write_history(&path, &bytes)
+        })
+        .await
+        .unwrap_or_else(|e| Err(std::io::Error::other(e)));
+        if let Err(e) = result {
+            tracing::warn!(path = %path.display(), error = %e, "failed to persist reindex job history");
+        }
     }
 }
 
+fn load_history(path: &std::path::Path) -> Vec<JobInfo> {
+    let raw = match fs::read(path) {
+        Ok(raw) => raw,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Vec::new(),
+        Err(err) => {
+            tracing::warn!(path = %path.display(), error = %err, "failed to read reindex job history");
+            return Vec::new();
+        }
+    };
+
+    serde_json::from_slice(&raw).unwrap_or_else(|err| {
+        tracing::warn!(path = %path.display(), error = %err, "corrupt reindex job history, ignoring");
+        Vec::new()
+    })
+}
+
+fn write_history(path: &std::path::Path, bytes: &[u8]) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, bytes)?;
+    fs::rename(tmp, path)
+}
+
 impl Default for JobManager {
     fn default() -> Self {
         Self::new()
@@ -138,6 +390,15 @@ pub enum ReindexError {
     #[error("Another reindex job is already in progress")]
     JobInProgress,
 
+    #[error("Table maintenance is in progress")]
+    MaintenanceInProgress,
+
+    #[error("Reindex job not found: {0}")]
+    JobNotFound(String),
+
+    #[error("Reindex job is not running: {0}")]
+    JobNotRunning(String),
+
     #[error("Indexer error: {0}")]
     IndexerError(#[from] IndexerError),
 
@@ -145,22 +406,48 @@ pub enum ReindexError {
     InternalError(String),
 }
 
-impl IntoResponse for ReindexError {
-    fn into_response(self) -> Response {
-        match self {
-            ReindexError::JobInProgress => (StatusCode::CONFLICT, self.to_string()).into_response(),
+impl From<ReindexError> for ApiError {
+    fn from(error: ReindexError) -> Self {
+        match error {
+            ReindexError::JobInProgress | ReindexError::MaintenanceInProgress => {
+                ApiError::conflict(error.to_string())
+            }
+            ReindexError::JobNotFound(ref job_id) => {
+                let details = serde_json::json!({ "job_id": job_id });
+                ApiError::not_found(error.to_string()).with_details(details)
+            }
+            ReindexError::JobNotRunning(ref job_id) => {
+                let details = serde_json::json!({ "job_id": job_id });
+                ApiError::conflict(error.to_string()).with_details(details)
+            }
+            ReindexError::IndexerError(IndexerError::InvalidPath(ref path)) => {
+                let details = serde_json::json!({ "field": "paths", "path": path });
+                ApiError::invalid_request(error.to_string()).with_details(details)
+            }
             ReindexError::IndexerError(_) | ReindexError::InternalError(_) => {
-                (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()).into_response()
+                ApiError::internal(error.to_string())
             }
         }
     }
 }
 
+impl IntoResponse for ReindexError {
+    fn into_response(self) -> Response {
+        ApiError::from(self).into_response()
+    }
+}
+
 /// POST /api/reindex 请求
 #[derive(Debug, Deserialize)]
 pub struct ReindexRequest {
     #[serde(default = "default_full_rebuild")]
     pub full: bool,
+    /// 仅重建指定文件（知识库目录内的相对路径）
+    #[serde(default)]
+    pub paths: Vec<String>,
+    /// 仅重建该前缀下的文件，例如 `runbooks/`
+    #[serde(default)]
+    pub prefix: Option<String>,
 }
 
 fn default_full_rebuild() -> bool {
@@ -183,41 +470,63 @@ pub struct ReindexStatusResponse {
 /// 处理 /api/reindex POST 请求
 pub async fn handle_reindex(
     State(state): State<Arc<AppState>>,
-    req: Json<ReindexRequest>,
+    req: ApiJson<ReindexRequest>,
 ) -> Result<Json<ReindexResponse>, ReindexError> {
-    tracing::info!(full_rebuild = req.full, "received reindex request");
+    tracing::info!(
+        full_rebuild = req.full,
+        paths = req.paths.len(),
+        prefix = req.prefix.as_deref().unwrap_or(""),
+        "received reindex request"
+    );
+
+    let scope = IndexScope::new(&req.paths, req.prefix.as_deref())?;
+    let job_id = submit_reindex(state, req.full, scope).await?;
+
+    Ok(Json(ReindexResponse {
+        job_id,
+        message: "Reindex job started successfully".to_string(),
+    }))
+}
 
-    // Start job
-    let job_id = state.job_manager.start_job().await?;
+/// 通过 JobManager 登记任务并在后台执行索引；已有任务运行时返回 `JobInProgress`，
+/// 表维护进行中返回 `MaintenanceInProgress`
+pub async fn submit_reindex(
+    state: Arc<AppState>,
+    full_rebuild: bool,
+    scope: IndexScope,
+) -> Result<String, ReindexError> {
+    let (job_id, control) = state.job_manager.start_job().await?;
     let job_id_for_task = job_id.clone();
-    let state_clone = state.clone();
-    let full_rebuild = req.full;
 
     // Run indexing in background
     tokio::spawn(async move {
         tracing::info!(job_id = %job_id_for_task, "started reindex job");
 
-        match state_clone.indexer.index(full_rebuild).await {
-            Ok(result) => {
-                tracing::info!(
-                    job_id = %job_id_for_task,
-                    successful = result.successful_chunks,
-                    failed = result.failed_chunks,
-                    "reindex job completed"
-                );
-                state_clone.job_manager.complete_job(result).await;
-            }
-            Err(e) => {
-                tracing::error!(job_id = %job_id_for_task, error = %e, "reindex job failed");
-                state_clone.job_manager.fail_job(e.to_string()).await;
-            }
+        let outcome = state
+            .indexer
+            .index_scoped(full_rebuild, &scope, &control)
+            .await;
+        match &outcome {
+            Ok(result) if result.cancelled => tracing::info!(
+                job_id = %job_id_for_task,
+                indexed_files = result.indexed_files,
+                "reindex job cancelled"
+            ),
+            Ok(result) => tracing::info!(
+                job_id = %job_id_for_task,
+                successful = result.successful_chunks,
+                failed = result.failed_chunks,
+                "reindex job completed"
+            ),
+            Err(e) => tracing::error!(job_id = %job_id_for_task, error = %e, "reindex job failed"),
         }
+        state
+            .job_manager
+            .finish_job(&job_id_for_task, outcome)
+            .await;
     });
 
-    Ok(Json(ReindexResponse {
-        job_id,
-        message: "Reindex job started successfully".to_string(),
-    }))
+    Ok(job_id)
 }
 
 /// 处理 /api/reindex GET 请求
@@ -229,6 +538,40 @@ pub async fn handle_reindex_status(
     Json(ReindexStatusResponse { job })
 }
 
+/// GET /api/reindex/jobs 响应
+#[derive(Debug, Serialize)]
+pub struct JobListResponse {
+    pub jobs: Vec<JobInfo>,
+}
+
+/// 处理 /api/reindex/jobs GET 请求
+pub async fn handle_list_jobs(State(state): State<Arc<AppState>>) -> Json<JobListResponse> {
+    Json(JobListResponse {
+        jobs: state.job_manager.list_jobs().await,
+    })
+}
+
+/// 处理 /api/reindex/jobs/{id} GET 请求
+pub async fn handle_get_job(
+    State(state): State<Arc<AppState>>,
+    Path(job_id): Path<String>,
+) -> Result<Json<JobInfo>, ReindexError> {
+    state
+        .job_manager
+        .get_job(&job_id)
+        .await
+        .map(Json)
+        .ok_or(ReindexError::JobNotFound(job_id))
+}
+
+/// 处理 /api/reindex/jobs/{id} DELETE 请求：协作式取消运行中的任务
+pub async fn handle_cancel_job(
+    State(state): State<Arc<AppState>>,
+    Path(job_id): Path<String>,
+) -> Result<Json<JobInfo>, ReindexError> {
+    state.job_manager.cancel_job(&job_id).await.map(Json)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -249,31 +592,129 @@ mod tests {
         );
     }
 
+    fn index_result() -> IndexResult {
+        IndexResult {
+            total_files: 1,
+            indexed_files: 1,
+            skipped_files: 0,
+            failed_files: 0,
+            total_chunks: 1,
+            successful_chunks: 1,
+            failed_chunks: 0,
+            deleted_chunks: 0,
+            duration_ms: 10,
+            files: Vec::new(),
+            version: None,
+            cancelled: false,
+        }
+    }
+
     #[tokio::test]
     async fn start_job_allows_new_job_after_completion() {
         let manager = JobManager::new();
-        let first_job_id = manager.start_job().await.expect("first job should start");
+        let (first_job_id, _) = manager.start_job().await.expect("first job should start");
         assert!(!first_job_id.is_empty());
+        assert!(matches!(
+            manager.start_job().await,
+            Err(ReindexError::JobInProgress)
+        ));
 
-        manager
-            .complete_job(IndexResult {
-                total_files: 1,
-                indexed_files: 1,
-                skipped_files: 0,
-                failed_files: 0,
-                total_chunks: 1,
-                successful_chunks: 1,
-                failed_chunks: 0,
-                deleted_chunks: 0,
-                duration_ms: 10,
-            })
-            .await;
+        manager.finish_job(&first_job_id, Ok(index_result())).await;
 
-        let second_job_id = manager
+        let (second_job_id, _) = manager
             .start_job()
             .await
             .expect("completed job should not block new job");
         assert_ne!(first_job_id, second_job_id);
         assert!(manager.get_last_index_time().await.is_some());
+        assert_eq!(manager.index_version(), 1);
+
+        let jobs = manager.list_jobs().await;
+        assert_eq!(jobs.len(), 2);
+        assert_eq!(jobs[0].job_id, second_job_id);
+        assert_eq!(jobs[1].status, JobStatus::Completed);
+    }
+
+    #[tokio::test]
+    async fn maintenance_and_reindex_exclude_each_other() {
+        let manager = JobManager::new();
+        let slot = manager
+            .reserve_maintenance()
+            .await
+            .expect("idle manager should allow maintenance");
+        assert!(matches!(
+            manager.reserve_maintenance().await,
+            Err(ReindexError::MaintenanceInProgress)
+        ));
+        assert!(matches!(
+            manager.start_job().await,
+            Err(ReindexError::MaintenanceInProgress)
+        ));
+
+        drop(slot);
+        let (job_id, _) = manager
+            .start_job()
+            .await
+            .expect("released slot should not block new job");
+        assert!(matches!(
+            manager.reserve_maintenance().await,
+            Err(ReindexError::JobInProgress)
+        ));
+        manager.finish_job(&job_id, Ok(index_result())).await;
+    }
+
+    #[tokio::test]
+    async fn cancel_job_signals_running_job() {
+        let manager = JobManager::new();
+        let (job_id, control) = manager.start_job().await.unwrap();
+
+        assert!(matches!(
+            manager.cancel_job("missing").await,
+            Err(ReindexError::JobNotFound(_))
+        ));
+
+        let job = manager.cancel_job(&job_id).await.unwrap();
+        assert!(job.cancel_requested);
+        assert!(control.is_cancelled());
+
+        let partial = IndexResult {
+            cancelled: true,
+            ..index_result()
+        };
+        manager.finish_job(&job_id, Ok(partial)).await;
+        let job = manager.get_job(&job_id).await.unwrap();
+        assert_eq!(job.status, JobStatus::Cancelled);
+        assert_eq!(job.result.as_ref().map(|r| r.indexed_files), Some(1));
+        assert!(manager.get_last_index_time().await.is_none());
+        assert!(matches!(
+            manager.cancel_job(&job_id).await,
+            Err(ReindexError::JobNotRunning(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn history_survives_restart() {
+        let dir = std::env::temp_dir().join(format!("engineqa-jobs-{}", Uuid::new_v4()));
+        let config = JobHistoryConfig {
+            path: dir.join("jobs.json").to_string_lossy().to_string(),
+            max_entries: 2,
+        };
+
+        let manager = JobManager::with_history(&config);
+        for _ in 0..2 {
+            let (job_id, _) = manager.start_job().await.unwrap();
+            manager.finish_job(&job_id, Ok(index_result())).await;
+        }
+        let (interrupted, _) = manager.start_job().await.unwrap();
+
+        let restored = JobManager::with_history(&config);
+        let jobs = restored.list_jobs().await;
+        assert_eq!(jobs.len(), 2);
+        assert_eq!(jobs[0].job_id, interrupted);
+        assert_eq!(jobs[0].status, JobStatus::Failed);
+        assert!(restored.get_last_index_time().await.is_some());
+        assert!(restored.start_job().await.is_ok());
+
+        let _ = fs::remove_dir_all(dir);
     }
 }
@@ -7,7 +7,12 @@ use axum::{
 use serde::{Serialize, Serializer};
 use std::sync::Arc;
 
-use crate::{AppState, vector_store::VectorStoreError};
+use crate::{
+    AppState,
+    api::{error_code::ErrorCode, error_response::ApiError},
+    cache::embedding::EmbeddingCacheStats,
+    vector_store::VectorStoreError,
+};
 
 /// 上游健康状态
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
@@ -44,6 +49,8 @@ pub struct StatusResponse {
     /// 最后索引时间
     #[serde(serialize_with = "serialize_option_datetime")]
     pub last_index_time: Option<String>,
+    /// 下一次定时重建索引时间（未配置 REINDEX_SCHEDULE 时为 null）
+    pub next_scheduled_reindex: Option<String>,
     /// 上游健康状态
     pub upstream_health: UpstreamHealth,
     /// 限流状态
@@ -52,6 +59,8 @@ pub struct StatusResponse {
     pub vector_store_connected: bool,
     /// 兼容字段：后续版本将移除
     pub qdrant_connected: bool,
+    /// embedding 缓存统计
+    pub embedding_cache: EmbeddingCacheStats,
 }
 
 /// 序列化可选的日期时间
@@ -87,16 +96,22 @@ pub enum StatusError {
     InternalError(String),
 }
 
+impl From<StatusError> for ApiError {
+    fn from(error: StatusError) -> Self {
+        match error {
+            StatusError::VectorStoreError(_) => ApiError::new(
+                StatusCode::SERVICE_UNAVAILABLE,
+                ErrorCode::RetrievalFailed,
+                error.to_string(),
+            ),
+            StatusError::InternalError(_) => ApiError::internal(error.to_string()),
+        }
+    }
+}
+
 impl IntoResponse for StatusError {
     fn into_response(self) -> Response {
-        match self {
-            StatusError::VectorStoreError(_) => {
-                (StatusCode::SERVICE_UNAVAILABLE, self.to_string()).into_response()
-            }
-            StatusError::InternalError(_) => {
-                (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()).into_response()
-            }
-        }
+        ApiError::from(self).into_response()
     }
 }
 
@@ -112,12 +127,13 @@ pub async fn handle_status(
     let vector_store_connected = true;
 
     Ok(Json(StatusResponse {
-        provider: state.config.infer_provider.clone(),
-        model: state.config.internal_api.chat_model.clone(),
+        provider: state.provider.name().to_string(),
+        model: state.provider.chat_model().to_string(),
         vector_store: state.config.vector_store.clone(),
-        vector_table: state.config.lancedb_table.clone(),
+        vector_table: state.config.vector_table().to_string(),
         index_size: collection_info.points_count,
         last_index_time,
+        next_scheduled_reindex: state.scheduler.next_run().map(|next| next.to_rfc3339()),
         upstream_health,
         rate_limit_state: RateLimitState {
             rpm_limit: state.config.internal_api.chat_rate_limit_rpm,
@@ -125,6 +141,7 @@ pub async fn handle_status(
         },
         vector_store_connected,
         qdrant_connected: vector_store_connected,
+        embedding_cache: state.embedding_cache.stats().await,
     }))
 }
 
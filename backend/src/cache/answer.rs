@@ -0,0 +1,198 @@
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+use crate::{
+    api::query::QuerySource, config::AnswerCacheConfig, provider::ServedBy,
+    rag::grounding::GroundingReport,
+};
+
+/// 缓存的答案内容
+#[derive(Debug, Clone)]
+pub struct CachedAnswer {
+    /// 原始问题
+    pub question: String,
+    pub answer: String,
+    pub sources: Vec<QuerySource>,
+    pub served_by: Option<ServedBy>,
+    pub grounding: Option<GroundingReport>,
+}
+
+#[derive(Debug, Clone)]
+struct AnswerEntry {
+    embedding: Vec<f32>,
+    cached: CachedAnswer,
+    variant: String,
+    index_version: u64,
+    created_at: Instant,
+}
+
+/// 语义答案缓存：问题向量与已缓存问题的余弦相似度达到阈值、
+/// 且索引版本未变化时，直接复用先前的非降级答案
+///
+/// `variant` 标识影响答案的请求参数（检索参数、提示词模板等），仅在相同 variant 之间复用。
+pub struct AnswerCache {
+    enabled: bool,
+    similarity_threshold: f32,
+    max_entries: usize,
+    ttl: Duration,
+    entries: Mutex<Vec<AnswerEntry>>,
+}
+
+impl AnswerCache {
+    pub fn new(config: &AnswerCacheConfig) -> Self {
+        Self {
+            enabled: config.enabled,
+            similarity_threshold: config.similarity_threshold,
+            max_entries: config.max_entries,
+            ttl: Duration::from_secs(config.ttl_secs),
+            entries: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// 查找最相似且仍然有效的缓存答案，返回答案与相似度
+    pub async fn lookup(
+        &self,
+        embedding: &[f32],
+        variant: &str,
+        index_version: u64,
+    ) -> Option<(CachedAnswer, f32)> {
+        if !self.enabled {
+            return None;
+        }
+
+        let mut entries = self.entries.lock().await;
+        entries.retain(|entry| {
+            entry.index_version == index_version && entry.created_at.elapsed() < self.ttl
+        });
+
+        entries
+            .iter()
+            .filter(|entry| entry.variant == variant)
+            .map(|entry| (entry, cosine_similarity(embedding, &entry.embedding)))
+            .filter(|(_, similarity)| *similarity >= self.similarity_threshold)
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(entry, similarity)| (entry.cached.clone(), similarity))
+    }
+
+    pub async fn insert(
+        &self,
+        embedding: Vec<f32>,
+        variant: &str,
+        index_version: u64,
+        cached: CachedAnswer,
+    ) {
+        if !self.enabled || self.max_entries == 0 {
+            return;
+        }
+
+        let mut entries = self.entries.lock().await;
+        if entries.len() >= self.max_entries {
+            // 按插入顺序淘汰最旧的条目
+            let overflow = entries.len() + 1 - self.max_entries;
+            entries.drain(..overflow);
+        }
+
+        entries.push(AnswerEntry {
+            embedding,
+            cached,
+            variant: variant.to_string(),
+            index_version,
+            created_at: Instant::now(),
+        });
+    }
+}
+
+pub(crate) fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+
+    dot / (norm_a * norm_b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache(ttl_secs: u64) -> AnswerCache {
+        AnswerCache::new(&AnswerCacheConfig {
+            enabled: true,
+            similarity_threshold: 0.95,
+            max_entries: 2,
+            ttl_secs,
+        })
+    }
+
+    async fn insert(cache: &AnswerCache, embedding: Vec<f32>, answer: &str, version: u64) {
+        let cached = CachedAnswer {
+            question: "q".to_string(),
+            answer: answer.to_string(),
+            sources: vec![],
+            served_by: None,
+            grounding: None,
+        };
+        cache.insert(embedding, "k6", version, cached).await;
+    }
+
+    #[tokio::test]
+    async fn hits_similar_question_with_same_index_version() {
+        let cache = cache(60);
+        insert(&cache, vec![1.0, 0.0], "a1", 1).await;
+
+        let (hit, similarity) = cache
+            .lookup(&[0.99, 0.05], "k6", 1)
+            .await
+            .expect("should hit");
+        assert_eq!(hit.answer, "a1");
+        assert!(similarity > 0.95);
+
+        assert!(cache.lookup(&[0.0, 1.0], "k6", 1).await.is_none());
+        assert!(cache.lookup(&[1.0, 0.0], "k3", 1).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn misses_after_index_version_changes() {
+        let cache = cache(60);
+        insert(&cache, vec![1.0, 0.0], "a1", 1).await;
+
+        assert!(cache.lookup(&[1.0, 0.0], "k6", 2).await.is_none());
+        assert!(cache.lookup(&[1.0, 0.0], "k6", 1).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn expired_entries_are_ignored() {
+        let cache = cache(0);
+        insert(&cache, vec![1.0, 0.0], "a1", 1).await;
+        assert!(cache.lookup(&[1.0, 0.0], "k6", 1).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn evicts_oldest_when_full() {
+        let cache = cache(60);
+        insert(&cache, vec![1.0, 0.0, 0.0], "a1", 1).await;
+        insert(&cache, vec![0.0, 1.0, 0.0], "a2", 1).await;
+        insert(&cache, vec![0.0, 0.0, 1.0], "a3", 1).await;
+
+        assert!(cache.lookup(&[1.0, 0.0, 0.0], "k6", 1).await.is_none());
+        assert_eq!(
+            cache
+                .lookup(&[0.0, 0.0, 1.0], "k6", 1)
+                .await
+                .unwrap()
+                .0
+                .answer,
+            "a3"
+        );
+    }
+}
@@ -0,0 +1,466 @@
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    fs,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+use tokio::sync::Mutex;
+
+use crate::{
+    config::EmbeddingCacheConfig,
+    provider::{InferenceProvider, ProviderResult},
+};
+
+/// 超出容量时一次淘汰的比例，避免每次插入都全量扫描
+const EVICT_RATIO: f64 = 0.1;
+
+/// 快照文件头：魔数与格式版本
+const SNAPSHOT_MAGIC: &[u8; 4] = b"EQEC";
+const SNAPSHOT_VERSION: u8 = 1;
+
+type CacheKey = [u8; 32];
+
+#[derive(Debug, Clone)]
+struct CacheEntry {
+    /// 落盘时只复制引用，不在锁内复制向量
+    vector: Arc<[f32]>,
+    last_used: u64,
+}
+
+/// 落盘用的快照，条目按最近使用时间升序
+struct Snapshot {
+    model: String,
+    entries: Vec<(u64, CacheKey, Arc<[f32]>)>,
+}
+
+/// 缓存统计
+#[derive(Debug, Clone, Serialize)]
+pub struct EmbeddingCacheStats {
+    pub enabled: bool,
+    pub model: String,
+    pub entries: usize,
+    pub max_entries: usize,
+    pub hits: u64,
+    pub misses: u64,
+    pub hit_rate: f64,
+}
+
+/// 持久化的 embedding 缓存，键为 SHA-256(embed_model + 文本)
+///
+/// 快照为紧凑的二进制格式（模型名 + 每条的键与 little-endian f32 向量），
+/// 仅在有新写入时整体重写。快照记录了生成向量的模型，启动时模型不一致则整体丢弃。
+pub struct EmbeddingCache {
+    enabled: bool,
+    model: String,
+    path: PathBuf,
+    max_entries: usize,
+    entries: Mutex<HashMap<CacheKey, CacheEntry>>,
+    tick: AtomicU64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    dirty: AtomicBool,
+}
+
+impl EmbeddingCache {
+    /// 创建缓存并尝试从快照加载
+    pub fn new(config: &EmbeddingCacheConfig, model: &str) -> Self {
+        let path = PathBuf::from(&config.path);
+        let entries = if config.enabled {
+            load_snapshot(&path, model, config.max_entries)
+        } else {
+            HashMap::new()
+        };
+        let tick = entries.len() as u64;
+
+        Self {
+            enabled: config.enabled,
+            model: model.to_string(),
+            path,
+            max_entries: config.max_entries,
+            entries: Mutex::new(entries),
+            tick: AtomicU64::new(tick),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            dirty: AtomicBool::new(false),
+        }
+    }
+
+    /// 不缓存任何内容的实例
+    pub fn disabled() -> Self {
+        Self::new(
+            &EmbeddingCacheConfig {
+                enabled: false,
+                path: String::new(),
+                max_entries: 0,
+                flush_interval_secs: 0,
+            },
+            "",
+        )
+    }
+
+    fn key(model: &str, text: &str) -> CacheKey {
+        let mut hasher = Sha256::new();
+        hasher.update(model.as_bytes());
+        hasher.update([0u8]);
+        hasher.update(text.as_bytes());
+        hasher.finalize().into()
+    }
+
+    pub async fn get(&self, text: &str) -> Option<Vec<f32>> {
+        if !self.enabled {
+            return None;
+        }
+
+        let key = Self::key(&self.model, text);
+        let mut entries = self.entries.lock().await;
+        match entries.get_mut(&key) {
+            Some(entry) => {
+                entry.last_used = self.tick.fetch_add(1, Ordering::Relaxed);
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                Some(entry.vector.to_vec())
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    /// 写入缓存；`model` 与缓存模型不一致（如回退到备用提供方）时不写入，避免混用向量空间
+    pub async fn insert(&self, model: &str, text: &str, vector: Vec<f32>) {
+        if !self.enabled || self.max_entries == 0 || model != self.model {
+            return;
+        }
+
+        let key = Self::key(&self.model, text);
+        let mut entries = self.entries.lock().await;
+        entries.insert(
+            key,
+            CacheEntry {
+                vector: vector.into(),
+                last_used: self.tick.fetch_add(1, Ordering::Relaxed),
+            },
+        );
+
+        if entries.len() > self.max_entries {
+            evict_least_recently_used(&mut entries, self.max_entries);
+        }
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+
+    pub async fn stats(&self) -> EmbeddingCacheStats {
+        let hits = self.hits.load(Ordering::Relaxed);
+        let misses = self.misses.load(Ordering::Relaxed);
+        let total = hits + misses;
+
+        EmbeddingCacheStats {
+            enabled: self.enabled,
+            model: self.model.clone(),
+            entries: self.entries.lock().await.len(),
+            max_entries: self.max_entries,
+            hits,
+            misses,
+            hit_rate: if total == 0 {
+                0.0
+            } else {
+                hits as f64 / total as f64
+            },
+        }
+    }
+
+    /// 有新写入时将缓存落盘
+    pub async fn flush(&self) -> std::io::Result<()> {
+        if !self.enabled || !self.dirty.swap(false, Ordering::Relaxed) {
+            return Ok(());
+        }
+
+        let entries: Vec<(u64, CacheKey, Arc<[f32]>)> = self
+            .entries
+            .lock()
+            .await
+            .iter()
+            .map(|(key, entry)| (entry.last_used, *key, entry.vector.clone()))
+            .collect();
+        let mut snapshot = Snapshot {
+            model: self.model.clone(),
+            entries,
+        };
+
+        let path = self.path.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            snapshot
+                .entries
+                .sort_unstable_by_key(|(last_used, _, _)| *last_used);
+            write_snapshot(&path, &snapshot)
+        })
+        .await
+        .map_err(std::io::Error::other)?;
+
+        if result.is_err() {
+            self.dirty.store(true, Ordering::Relaxed);
+        }
+        result
+    }
+
+    /// 定期落盘的后台任务
+    pub fn spawn_flusher(self: Arc<Self>, interval: Duration) {
+        if !self.enabled || interval.is_zero() {
+            return;
+        }
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                if let Err(err) = self.flush().await {
+                    tracing::warn!(error = %err, "failed to flush embedding cache");
+                }
+            }
+        });
+    }
+}
+
+/// 先查缓存，未命中再调用提供方并回填
+pub async fn embed_cached(
+    provider: &dyn InferenceProvider,
+    cache: &EmbeddingCache,
+    text: &str,
+) -> ProviderResult<Vec<f32>> {
+    if let Some(vector) = cache.get(text).await {
+        return Ok(vector);
+    }
+
+    let (vector, served_by) = provider.embed_routed(text).await?;
+    cache.insert(&served_by.model, text, vector.clone()).await;
+    Ok(vector)
+}
+
+fn evict_least_recently_used(entries: &mut HashMap<CacheKey, CacheEntry>, max_entries: usize) {
+    let target = max_entries - ((max_entries as f64 * EVICT_RATIO) as usize);
+    let mut by_age: Vec<(u64, CacheKey)> = entries
+        .iter()
+        .map(|(key, entry)| (entry.last_used, *key))
+        .collect();
+    by_age.sort_unstable();
+
+    let excess = entries.len().saturating_sub(target);
+    for (_, key) in by_age.into_iter().take(excess) {
+        entries.remove(&key);
+    }
+}
+
+fn load_snapshot(path: &Path, model: &str, max_entries: usize) -> HashMap<CacheKey, CacheEntry> {
+    let raw = match fs::read(path) {
+        Ok(raw) => raw,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return HashMap::new(),
+        Err(err) => {
+            tracing::warn!(path = %path.display(), error = %err, "failed to read embedding cache");
+            return HashMap::new();
+        }
+    };
+
+    // 旧版 JSON 快照或损坏的文件同样丢弃，之后按新格式重写
+    let Some((cached_model, mut snapshot)) = decode_snapshot(&raw) else {
+        tracing::warn!(path = %path.display(), "unreadable embedding cache, ignoring");
+        return HashMap::new();
+    };
+
+    if cached_model != model {
+        tracing::info!(
+            cached_model = %cached_model,
+            current_model = %model,
+            "embedding model changed, invalidating embedding cache"
+        );
+        return HashMap::new();
+    }
+
+    // 快照按最近使用时间升序，超出容量时保留最近使用的部分
+    let excess = snapshot.len().saturating_sub(max_entries);
+    let entries: HashMap<CacheKey, CacheEntry> = snapshot
+        .drain(excess..)
+        .enumerate()
+        .map(|(idx, (key, vector))| {
+            (
+                key,
+                CacheEntry {
+                    vector,
+                    last_used: idx as u64,
+                },
+            )
+        })
+        .collect();
+
+    tracing::info!(entries = entries.len(), model = %model, "embedding cache loaded");
+    entries
+}
+
+/// 解析快照：魔数、版本、模型名，之后每条为 32 字节键、u32 维度与向量
+fn decode_snapshot(raw: &[u8]) -> Option<(String, Vec<(CacheKey, Arc<[f32]>)>)> {
+    fn take<'a>(raw: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+        let (head, rest) = raw.split_at_checked(len)?;
+        *raw = rest;
+        Some(head)
+    }
+    fn take_u32(raw: &mut &[u8]) -> Option<usize> {
+        Some(u32::from_le_bytes(take(raw, 4)?.try_into().ok()?) as usize)
+    }
+
+    let mut raw = raw;
+    if take(&mut raw, 4)? != SNAPSHOT_MAGIC || take(&mut raw, 1)? != [SNAPSHOT_VERSION] {
+        return None;
+    }
+    let model_len = take_u32(&mut raw)?;
+    let model = String::from_utf8(take(&mut raw, model_len)?.to_vec()).ok()?;
+
+    let mut entries = Vec::new();
+    while !raw.is_empty() {
+        let key: CacheKey = take(&mut raw, 32)?.try_into().ok()?;
+        let dim = take_u32(&mut raw)?;
+        let vector: Arc<[f32]> = take(&mut raw, dim.checked_mul(4)?)?
+            .chunks_exact(4)
+            .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            .collect();
+        entries.push((key, vector));
+    }
+    Some((model, entries))
+}
+
+fn write_snapshot(path: &Path, snapshot: &Snapshot) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension("tmp");
+    let mut writer = BufWriter::new(fs::File::create(&tmp)?);
+    writer.write_all(SNAPSHOT_MAGIC)?;
+    writer.write_all(&[SNAPSHOT_VERSION])?;
+    writer.write_all(&(snapshot.model.len() as u32).to_le_bytes())?;
+    writer.write_all(snapshot.model.as_bytes())?;
+    for (_, key, vector) in &snapshot.entries {
+        writer.write_all(key)?;
+        writer.write_all(&(vector.len() as u32).to_le_bytes())?;
+        for value in vector.iter() {
+            writer.write_all(&value.to_le_bytes())?;
+        }
+    }
+    writer
+        .into_inner()
+        .map_err(|e| e.into_error())?
+        .sync_all()?;
+    fs::rename(tmp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(path: &Path, max_entries: usize) -> EmbeddingCacheConfig {
+        EmbeddingCacheConfig {
+            enabled: true,
+            path: path.to_string_lossy().to_string(),
+            max_entries,
+            flush_interval_secs: 0,
+        }
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("engineqa-{name}-{}.bin", uuid::Uuid::new_v4()))
+    }
+
+    #[tokio::test]
+    async fn records_hits_and_misses() {
+        let cache = EmbeddingCache::new(&config(&temp_path("hits"), 10), "embed-a");
+
+        assert!(cache.get("hello").await.is_none());
+        cache.insert("embed-a", "hello", vec![1.0, 2.0]).await;
+        assert_eq!(cache.get("hello").await, Some(vec![1.0, 2.0]));
+
+        let stats = cache.stats().await;
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.hit_rate, 0.5);
+    }
+
+    #[tokio::test]
+    async fn ignores_vectors_from_other_models() {
+        let cache = EmbeddingCache::new(&config(&temp_path("model"), 10), "embed-a");
+        cache.insert("embed-b", "hello", vec![1.0]).await;
+        assert!(cache.get("hello").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn evicts_least_recently_used_when_full() {
+        let cache = EmbeddingCache::new(&config(&temp_path("evict"), 10), "embed-a");
+        for i in 0..10 {
+            cache
+                .insert("embed-a", &format!("t{i}"), vec![i as f32])
+                .await;
+        }
+        // 访问 t0，使其成为最近使用
+        assert!(cache.get("t0").await.is_some());
+        cache.insert("embed-a", "t10", vec![10.0]).await;
+
+        let stats = cache.stats().await;
+        assert!(stats.entries <= 10);
+        assert!(cache.get("t0").await.is_some());
+        assert!(cache.get("t1").await.is_none());
+        assert!(cache.get("t10").await.is_some());
+    }
+
+    #[tokio::test]
+    async fn snapshot_is_invalidated_when_model_changes() {
+        let path = temp_path("snapshot");
+        let cache = EmbeddingCache::new(&config(&path, 10), "embed-a");
+        cache.insert("embed-a", "hello", vec![1.0]).await;
+        cache.flush().await.expect("flush should succeed");
+
+        let reloaded = EmbeddingCache::new(&config(&path, 10), "embed-a");
+        assert_eq!(reloaded.get("hello").await, Some(vec![1.0]));
+
+        let switched = EmbeddingCache::new(&config(&path, 10), "embed-b");
+        assert!(switched.get("hello").await.is_none());
+
+        let _ = fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn snapshot_keeps_most_recently_used_entries() {
+        let path = temp_path("recent");
+        let cache = EmbeddingCache::new(&config(&path, 10), "embed-a");
+        for i in 0..4 {
+            cache
+                .insert("embed-a", &format!("t{i}"), vec![i as f32, 0.5])
+                .await;
+        }
+        assert!(cache.get("t0").await.is_some());
+        cache.flush().await.expect("flush should succeed");
+
+        // 重新加载时容量变小，保留最近使用的 t3 与 t0
+        let reloaded = EmbeddingCache::new(&config(&path, 2), "embed-a");
+        assert_eq!(reloaded.stats().await.entries, 2);
+        assert_eq!(reloaded.get("t0").await, Some(vec![0.0, 0.5]));
+        assert_eq!(reloaded.get("t3").await, Some(vec![3.0, 0.5]));
+
+        // 没有新写入时不重写快照
+        fs::remove_file(&path).unwrap();
+        reloaded.flush().await.expect("flush should succeed");
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn ignores_legacy_json_snapshot() {
+        let path = temp_path("legacy");
+        fs::write(&path, br#"{"model":"embed-a","entries":[]}"#).unwrap();
+        let cache = EmbeddingCache::new(&config(&path, 10), "embed-a");
+        assert_eq!(cache.stats().await.entries, 0);
+
+        let _ = fs::remove_file(path);
+    }
+}
@@ -1,17 +1,318 @@
+use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, env, fmt, net::SocketAddr, str::FromStr};
 
+use crate::{
+    provider::{
+        mock::MOCK_EMBED_MODEL,
+        registry::{INTERNAL_API, KNOWN_PROVIDERS, MOCK, OPENAI_COMPAT},
+    },
+    vector_store::{
+        KNOWN_VECTOR_STORES, LANCEDB, MAX_PRUNE_OLDER_THAN_SECS, MEMORY, PGVECTOR, QDRANT,
+    },
+};
+
 #[derive(Debug, Clone)]
 pub struct AppConfig {
     pub host: String,
     pub port: u16,
     pub infer_provider: String,
+    /// chat 提供方回退链（按顺序尝试）
+    pub chat_providers: Vec<String>,
+    /// embedding 提供方回退链（按顺序尝试）
+    pub embed_providers: Vec<String>,
     pub vector_store: String,
     pub lancedb_uri: String,
     pub lancedb_table: String,
+    /// 内存向量存储的 JSON 快照路径，未配置时不落盘
+    pub memory_store_path: Option<String>,
+    pub qdrant: QdrantConfig,
+    pub pgvector: PgVectorConfig,
     pub embedding_vector_size: usize,
+    pub distance_metric: DistanceMetric,
     pub vector_score_threshold: f32,
+    pub vector_index: VectorIndexConfig,
+    pub index_maintenance: IndexMaintenanceConfig,
     pub knowledge_dir: String,
+    pub knowledge_watch: KnowledgeWatchConfig,
+    pub reindex_schedule: ReindexScheduleConfig,
+    pub job_history: JobHistoryConfig,
+    pub index_versioning: IndexVersioningConfig,
+    /// 索引记录的 embedding 模型/维度与配置不一致时的处理方式
+    pub index_spec_policy: IndexSpecPolicy,
     pub internal_api: InternalApiConfig,
+    /// OpenAI 兼容端点（可选，作为备用提供方）
+    pub openai_compat: Option<InternalApiConfig>,
+    /// 本地 mock 提供方（离线开发与测试）
+    pub mock: MockProviderConfig,
+    pub embedding_cache: EmbeddingCacheConfig,
+    pub answer_cache: AnswerCacheConfig,
+    pub prompt: PromptConfig,
+    pub grounding: GroundingConfig,
+    pub query_rewrite: QueryRewriteConfig,
+    pub query_limits: QueryLimitsConfig,
+}
+
+#[derive(Debug, Clone)]
+pub struct EmbeddingCacheConfig {
+    pub enabled: bool,
+    /// 快照文件路径
+    pub path: String,
+    pub max_entries: usize,
+    /// 定期落盘间隔，0 表示仅在索引结束时落盘
+    pub flush_interval_secs: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct PromptConfig {
+    /// 模板目录，`<name>.toml` 即模板 `<name>`
+    pub dir: String,
+    /// 请求未指定模板时使用的模板名
+    pub default_template: String,
+    /// 模板目录变更检查间隔，0 表示不热加载
+    pub reload_interval_secs: u64,
+}
+
+/// 知识库目录变更监听
+#[derive(Debug, Clone)]
+pub struct KnowledgeWatchConfig {
+    pub enabled: bool,
+    /// 最后一次变更后静默多久才触发增量索引
+    pub debounce_ms: u64,
+}
+
+/// 内置定时重建索引
+#[derive(Debug, Clone)]
+pub struct ReindexScheduleConfig {
+    /// cron 表达式（`秒 分 时 日 月 周`，也接受 5 段的标准 crontab），未设置时不启用
+    pub schedule: Option<cron::Schedule>,
+    /// true 为全量重建，false 为增量
+    pub full: bool,
+}
+
+/// Qdrant REST 服务，集合与 Python 后端共用
+#[derive(Debug, Clone)]
+pub struct QdrantConfig {
+    pub url: String,
+    pub api_key: Option<String>,
+    pub collection: String,
+    pub timeout_ms: u64,
+}
+
+/// PostgreSQL + pgvector（需启用 `pgvector` feature）
+#[derive(Debug, Clone)]
+pub struct PgVectorConfig {
+    pub database_url: String,
+    /// 表与迁移记录所在的 schema
+    pub schema: String,
+    pub max_connections: u32,
+}
+
+/// 索引任务历史
+#[derive(Debug, Clone)]
+pub struct JobHistoryConfig {
+    /// 历史记录 JSON 文件路径
+    pub path: String,
+    /// 保留的最大任务数
+    pub max_entries: usize,
+}
+
+/// 索引记录的生成参数与当前配置不一致时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexSpecPolicy {
+    /// 拒绝启动
+    Refuse,
+    /// 仅告警（维度或距离度量不同时仍拒绝）
+    Warn,
+    /// 启动后用当前模型全量重建到新表并切换
+    Migrate,
+}
+
+/// 向量距离度量，检索与索引构建使用同一度量
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DistanceMetric {
+    #[default]
+    Cosine,
+    L2,
+    Dot,
+}
+
+impl DistanceMetric {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DistanceMetric::Cosine => "cosine",
+            DistanceMetric::L2 => "l2",
+            DistanceMetric::Dot => "dot",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "cosine" => Some(DistanceMetric::Cosine),
+            "l2" => Some(DistanceMetric::L2),
+            "dot" => Some(DistanceMetric::Dot),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for DistanceMetric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// 向量索引类型
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VectorIndexType {
+    /// LanceDB 默认参数的 IVF-PQ
+    #[default]
+    Auto,
+    IvfPq,
+    IvfHnswPq,
+    IvfHnswSq,
+}
+
+/// 向量索引的构建与查询参数，未设置的项使用 LanceDB 默认值
+#[derive(Debug, Clone, Default)]
+pub struct VectorIndexConfig {
+    pub index_type: VectorIndexType,
+    /// IVF 分区数
+    pub num_partitions: Option<u32>,
+    /// PQ 子向量数（IVF-PQ / IVF-HNSW-PQ）
+    pub num_sub_vectors: Option<u32>,
+    /// HNSW 每个节点的边数
+    pub hnsw_m: Option<u32>,
+    /// HNSW 构建时的候选集大小
+    pub hnsw_ef_construction: Option<u32>,
+    /// 查询时探查的分区数
+    pub nprobes: Option<usize>,
+    /// 查询时按原始向量重排的倍数
+    pub refine_factor: Option<u32>,
+    /// HNSW 查询时的候选集大小
+    pub ef: Option<usize>,
+}
+
+/// 向量表后台维护
+#[derive(Debug, Clone)]
+pub struct IndexMaintenanceConfig {
+    /// 后台维护间隔，0 表示仅通过管理接口手动触发
+    pub interval_secs: u64,
+    /// 清理早于该时长的数据集历史版本
+    pub prune_older_than_secs: u64,
+}
+
+/// 全量重建的蓝绿构建策略
+#[derive(Debug, Clone)]
+pub struct IndexVersioningConfig {
+    /// 全量重建写入新版本，校验通过后再切换
+    pub blue_green: bool,
+    /// 切换后保留的旧版本数量（用于回滚）
+    pub keep_versions: usize,
+    /// 允许的最大失败比例（失败文件或失败片段），超出则放弃新版本
+    pub max_failed_ratio: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct AnswerCacheConfig {
+    pub enabled: bool,
+    /// 判定为同一问题的最低余弦相似度
+    pub similarity_threshold: f32,
+    pub max_entries: usize,
+    pub ttl_secs: u64,
+}
+
+/// 答案事实性校验方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroundingMode {
+    /// 句子与检索片段的向量相似度
+    Embedding,
+    /// 由 LLM 逐句判定是否有依据
+    Llm,
+    /// 先用向量相似度筛选，再由 LLM 复核未通过的句子
+    Hybrid,
+}
+
+impl GroundingMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GroundingMode::Embedding => "embedding",
+            GroundingMode::Llm => "llm",
+            GroundingMode::Hybrid => "hybrid",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GroundingConfig {
+    pub enabled: bool,
+    pub mode: GroundingMode,
+    /// 句子被视为有依据的最低相似度（embedding / hybrid 模式）
+    pub sentence_threshold: f32,
+    /// 答案整体有依据句子占比的下限
+    pub min_score: f32,
+    /// 低于下限时是否将响应标记为降级
+    pub degrade_on_low_score: bool,
+}
+
+/// 查询请求参数的服务端上限
+#[derive(Debug, Clone)]
+pub struct QueryLimitsConfig {
+    pub max_top_k: u64,
+    /// 问题最大字符数
+    pub max_question_chars: usize,
+}
+
+/// 检索前的查询改写方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RewriteMode {
+    /// 生成若干改写问题
+    Paraphrase,
+    /// 生成 HyDE 假设性答案
+    Hyde,
+    /// 两者都生成
+    Both,
+}
+
+#[derive(Debug, Clone)]
+pub struct QueryRewriteConfig {
+    pub enabled: bool,
+    pub mode: RewriteMode,
+    /// 改写问题的数量（paraphrase / both 模式）
+    pub num_rewrites: usize,
+    /// RRF 融合常数 k
+    pub rrf_k: f32,
+}
+
+/// mock 提供方注入的故障类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MockFault {
+    /// 不注入故障
+    None,
+    /// 返回 429
+    RateLimit,
+    /// 返回 500
+    ServerError,
+    /// 模拟上游超时
+    Timeout,
+}
+
+/// mock 故障注入的作用范围
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MockFaultTarget {
+    Chat,
+    Embed,
+    All,
+}
+
+#[derive(Debug, Clone)]
+pub struct MockProviderConfig {
+    /// 每次调用附加的延迟
+    pub latency_ms: u64,
+    pub fault: MockFault,
+    /// 故障触发概率，取值 [0, 1]
+    pub fault_rate: f32,
+    pub fault_target: MockFaultTarget,
 }
 
 #[derive(Debug, Clone)]
@@ -29,6 +330,10 @@ pub struct InternalApiConfig {
     pub chat_burst: u32,
     pub retry_chat_max: u32,
     pub retry_embed_max: u32,
+    pub retry_base_delay_ms: u64,
+    pub retry_max_delay_ms: u64,
+    pub retry_chat_budget_ms: u64,
+    pub retry_embed_budget_ms: u64,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -68,6 +373,17 @@ impl AppConfig {
         Self::from_map(&vars)
     }
 
+    /// 当前向量存储使用的表（集合）名
+    pub fn vector_table(&self) -> &str {
+        match self.vector_store.as_str() {
+            MEMORY => "memory",
+            QDRANT => &self.qdrant.collection,
+            // 表名由迁移固定
+            PGVECTOR => "knowledge_chunks",
+            _ => &self.lancedb_table,
+        }
+    }
+
     pub fn socket_addr(&self) -> Result<SocketAddr, ConfigError> {
         let raw = format!("{}:{}", self.host, self.port);
         SocketAddr::from_str(&raw).map_err(|err| ConfigError::InvalidSocketAddr {
@@ -81,16 +397,84 @@ impl AppConfig {
         let host = optional_var(vars, "APP_HOST", "127.0.0.1");
         let port = parse_u16(vars, "APP_PORT", 8080)?;
 
-        let vector_store = optional_var(vars, "VECTOR_STORE", "lancedb");
+        let vector_store = parse_vector_store(vars)?;
         let lancedb_uri = optional_var(vars, "LANCEDB_URI", "./.lancedb");
         let lancedb_table = optional_var(vars, "LANCEDB_TABLE", "knowledge_chunks");
+        let memory_store_path = vars
+            .get("MEMORY_STORE_PATH")
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty());
+        let qdrant = QdrantConfig {
+            url: optional_var(vars, "QDRANT_URL", "http://localhost:6333"),
+            api_key: vars
+                .get("QDRANT_API_KEY")
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty()),
+            collection: optional_var(vars, "QDRANT_COLLECTION", "knowledge_chunks"),
+            timeout_ms: parse_u64(vars, "QDRANT_TIMEOUT_MS", 5000)?,
+        };
+        let pgvector = PgVectorConfig {
+            database_url: if vector_store == PGVECTOR {
+                required_var(vars, "PGVECTOR_DATABASE_URL")?
+            } else {
+                optional_var(vars, "PGVECTOR_DATABASE_URL", "")
+            },
+            schema: parse_pg_schema(vars)?,
+            max_connections: parse_u32(vars, "PGVECTOR_MAX_CONNECTIONS", 5)?,
+        };
         let embedding_vector_size = parse_usize(vars, "EMBEDDING_VECTOR_SIZE", 1536)?;
+        let distance_metric = parse_distance_metric(vars)?;
         let vector_score_threshold = parse_f32(vars, "VECTOR_SCORE_THRESHOLD", 0.3)?;
         let knowledge_dir = optional_var(vars, "KNOWLEDGE_DIR", "./knowledge");
 
+        let vector_index = VectorIndexConfig {
+            index_type: parse_vector_index_type(vars)?,
+            num_partitions: parse_optional_u32(vars, "VECTOR_INDEX_PARTITIONS")?,
+            num_sub_vectors: parse_optional_u32(vars, "VECTOR_INDEX_SUB_VECTORS")?,
+            hnsw_m: parse_optional_u32(vars, "VECTOR_INDEX_HNSW_M")?,
+            hnsw_ef_construction: parse_optional_u32(vars, "VECTOR_INDEX_HNSW_EF_CONSTRUCTION")?,
+            nprobes: parse_optional_u32(vars, "VECTOR_SEARCH_NPROBES")?.map(|n| n as usize),
+            refine_factor: parse_optional_u32(vars, "VECTOR_SEARCH_REFINE_FACTOR")?,
+            ef: parse_optional_u32(vars, "VECTOR_SEARCH_EF")?.map(|n| n as usize),
+        };
+
+        let prune_older_than_secs = parse_u64(vars, "INDEX_PRUNE_OLDER_THAN_SECS", 604_800)?;
+        if prune_older_than_secs > MAX_PRUNE_OLDER_THAN_SECS {
+            return Err(ConfigError::InvalidEnv {
+                key: "INDEX_PRUNE_OLDER_THAN_SECS",
+                value: prune_older_than_secs.to_string(),
+                reason: "expected at most 315360000 (10 years)",
+            });
+        }
+        let index_maintenance = IndexMaintenanceConfig {
+            interval_secs: parse_u64(vars, "INDEX_MAINTENANCE_INTERVAL_SECS", 21_600)?,
+            prune_older_than_secs,
+        };
+
+        let infer_provider = optional_var(vars, "INFER_PROVIDER", "internal_api");
+        let chat_providers = parse_provider_list(vars, "CHAT_PROVIDERS", &infer_provider)?;
+        let embed_providers = parse_provider_list(vars, "EMBED_PROVIDERS", &infer_provider)?;
+
+        // 仅当提供方链用到 internal_api 时才要求其地址与凭证
+        let uses_internal_api = chat_providers
+            .iter()
+            .chain(embed_providers.iter())
+            .any(|name| name == INTERNAL_API);
+        let (base_url, token) = if uses_internal_api {
+            (
+                required_var(vars, "INTERNAL_API_BASE_URL")?,
+                required_var(vars, "INTERNAL_API_TOKEN")?,
+            )
+        } else {
+            (
+                optional_var(vars, "INTERNAL_API_BASE_URL", ""),
+                optional_var(vars, "INTERNAL_API_TOKEN", ""),
+            )
+        };
+
         let internal_api = InternalApiConfig {
-            base_url: required_var(vars, "INTERNAL_API_BASE_URL")?,
-            token: required_var(vars, "INTERNAL_API_TOKEN")?,
+            base_url,
+            token,
             chat_path: optional_var(vars, "INTERNAL_API_CHAT_PATH", "/v1/chat/completions"),
             embed_path: optional_var(vars, "INTERNAL_API_EMBED_PATH", "/v1/embeddings"),
             chat_model: optional_var(vars, "INTERNAL_API_CHAT_MODEL", "ad-qa-chat-v1"),
@@ -102,19 +486,196 @@ impl AppConfig {
             chat_burst: parse_u32(vars, "CHAT_BURST", 10)?,
             retry_chat_max: parse_u32(vars, "RETRY_CHAT_MAX", 1)?,
             retry_embed_max: parse_u32(vars, "RETRY_EMBED_MAX", 3)?,
+            retry_base_delay_ms: parse_u64(vars, "RETRY_BASE_DELAY_MS", 200)?,
+            retry_max_delay_ms: parse_u64(vars, "RETRY_MAX_DELAY_MS", 2000)?,
+            retry_chat_budget_ms: parse_u64(vars, "RETRY_CHAT_BUDGET_MS", 5000)?,
+            retry_embed_budget_ms: parse_u64(vars, "RETRY_EMBED_BUDGET_MS", 12000)?,
+        };
+
+        let openai_compat = match vars
+            .get("OPENAI_COMPAT_BASE_URL")
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+        {
+            Some(base_url) => Some(InternalApiConfig {
+                base_url: base_url.to_string(),
+                token: required_var(vars, "OPENAI_COMPAT_API_KEY")?,
+                chat_path: optional_var(vars, "OPENAI_COMPAT_CHAT_PATH", "/v1/chat/completions"),
+                embed_path: optional_var(vars, "OPENAI_COMPAT_EMBED_PATH", "/v1/embeddings"),
+                chat_model: optional_var(vars, "OPENAI_COMPAT_CHAT_MODEL", "gpt-4o-mini"),
+                embed_model: optional_var(
+                    vars,
+                    "OPENAI_COMPAT_EMBED_MODEL",
+                    "text-embedding-3-small",
+                ),
+                ..internal_api.clone()
+            }),
+            None => None,
+        };
+
+        let uses_openai_compat = chat_providers
+            .iter()
+            .chain(embed_providers.iter())
+            .any(|name| name == OPENAI_COMPAT);
+        if uses_openai_compat && openai_compat.is_none() {
+            return Err(ConfigError::MissingEnv("OPENAI_COMPAT_BASE_URL"));
+        }
+
+        let mock = MockProviderConfig {
+            latency_ms: parse_u64(vars, "MOCK_LATENCY_MS", 0)?,
+            fault: parse_mock_fault(vars)?,
+            fault_rate: parse_f32(vars, "MOCK_FAULT_RATE", 1.0)?,
+            fault_target: parse_mock_fault_target(vars)?,
+        };
+        if !(0.0..=1.0).contains(&mock.fault_rate) {
+            return Err(ConfigError::InvalidEnv {
+                key: "MOCK_FAULT_RATE",
+                value: mock.fault_rate.to_string(),
+                reason: "expected value between 0 and 1",
+            });
+        }
+
+        // 回退后的向量必须与索引处于同一向量空间，embedding 链只允许同一模型
+        let embed_models: Vec<&str> = embed_providers
+            .iter()
+            .map(|name| match name.as_str() {
+                INTERNAL_API => internal_api.embed_model.as_str(),
+                OPENAI_COMPAT => openai_compat
+                    .as_ref()
+                    .map(|api| api.embed_model.as_str())
+                    .unwrap_or_default(),
+                MOCK => MOCK_EMBED_MODEL,
+                _ => "",
+            })
+            .collect();
+        if embed_models.iter().any(|model| *model != embed_models[0]) {
+            return Err(ConfigError::InvalidEnv {
+                key: "EMBED_PROVIDERS",
+                value: embed_providers.join(","),
+                reason: "embedding fallback requires every provider to serve the same embed model",
+            });
+        }
+
+        let embedding_cache = EmbeddingCacheConfig {
+            enabled: parse_bool(vars, "EMBED_CACHE_ENABLED", true)?,
+            path: optional_var(vars, "EMBED_CACHE_PATH", "./.cache/embedding_cache.bin"),
+            max_entries: parse_usize(vars, "EMBED_CACHE_MAX_ENTRIES", 10_000)?,
+            flush_interval_secs: parse_u64(vars, "EMBED_CACHE_FLUSH_INTERVAL_SECS", 60)?,
+        };
+
+        let answer_cache = AnswerCacheConfig {
+            enabled: parse_bool(vars, "ANSWER_CACHE_ENABLED", false)?,
+            similarity_threshold: parse_f32(vars, "ANSWER_CACHE_SIMILARITY", 0.95)?,
+            max_entries: parse_usize(vars, "ANSWER_CACHE_MAX_ENTRIES", 256)?,
+            ttl_secs: parse_u64(vars, "ANSWER_CACHE_TTL_SECS", 3600)?,
         };
 
+        let prompt = PromptConfig {
+            dir: optional_var(vars, "PROMPT_TEMPLATE_DIR", "./prompts"),
+            default_template: optional_var(vars, "PROMPT_TEMPLATE", "default"),
+            reload_interval_secs: parse_u64(vars, "PROMPT_RELOAD_INTERVAL_SECS", 10)?,
+        };
+
+        let knowledge_watch = KnowledgeWatchConfig {
+            enabled: parse_bool(vars, "KNOWLEDGE_WATCH_ENABLED", false)?,
+            debounce_ms: parse_u64(vars, "KNOWLEDGE_WATCH_DEBOUNCE_MS", 2000)?,
+        };
+
+        let reindex_schedule = ReindexScheduleConfig {
+            schedule: parse_reindex_schedule(vars)?,
+            full: parse_bool(vars, "REINDEX_SCHEDULE_FULL", true)?,
+        };
+
+        let job_history = JobHistoryConfig {
+            path: optional_var(
+                vars,
+                "REINDEX_JOB_HISTORY_PATH",
+                "./.cache/reindex_jobs.json",
+            ),
+            max_entries: parse_usize(vars, "REINDEX_JOB_HISTORY_MAX", 50)?,
+        };
+
+        let index_versioning = IndexVersioningConfig {
+            blue_green: parse_bool(vars, "INDEX_BLUE_GREEN", true)?,
+            keep_versions: parse_usize(vars, "INDEX_KEEP_VERSIONS", 2)?,
+            max_failed_ratio: parse_f32(vars, "INDEX_MAX_FAILED_RATIO", 0.1)?,
+        };
+        if !(0.0..=1.0).contains(&index_versioning.max_failed_ratio) {
+            return Err(ConfigError::InvalidEnv {
+                key: "INDEX_MAX_FAILED_RATIO",
+                value: index_versioning.max_failed_ratio.to_string(),
+                reason: "expected value between 0 and 1",
+            });
+        }
+
+        let index_spec_policy = parse_index_spec_policy(vars)?;
+
+        let grounding = GroundingConfig {
+            enabled: parse_bool(vars, "GROUNDING_ENABLED", false)?,
+            mode: parse_grounding_mode(vars)?,
+            sentence_threshold: parse_f32(vars, "GROUNDING_SENTENCE_THRESHOLD", 0.75)?,
+            min_score: parse_f32(vars, "GROUNDING_MIN_SCORE", 0.6)?,
+            degrade_on_low_score: parse_bool(vars, "GROUNDING_DEGRADE", true)?,
+        };
+        if !(0.0..=1.0).contains(&grounding.min_score) {
+            return Err(ConfigError::InvalidEnv {
+                key: "GROUNDING_MIN_SCORE",
+                value: grounding.min_score.to_string(),
+                reason: "expected value between 0 and 1",
+            });
+        }
+
+        let query_rewrite = QueryRewriteConfig {
+            enabled: parse_bool(vars, "QUERY_REWRITE_ENABLED", false)?,
+            mode: parse_rewrite_mode(vars)?,
+            num_rewrites: parse_usize(vars, "QUERY_REWRITE_COUNT", 3)?,
+            rrf_k: parse_f32(vars, "QUERY_REWRITE_RRF_K", 60.0)?,
+        };
+
+        let query_limits = QueryLimitsConfig {
+            max_top_k: parse_u64(vars, "QUERY_MAX_TOP_K", 20)?,
+            max_question_chars: parse_usize(vars, "QUERY_MAX_QUESTION_CHARS", 2000)?,
+        };
+        if query_limits.max_top_k == 0 {
+            return Err(ConfigError::InvalidEnv {
+                key: "QUERY_MAX_TOP_K",
+                value: "0".to_string(),
+                reason: "expected positive integer",
+            });
+        }
+
         Ok(Self {
             host,
             port,
-            infer_provider: optional_var(vars, "INFER_PROVIDER", "internal_api"),
+            infer_provider,
+            chat_providers,
+            embed_providers,
             vector_store,
             lancedb_uri,
             lancedb_table,
+            memory_store_path,
+            qdrant,
+            pgvector,
             embedding_vector_size,
+            distance_metric,
             vector_score_threshold,
+            vector_index,
+            index_maintenance,
             knowledge_dir,
+            knowledge_watch,
+            reindex_schedule,
+            job_history,
+            index_versioning,
+            index_spec_policy,
             internal_api,
+            openai_compat,
+            mock,
+            embedding_cache,
+            answer_cache,
+            prompt,
+            grounding,
+            query_rewrite,
+            query_limits,
         })
     }
 }
@@ -137,6 +698,243 @@ fn optional_var(
         .unwrap_or_else(|| default.to_string())
 }
 
+fn parse_provider_list(
+    vars: &HashMap<String, String>,
+    key: &'static str,
+    default: &str,
+) -> Result<Vec<String>, ConfigError> {
+    let raw = vars
+        .get(key)
+        .map(|value| value.trim())
+        .filter(|value| !value.is_empty())
+        .unwrap_or(default);
+
+    let names: Vec<String> = raw
+        .split(',')
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .collect();
+
+    if names.is_empty() {
+        return Err(ConfigError::InvalidEnv {
+            key,
+            value: raw.to_string(),
+            reason: "expected at least one provider",
+        });
+    }
+
+    if let Some(unknown) = names
+        .iter()
+        .find(|name| !KNOWN_PROVIDERS.contains(&name.as_str()))
+    {
+        return Err(ConfigError::InvalidEnv {
+            key,
+            value: unknown.clone(),
+            reason: "unknown inference provider",
+        });
+    }
+
+    Ok(names)
+}
+
+fn parse_vector_store(vars: &HashMap<String, String>) -> Result<String, ConfigError> {
+    let name = optional_var(vars, "VECTOR_STORE", LANCEDB);
+    if !KNOWN_VECTOR_STORES.contains(&name.as_str()) {
+        return Err(ConfigError::InvalidEnv {
+            key: "VECTOR_STORE",
+            value: name,
+            reason: "unknown vector store",
+        });
+    }
+    Ok(name)
+}
+
+/// schema 名直接拼入 SQL，只允许小写标识符
+fn parse_pg_schema(vars: &HashMap<String, String>) -> Result<String, ConfigError> {
+    let schema = optional_var(vars, "PGVECTOR_SCHEMA", "public");
+    let valid = schema.starts_with(|c: char| c.is_ascii_lowercase() || c == '_')
+        && schema
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if !valid {
+        return Err(ConfigError::InvalidEnv {
+            key: "PGVECTOR_SCHEMA",
+            value: schema,
+            reason: "expected a lowercase identifier",
+        });
+    }
+    Ok(schema)
+}
+
+fn parse_mock_fault(vars: &HashMap<String, String>) -> Result<MockFault, ConfigError> {
+    match optional_var(vars, "MOCK_FAULT", "none").as_str() {
+        "none" => Ok(MockFault::None),
+        "rate_limit" => Ok(MockFault::RateLimit),
+        "server_error" => Ok(MockFault::ServerError),
+        "timeout" => Ok(MockFault::Timeout),
+        other => Err(ConfigError::InvalidEnv {
+            key: "MOCK_FAULT",
+            value: other.to_string(),
+            reason: "expected one of none, rate_limit, server_error, timeout",
+        }),
+    }
+}
+
+fn parse_mock_fault_target(vars: &HashMap<String, String>) -> Result<MockFaultTarget, ConfigError> {
+    match optional_var(vars, "MOCK_FAULT_TARGET", "all").as_str() {
+        "chat" => Ok(MockFaultTarget::Chat),
+        "embed" => Ok(MockFaultTarget::Embed),
+        "all" => Ok(MockFaultTarget::All),
+        other => Err(ConfigError::InvalidEnv {
+            key: "MOCK_FAULT_TARGET",
+            value: other.to_string(),
+            reason: "expected one of chat, embed, all",
+        }),
+    }
+}
+
+fn parse_grounding_mode(vars: &HashMap<String, String>) -> Result<GroundingMode, ConfigError> {
+    match optional_var(vars, "GROUNDING_MODE", "embedding").as_str() {
+        "embedding" => Ok(GroundingMode::Embedding),
+        "llm" => Ok(GroundingMode::Llm),
+        "hybrid" => Ok(GroundingMode::Hybrid),
+        other => Err(ConfigError::InvalidEnv {
+            key: "GROUNDING_MODE",
+            value: other.to_string(),
+            reason: "expected one of embedding, llm, hybrid",
+        }),
+    }
+}
+
+fn parse_index_spec_policy(vars: &HashMap<String, String>) -> Result<IndexSpecPolicy, ConfigError> {
+    match optional_var(vars, "INDEX_SPEC_MISMATCH", "refuse").as_str() {
+        "refuse" => Ok(IndexSpecPolicy::Refuse),
+        "warn" => Ok(IndexSpecPolicy::Warn),
+        "migrate" => Ok(IndexSpecPolicy::Migrate),
+        other => Err(ConfigError::InvalidEnv {
+            key: "INDEX_SPEC_MISMATCH",
+            value: other.to_string(),
+            reason: "expected one of refuse, warn, migrate",
+        }),
+    }
+}
+
+fn parse_distance_metric(vars: &HashMap<String, String>) -> Result<DistanceMetric, ConfigError> {
+    let raw = optional_var(vars, "VECTOR_DISTANCE_METRIC", "cosine");
+    DistanceMetric::from_name(&raw).ok_or(ConfigError::InvalidEnv {
+        key: "VECTOR_DISTANCE_METRIC",
+        value: raw,
+        reason: "expected one of cosine, l2, dot",
+    })
+}
+
+fn parse_vector_index_type(vars: &HashMap<String, String>) -> Result<VectorIndexType, ConfigError> {
+    match optional_var(vars, "VECTOR_INDEX_TYPE", "auto").as_str() {
+        "auto" => Ok(VectorIndexType::Auto),
+        "ivf_pq" => Ok(VectorIndexType::IvfPq),
+        "ivf_hnsw_pq" => Ok(VectorIndexType::IvfHnswPq),
+        "ivf_hnsw_sq" => Ok(VectorIndexType::IvfHnswSq),
+        other => Err(ConfigError::InvalidEnv {
+            key: "VECTOR_INDEX_TYPE",
+            value: other.to_string(),
+            reason: "expected one of auto, ivf_pq, ivf_hnsw_pq, ivf_hnsw_sq",
+        }),
+    }
+}
+
+fn parse_rewrite_mode(vars: &HashMap<String, String>) -> Result<RewriteMode, ConfigError> {
+    match optional_var(vars, "QUERY_REWRITE_MODE", "paraphrase").as_str() {
+        "paraphrase" => Ok(RewriteMode::Paraphrase),
+        "hyde" => Ok(RewriteMode::Hyde),
+        "both" => Ok(RewriteMode::Both),
+        other => Err(ConfigError::InvalidEnv {
+            key: "QUERY_REWRITE_MODE",
+            value: other.to_string(),
+            reason: "expected one of paraphrase, hyde, both",
+        }),
+    }
+}
+
+/// 解析 `REINDEX_SCHEDULE`；5 段 crontab 表达式自动补秒字段，
+/// 星期字段按 crontab 的编号（0 与 7 为周日）换算为 cron 库使用的英文缩写
+fn parse_reindex_schedule(
+    vars: &HashMap<String, String>,
+) -> Result<Option<cron::Schedule>, ConfigError> {
+    let raw = optional_var(vars, "REINDEX_SCHEDULE", "");
+    if raw.is_empty() {
+        return Ok(None);
+    }
+
+    let invalid = |raw: String| ConfigError::InvalidEnv {
+        key: "REINDEX_SCHEDULE",
+        value: raw,
+        reason: "expected cron expression",
+    };
+    let fields: Vec<&str> = raw.split_whitespace().collect();
+    let expression = if let [minute, hour, day, month, weekday] = fields[..] {
+        let Some(weekday) = crontab_day_of_week(weekday) else {
+            return Err(invalid(raw));
+        };
+        format!("0 {minute} {hour} {day} {month} {weekday}")
+    } else {
+        raw.clone()
+    };
+
+    cron::Schedule::from_str(&expression)
+        .map(Some)
+        .map_err(|_| invalid(raw))
+}
+
+const WEEKDAY_NAMES: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+/// 把 crontab 星期字段中的数字（0-7，0 与 7 为周日）换算为英文缩写；
+/// cron 库从 1 开始编号且 1 为周日，直接透传数字会整体错开一天。
+/// 含数字的区间展开为列表，`*`、`*/n` 与英文缩写原样保留
+fn crontab_day_of_week(field: &str) -> Option<String> {
+    let day = |value: &str| value.parse::<usize>().ok().filter(|day| *day <= 7);
+    let mut items = Vec::new();
+    for item in field.split(',') {
+        let (base, step) = match item.split_once('/') {
+            Some((base, step)) => (base, Some(step.parse::<usize>().ok().filter(|n| *n > 0)?)),
+            None => (item, None),
+        };
+        let range = match base.split_once('-') {
+            Some((start, end)) => day(start).zip(day(end)),
+            None => day(base).map(|start| (start, if step.is_some() { 7 } else { start })),
+        };
+        match range {
+            Some((start, end)) if start <= end => items.extend(
+                (start..=end)
+                    .step_by(step.unwrap_or(1))
+                    .map(|day| WEEKDAY_NAMES[day % 7].to_string()),
+            ),
+            Some(_) => return None,
+            None if base.bytes().any(|b| b.is_ascii_digit()) => return None,
+            None => items.push(item.to_string()),
+        }
+    }
+    Some(items.join(","))
+}
+
+fn parse_bool(
+    vars: &HashMap<String, String>,
+    key: &'static str,
+    default: bool,
+) -> Result<bool, ConfigError> {
+    match vars.get(key).map(|value| value.trim().to_ascii_lowercase()) {
+        Some(raw) if !raw.is_empty() => match raw.as_str() {
+            "true" | "1" | "yes" | "on" => Ok(true),
+            "false" | "0" | "no" | "off" => Ok(false),
+            _ => Err(ConfigError::InvalidEnv {
+                key,
+                value: raw,
+                reason: "expected boolean",
+            }),
+        },
+        _ => Ok(default),
+    }
+}
+
 fn parse_u16(
     vars: &HashMap<String, String>,
     key: &'static str,
@@ -167,6 +965,14 @@ fn parse_u32(
     }
 }
 
+/// 未设置或为 0 时返回 None（使用 LanceDB 默认值）
+fn parse_optional_u32(
+    vars: &HashMap<String, String>,
+    key: &'static str,
+) -> Result<Option<u32>, ConfigError> {
+    Ok(Some(parse_u32(vars, key, 0)?).filter(|value| *value > 0))
+}
+
 fn parse_u64(
     vars: &HashMap<String, String>,
     key: &'static str,
@@ -216,7 +1022,9 @@ fn parse_f32(
 mod tests {
     use std::collections::HashMap;
 
-    use super::{AppConfig, ConfigError};
+    use chrono::{Datelike, Weekday};
+
+    use super::{AppConfig, ConfigError, crontab_day_of_week};
 
     fn minimum_env() -> HashMap<String, String> {
         HashMap::from([
@@ -260,12 +1068,303 @@ mod tests {
         assert_eq!(config.host, "127.0.0.1");
         assert_eq!(config.port, 8080);
         assert_eq!(config.infer_provider, "internal_api");
+        assert_eq!(config.chat_providers, vec!["internal_api"]);
+        assert_eq!(config.embed_providers, vec!["internal_api"]);
+        assert!(config.openai_compat.is_none());
+        assert!(config.embedding_cache.enabled);
+        assert_eq!(config.embedding_cache.max_entries, 10_000);
+        assert!(!config.answer_cache.enabled);
+        assert_eq!(config.answer_cache.similarity_threshold, 0.95);
+        assert_eq!(config.prompt.default_template, "default");
+        assert!(!config.grounding.enabled);
+        assert_eq!(config.grounding.mode, super::GroundingMode::Embedding);
+        assert!(!config.query_rewrite.enabled);
+        assert_eq!(config.query_rewrite.mode, super::RewriteMode::Paraphrase);
+        assert_eq!(config.query_rewrite.num_rewrites, 3);
+        assert_eq!(config.query_limits.max_top_k, 20);
+        assert_eq!(config.query_limits.max_question_chars, 2000);
+        assert!(!config.knowledge_watch.enabled);
+        assert_eq!(config.knowledge_watch.debounce_ms, 2000);
+        assert!(config.reindex_schedule.schedule.is_none());
+        assert!(config.reindex_schedule.full);
+        assert_eq!(config.job_history.max_entries, 50);
+        assert!(config.index_versioning.blue_green);
+        assert_eq!(config.index_versioning.keep_versions, 2);
+        assert_eq!(config.index_spec_policy, super::IndexSpecPolicy::Refuse);
         assert_eq!(config.vector_store, "lancedb");
         assert_eq!(config.lancedb_table, "knowledge_chunks");
+        assert!(config.memory_store_path.is_none());
+        assert_eq!(config.pgvector.schema, "public");
         assert_eq!(config.embedding_vector_size, 1536);
         assert_eq!(config.vector_score_threshold, 0.3);
+        assert_eq!(config.distance_metric, super::DistanceMetric::Cosine);
+        assert_eq!(config.vector_index.index_type, super::VectorIndexType::Auto);
+        assert!(config.vector_index.nprobes.is_none());
+        assert_eq!(config.index_maintenance.interval_secs, 21_600);
         assert_eq!(config.internal_api.chat_path, "/v1/chat/completions");
         assert_eq!(config.internal_api.embed_model, "ad-embed-v1");
         assert_eq!(config.internal_api.retry_embed_max, 3);
+        assert_eq!(config.internal_api.retry_base_delay_ms, 200);
+        assert_eq!(config.internal_api.retry_chat_budget_ms, 5000);
+    }
+
+    #[test]
+    fn parses_reindex_schedule() {
+        let mut vars = minimum_env();
+        vars.insert("REINDEX_SCHEDULE".to_string(), "0 3 * * Sun".to_string());
+        vars.insert("REINDEX_SCHEDULE_FULL".to_string(), "false".to_string());
+        let config = AppConfig::from_map(&vars).expect("config should load");
+        assert!(config.reindex_schedule.schedule.is_some());
+        assert!(!config.reindex_schedule.full);
+
+        // crontab 的星期编号：0 与 7 为周日，1 为周一
+        for (weekday, expected) in [
+            ("1", Weekday::Mon),
+            ("0", Weekday::Sun),
+            ("7", Weekday::Sun),
+            ("6", Weekday::Sat),
+            ("sat", Weekday::Sat),
+        ] {
+            vars.insert("REINDEX_SCHEDULE".to_string(), format!("0 3 * * {weekday}"));
+            let config = AppConfig::from_map(&vars).expect("config should load");
+            let next = config
+                .reindex_schedule
+                .schedule
+                .unwrap()
+                .upcoming(chrono::Utc)
+                .next()
+                .unwrap();
+            assert_eq!(next.weekday(), expected, "weekday field `{weekday}`");
+        }
+
+        vars.insert("REINDEX_SCHEDULE".to_string(), "every sunday".to_string());
+        assert!(matches!(
+            AppConfig::from_map(&vars),
+            Err(ConfigError::InvalidEnv {
+                key: "REINDEX_SCHEDULE",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn rejects_out_of_range_prune_age() {
+        let mut vars = minimum_env();
+        vars.insert(
+            "INDEX_PRUNE_OLDER_THAN_SECS".to_string(),
+            u64::MAX.to_string(),
+        );
+        assert!(matches!(
+            AppConfig::from_map(&vars),
+            Err(ConfigError::InvalidEnv {
+                key: "INDEX_PRUNE_OLDER_THAN_SECS",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn translates_crontab_day_of_week() {
+        assert_eq!(crontab_day_of_week("1-5").unwrap(), "MON,TUE,WED,THU,FRI");
+        assert_eq!(crontab_day_of_week("5-7").unwrap(), "FRI,SAT,SUN");
+        assert_eq!(crontab_day_of_week("0,3").unwrap(), "SUN,WED");
+        assert_eq!(crontab_day_of_week("1-5/2").unwrap(), "MON,WED,FRI");
+        assert_eq!(crontab_day_of_week("*").unwrap(), "*");
+        assert_eq!(crontab_day_of_week("MON-FRI").unwrap(), "MON-FRI");
+        assert!(crontab_day_of_week("8").is_none());
+        assert!(crontab_day_of_week("5-1").is_none());
+    }
+
+    #[test]
+    fn parses_vector_store_selection() {
+        let mut vars = minimum_env();
+        vars.insert("VECTOR_STORE".to_string(), "memory".to_string());
+        vars.insert(
+            "MEMORY_STORE_PATH".to_string(),
+            "./.memory.json".to_string(),
+        );
+        let config = AppConfig::from_map(&vars).expect("config should load");
+        assert_eq!(config.vector_store, "memory");
+        assert_eq!(config.memory_store_path.as_deref(), Some("./.memory.json"));
+
+        vars.insert("VECTOR_STORE".to_string(), "qdrant".to_string());
+        vars.insert("QDRANT_URL".to_string(), "http://qdrant:6333".to_string());
+        let config = AppConfig::from_map(&vars).expect("config should load");
+        assert_eq!(config.qdrant.url, "http://qdrant:6333");
+        assert!(config.qdrant.api_key.is_none());
+        assert_eq!(config.vector_table(), "knowledge_chunks");
+
+        vars.insert("PGVECTOR_SCHEMA".to_string(), "engine-qa".to_string());
+        assert!(matches!(
+            AppConfig::from_map(&vars),
+            Err(ConfigError::InvalidEnv {
+                key: "PGVECTOR_SCHEMA",
+                ..
+            })
+        ));
+        vars.remove("PGVECTOR_SCHEMA");
+
+        vars.insert("VECTOR_STORE".to_string(), "faiss".to_string());
+        assert!(matches!(
+            AppConfig::from_map(&vars),
+            Err(ConfigError::InvalidEnv {
+                key: "VECTOR_STORE",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn parses_distance_metric() {
+        let mut vars = minimum_env();
+        vars.insert("VECTOR_DISTANCE_METRIC".to_string(), "dot".to_string());
+        let config = AppConfig::from_map(&vars).expect("config should load");
+        assert_eq!(config.distance_metric, super::DistanceMetric::Dot);
+
+        vars.insert("VECTOR_DISTANCE_METRIC".to_string(), "hamming".to_string());
+        assert!(matches!(
+            AppConfig::from_map(&vars),
+            Err(ConfigError::InvalidEnv {
+                key: "VECTOR_DISTANCE_METRIC",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn parses_vector_index_tuning() {
+        let mut vars = minimum_env();
+        vars.insert("VECTOR_INDEX_TYPE".to_string(), "ivf_hnsw_sq".to_string());
+        vars.insert("VECTOR_INDEX_PARTITIONS".to_string(), "64".to_string());
+        vars.insert("VECTOR_INDEX_HNSW_M".to_string(), "0".to_string());
+        vars.insert("VECTOR_SEARCH_NPROBES".to_string(), "32".to_string());
+        let config = AppConfig::from_map(&vars).expect("config should load");
+        assert_eq!(
+            config.vector_index.index_type,
+            super::VectorIndexType::IvfHnswSq
+        );
+        assert_eq!(config.vector_index.num_partitions, Some(64));
+        assert_eq!(config.vector_index.hnsw_m, None);
+        assert_eq!(config.vector_index.nprobes, Some(32));
+
+        vars.insert("VECTOR_INDEX_TYPE".to_string(), "flat".to_string());
+        assert!(matches!(
+            AppConfig::from_map(&vars),
+            Err(ConfigError::InvalidEnv {
+                key: "VECTOR_INDEX_TYPE",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn parses_provider_fallback_chain() {
+        let mut vars = minimum_env();
+        vars.insert(
+            "CHAT_PROVIDERS".to_string(),
+            "internal_api, openai_compat".to_string(),
+        );
+        vars.insert(
+            "OPENAI_COMPAT_BASE_URL".to_string(),
+            "https://api.example.com".to_string(),
+        );
+        vars.insert("OPENAI_COMPAT_API_KEY".to_string(), "sk-test".to_string());
+
+        let config = AppConfig::from_map(&vars).expect("config should load");
+        assert_eq!(config.chat_providers, vec!["internal_api", "openai_compat"]);
+        assert_eq!(config.embed_providers, vec!["internal_api"]);
+        let compat = config.openai_compat.expect("openai compat configured");
+        assert_eq!(compat.chat_model, "gpt-4o-mini");
+        assert_eq!(compat.llm_timeout_ms, config.internal_api.llm_timeout_ms);
+    }
+
+    #[test]
+    fn embed_fallback_requires_same_model() {
+        let mut vars = minimum_env();
+        vars.insert(
+            "EMBED_PROVIDERS".to_string(),
+            "internal_api,openai_compat".to_string(),
+        );
+        vars.insert(
+            "OPENAI_COMPAT_BASE_URL".to_string(),
+            "https://api.example.com".to_string(),
+        );
+        vars.insert("OPENAI_COMPAT_API_KEY".to_string(), "sk-test".to_string());
+
+        assert!(matches!(
+            AppConfig::from_map(&vars),
+            Err(ConfigError::InvalidEnv {
+                key: "EMBED_PROVIDERS",
+                ..
+            })
+        ));
+
+        vars.insert(
+            "OPENAI_COMPAT_EMBED_MODEL".to_string(),
+            "ad-embed-v1".to_string(),
+        );
+        let config = AppConfig::from_map(&vars).expect("same embed model should be accepted");
+        assert_eq!(
+            config.embed_providers,
+            vec!["internal_api", "openai_compat"]
+        );
+    }
+
+    #[test]
+    fn openai_compat_in_chain_requires_base_url() {
+        let mut vars = minimum_env();
+        vars.insert("CHAT_PROVIDERS".to_string(), "openai_compat".to_string());
+
+        let result = AppConfig::from_map(&vars);
+        assert_eq!(
+            result.unwrap_err(),
+            ConfigError::MissingEnv("OPENAI_COMPAT_BASE_URL")
+        );
+    }
+
+    #[test]
+    fn unknown_provider_in_chain_fails() {
+        let mut vars = minimum_env();
+        vars.insert("EMBED_PROVIDERS".to_string(), "nope".to_string());
+
+        assert!(matches!(
+            AppConfig::from_map(&vars),
+            Err(ConfigError::InvalidEnv {
+                key: "EMBED_PROVIDERS",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn mock_provider_does_not_require_internal_api() {
+        let vars = HashMap::from([
+            ("INFER_PROVIDER".to_string(), "mock".to_string()),
+            ("MOCK_FAULT".to_string(), "rate_limit".to_string()),
+            ("MOCK_FAULT_RATE".to_string(), "0.5".to_string()),
+        ]);
+
+        let config = AppConfig::from_map(&vars).expect("config should load");
+        assert_eq!(config.chat_providers, vec!["mock"]);
+        assert!(config.internal_api.base_url.is_empty());
+        assert_eq!(config.mock.fault, super::MockFault::RateLimit);
+        assert_eq!(config.mock.fault_rate, 0.5);
+        assert_eq!(config.mock.fault_target, super::MockFaultTarget::All);
+    }
+
+    #[test]
+    fn invalid_mock_fault_fails() {
+        let vars = HashMap::from([
+            ("INFER_PROVIDER".to_string(), "mock".to_string()),
+            ("MOCK_FAULT".to_string(), "explode".to_string()),
+        ]);
+
+        assert!(matches!(
+            AppConfig::from_map(&vars),
+            Err(ConfigError::InvalidEnv {
+                key: "MOCK_FAULT",
+                ..
+            })
+        ));
     }
 }
@@ -1,15 +1,22 @@
+pub mod scheduler;
+pub mod watcher;
+
 use crate::{
-    config::InternalApiConfig,
-    provider::{InferenceProvider, InternalApiProvider, ProviderError},
-    vector_store::{StoredChunk, VectorStore, VectorStoreError},
+    cache::embedding::{EmbeddingCache, embed_cached},
+    config::IndexVersioningConfig,
+    provider::{InferenceProvider, ProviderError},
+    vector_store::{DocumentSummary, StoredChunk, VectorStore, VectorStoreError, spec_mismatch},
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::{
     collections::{HashMap, HashSet},
     fs,
-    path::{Path, PathBuf},
-    sync::Arc,
+    path::{Component, Path, PathBuf},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
     time::Instant,
 };
 use tracing::{debug, error, info, warn};
@@ -17,7 +24,7 @@ use tracing::{debug, error, info, warn};
 const DEFAULT_CHUNK_SIZE: usize = 1000; // 中文字符数
 const DEFAULT_OVERLAP: usize = 125; // 中文字符数
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IndexResult {
     pub total_files: usize,
     pub indexed_files: usize,
@@ -28,6 +35,218 @@ pub struct IndexResult {
     pub failed_chunks: usize,
     pub deleted_chunks: usize,
     pub duration_ms: u128,
+    /// 每个文件的处理结果
+    pub files: Vec<FileOutcome>,
+    /// 蓝绿构建生成并切换到的索引版本
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// 任务被取消，结果只包含取消前处理完的文件
+    #[serde(default)]
+    pub cancelled: bool,
+}
+
+/// 单个文件在索引任务中的处理状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileStatus {
+    /// 已重新分块并写入
+    Indexed,
+    /// 内容未变化，跳过
+    Unchanged,
+    /// 源文件已删除，索引中的片段被清理
+    Deleted,
+    /// 处理失败
+    Failed,
+    /// 指定的路径既不在知识库目录中，也不在索引中
+    NotFound,
+}
+
+/// 单个文件的处理结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileOutcome {
+    pub path: String,
+    pub doc_id: String,
+    pub status: FileStatus,
+    pub chunks: usize,
+    /// 内容未变化、未重新向量化的片段数
+    #[serde(default)]
+    pub reused_chunks: usize,
+    pub failed_chunks: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl FileOutcome {
+    fn new(path: String, doc_id: String, status: FileStatus) -> Self {
+        Self {
+            path,
+            doc_id,
+            status,
+            chunks: 0,
+            reused_chunks: 0,
+            failed_chunks: 0,
+            error: None,
+        }
+    }
+}
+
+/// 索引任务的实时进度
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IndexProgress {
+    pub files_total: usize,
+    pub files_done: usize,
+    pub chunks_embedded: usize,
+    pub current_file: Option<String>,
+    /// 按已完成文件的平均耗时估算的剩余秒数
+    pub eta_secs: Option<u64>,
+}
+
+#[derive(Debug, Default)]
+struct ProgressState {
+    progress: IndexProgress,
+    started: Option<Instant>,
+}
+
+/// 索引任务的进度上报与协作式取消，clone 后共享同一状态
+#[derive(Debug, Clone, Default)]
+pub struct IndexControl {
+    state: Arc<Mutex<ProgressState>>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl IndexControl {
+    /// 请求取消；索引在处理下一个文件前检查该标记
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// 当前进度快照
+    pub fn progress(&self) -> IndexProgress {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let mut progress = state.progress.clone();
+
+        let remaining = progress.files_total.saturating_sub(progress.files_done);
+        progress.eta_secs = match state.started {
+            Some(started) if progress.files_done > 0 => {
+                let per_file = started.elapsed().as_secs_f64() / progress.files_done as f64;
+                Some((per_file * remaining as f64).ceil() as u64)
+            }
+            _ => None,
+        };
+        progress
+    }
+
+    fn update(&self, f: impl FnOnce(&mut ProgressState)) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        f(&mut state);
+    }
+
+    fn start(&self, files_total: usize) {
+        self.update(|state| {
+            state.progress.files_total = files_total;
+            state.started = Some(Instant::now());
+        });
+    }
+
+    fn begin_file(&self, path: &str) {
+        self.update(|state| state.progress.current_file = Some(path.to_string()));
+    }
+
+    fn finish_file(&self) {
+        self.update(|state| {
+            state.progress.files_done += 1;
+            state.progress.current_file = None;
+        });
+    }
+
+    fn chunk_embedded(&self) {
+        self.update(|state| state.progress.chunks_embedded += 1);
+    }
+}
+
+/// 索引范围：默认为整个知识库目录，可限定为若干文件或路径前缀
+#[derive(Debug, Clone, Default)]
+pub struct IndexScope {
+    paths: Vec<PathBuf>,
+    prefix: Option<String>,
+}
+
+impl IndexScope {
+    /// 校验并规范化请求中的路径与前缀，二者均为知识库目录内的相对路径
+    pub fn new(paths: &[String], prefix: Option<&str>) -> IndexerResult<Self> {
+        let paths = paths
+            .iter()
+            .map(|path| {
+                sanitize_relative_path(path).ok_or_else(|| IndexerError::InvalidPath(path.clone()))
+            })
+            .collect::<IndexerResult<Vec<_>>>()?;
+
+        let prefix = prefix
+            .map(|prefix| {
+                sanitize_prefix(prefix).ok_or_else(|| IndexerError::InvalidPath(prefix.to_string()))
+            })
+            .transpose()?;
+
+        Ok(Self { paths, prefix })
+    }
+
+    pub fn is_all(&self) -> bool {
+        self.paths.is_empty() && self.prefix.is_none()
+    }
+
+    fn contains(&self, relative_path: &str) -> bool {
+        if self.is_all() {
+            return true;
+        }
+
+        let normalized = relative_path.replace('\\', "/");
+        self.paths.iter().any(|path| Path::new(&normalized) == path)
+            || self
+                .prefix
+                .as_ref()
+                .is_some_and(|prefix| normalized.starts_with(prefix.as_str()))
+    }
+}
+
+/// 单篇文档的索引结果
+#[derive(Debug, Clone, Serialize)]
+pub struct DocumentIndexResult {
+    pub doc_id: String,
+    pub path: String,
+    pub chunk_ids: Vec<String>,
+    /// 内容未变化、直接沿用的片段数
+    pub reused_chunks: usize,
+    pub total_chunks: usize,
+    pub failed_chunks: usize,
+}
+
+impl DocumentIndexResult {
+    /// 转为单文件索引任务的结果，用于记入任务历史
+    pub fn to_index_result(&self, duration_ms: u128) -> IndexResult {
+        IndexResult {
+            total_files: 1,
+            indexed_files: 1,
+            skipped_files: 0,
+            failed_files: 0,
+            total_chunks: self.total_chunks,
+            successful_chunks: self.chunk_ids.len(),
+            failed_chunks: self.failed_chunks,
+            deleted_chunks: 0,
+            duration_ms,
+            files: vec![FileOutcome {
+                chunks: self.chunk_ids.len(),
+                reused_chunks: self.reused_chunks,
+                failed_chunks: self.failed_chunks,
+                ..FileOutcome::new(self.path.clone(), self.doc_id.clone(), FileStatus::Indexed)
+            }],
+            version: None,
+            cancelled: false,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -57,45 +276,87 @@ pub enum IndexerError {
 
     #[error("Markdown parsing error: {0}")]
     ParseError(String),
+
+    #[error("Invalid document path: {0}")]
+    InvalidPath(String),
+
+    #[error("New index version rejected: {0}")]
+    ValidationFailed(String),
 }
 
 pub type IndexerResult<T> = Result<T, IndexerError>;
 
 pub struct MarkdownIndexer {
-    provider: InternalApiProvider,
+    provider: Arc<dyn InferenceProvider>,
+    embedding_cache: Arc<EmbeddingCache>,
     vector_store: Arc<dyn VectorStore>,
     knowledge_dir: PathBuf,
+    versioning: IndexVersioningConfig,
     chunk_size: usize,
     overlap: usize,
 }
 
 impl MarkdownIndexer {
     pub fn new(
-        config: InternalApiConfig,
+        provider: Arc<dyn InferenceProvider>,
+        embedding_cache: Arc<EmbeddingCache>,
         vector_store: Arc<dyn VectorStore>,
         knowledge_dir: &str,
+        versioning: &IndexVersioningConfig,
     ) -> IndexerResult<Self> {
-        let provider = InternalApiProvider::new(config.clone());
         let knowledge_dir = PathBuf::from(knowledge_dir);
 
         Ok(Self {
             provider,
+            embedding_cache,
             vector_store,
             knowledge_dir,
+            versioning: versioning.clone(),
             chunk_size: DEFAULT_CHUNK_SIZE,
             overlap: DEFAULT_OVERLAP,
         })
     }
 
     pub async fn index(&self, full_rebuild: bool) -> IndexerResult<IndexResult> {
+        self.index_scoped(
+            full_rebuild,
+            &IndexScope::default(),
+            &IndexControl::default(),
+        )
+        .await
+    }
+
+    /// 只处理范围内的文件；范围内已删除的源文件同样会清理其片段
+    ///
+    /// 进度写入 `control`；取消后在下一个文件前停止，返回 `cancelled` 为 true 的结果，
+    /// 其中只包含已处理文件的结果。已处理的文件保持写入状态，
+    /// 未处理的文件与过期片段清理均被跳过。
+    ///
+    /// 启用蓝绿构建时，全量重建写入新版本，校验通过后才切换查询，
+    /// 取消或校验失败都会丢弃新版本，当前版本不受影响。
+    pub async fn index_scoped(
+        &self,
+        full_rebuild: bool,
+        scope: &IndexScope,
+        control: &IndexControl,
+    ) -> IndexerResult<IndexResult> {
         let start = Instant::now();
 
-        info!(full_rebuild, "starting markdown indexing");
+        info!(
+            full_rebuild,
+            scoped = !scope.is_all(),
+            "starting markdown indexing"
+        );
 
-        // Step 1: Scan all markdown files
-        let md_files = self.scan_markdown_files().await?;
+        // Step 1: Scan markdown files within scope
+        let md_files: Vec<(PathBuf, String)> = self
+            .scan_markdown_files()
+            .await?
+            .into_iter()
+            .filter(|(path, _)| scope.contains(&self.relative_path(path)))
+            .collect();
 
-        if md_files.is_empty() {
+        if md_files.is_empty() && scope.is_all() {
             warn!("no markdown files found in {:?}", self.knowledge_dir);
             return Ok(IndexResult {
                 total_files: 0,
@@ -107,9 +368,41 @@ impl MarkdownIndexer {
                 failed_chunks: 0,
                 deleted_chunks: 0,
                 duration_ms: start.elapsed().as_millis(),
+                files: Vec::new(),
+                version: None,
+                cancelled: false,
             });
         }
 
+        // embedding 模型、维度或距离度量变化时必须写入按新参数建的新表，不受蓝绿开关影响
+        let rebuild_all = full_rebuild && scope.is_all();
+        let migrating = rebuild_all && spec_mismatch(self.vector_store.as_ref()).await?.is_some();
+        if migrating {
+            info!(spec = %self.vector_store.spec(), "embedding spec changed, rebuilding into a new index version");
+        }
+
+        let staging = if rebuild_all && (self.versioning.blue_green || migrating) {
+            match self.vector_store.create_version().await {
+                Ok(staging) => Some(staging),
+                // 不能把新维度的向量写进旧表
+                Err(VectorStoreError::Unsupported(_)) if migrating => {
+                    return Err(VectorStoreError::MigrationUnsupported {
+                        stored: self.vector_store.stored_spec().await?,
+                        expected: self.vector_store.spec().clone(),
+                    }
+                    .into());
+                }
+                Err(VectorStoreError::Unsupported(_)) => None,
+                Err(e) => return Err(e.into()),
+            }
+        } else {
+            None
+        };
+        let target: &dyn VectorStore = match &staging {
+            Some((_, store)) => store.as_ref(),
+            None => self.vector_store.as_ref(),
+        };
+
         // Step 2: Inspect existing metadata from vector store
         let existing_files = self.vector_store.list_doc_hashes().await?;
 
@@ -119,8 +412,6 @@ impl MarkdownIndexer {
         } else {
             self.determine_files_to_index(&md_files, &existing_files)
         };
-        let indexed_files = files_to_index.len();
-
         info!(
             total_files = md_files.len(),
             files_to_index = files_to_index.len(),
@@ -128,29 +419,132 @@ impl MarkdownIndexer {
         );
 
         // Step 4: Process each file
+        let mut indexed_files = 0usize;
+        let mut cancelled = false;
         let mut total_chunks = 0usize;
         let mut successful_chunks = 0usize;
         let mut failed_chunks = 0usize;
         let mut failed_files = 0usize;
+        let mut files = Vec::new();
+
+        for (path, _) in &md_files {
+            if !files_to_index.iter().any(|(p, _)| p == path) {
+                let relative_path = self.relative_path(path);
+                let doc_id = compute_doc_id(&relative_path);
+                files.push(FileOutcome::new(
+                    relative_path,
+                    doc_id,
+                    FileStatus::Unchanged,
+                ));
+            }
+        }
+
+        control.start(files_to_index.len());
 
         for (path, hash) in files_to_index {
-            match self.process_file(&path, &hash).await {
-                Ok((successful, failed, total)) => {
-                    total_chunks += total;
-                    successful_chunks += successful;
-                    failed_chunks += failed;
+            if control.is_cancelled() {
+                warn!(indexed_files, "indexing cancelled");
+                cancelled = true;
+                break;
+            }
+            indexed_files += 1;
+            control.begin_file(&self.relative_path(&path));
+
+            let processed = self
+                .process_file(&path, &hash, target, full_rebuild, control)
+                .await;
+            control.finish_file();
+
+            match processed {
+                Ok(result) => {
+                    total_chunks += result.total_chunks;
+                    successful_chunks += result.chunk_ids.len();
+                    failed_chunks += result.failed_chunks;
+                    files.push(FileOutcome {
+                        chunks: result.chunk_ids.len(),
+                        reused_chunks: result.reused_chunks,
+                        failed_chunks: result.failed_chunks,
+                        ..FileOutcome::new(result.path, result.doc_id, FileStatus::Indexed)
+                    });
                 }
                 Err(e) => {
                     error!(file = %path.to_string_lossy(), error = %e, "failed to process file");
                     failed_files += 1;
+                    let relative_path = self.relative_path(&path);
+                    let doc_id = compute_doc_id(&relative_path);
+                    files.push(FileOutcome {
+                        error: Some(e.to_string()),
+                        ..FileOutcome::new(relative_path, doc_id, FileStatus::Failed)
+                    });
                 }
             }
         }
 
         // Step 5: Delete chunks from files that no longer exist
-        let deleted_chunks = self
-            .delete_obsolete_chunks(existing_files.keys().cloned().collect(), &md_files)
-            .await?;
+        // （蓝绿构建的新版本本就不含这些文件，只记录结果）
+        let mut deleted_chunks = 0usize;
+        let obsolete = if cancelled {
+            Vec::new()
+        } else {
+            self.obsolete_documents(scope, &md_files).await?
+        };
+        for document in obsolete {
+            let chunks = match staging {
+                Some(_) => document.chunk_count,
+                None => {
+                    let chunks = self.vector_store.delete_by_doc_id(&document.doc_id).await?;
+                    info!(doc_id = %document.doc_id, chunks, "deleted chunks of removed file");
+                    chunks
+                }
+            };
+            deleted_chunks += chunks;
+            files.push(FileOutcome {
+                chunks,
+                ..FileOutcome::new(document.path, document.doc_id, FileStatus::Deleted)
+            });
+        }
+
+        // 显式指定但既不存在于磁盘也不在索引中的路径
+        let requested: &[PathBuf] = if cancelled { &[] } else { &scope.paths };
+        for path in requested {
+            let relative_path = path.to_string_lossy().to_string();
+            if !files
+                .iter()
+                .any(|outcome| Path::new(&outcome.path.replace('\\', "/")) == path)
+            {
+                let doc_id = compute_doc_id(&relative_path);
+                files.push(FileOutcome::new(
+                    relative_path,
+                    doc_id,
+                    FileStatus::NotFound,
+                ));
+            }
+        }
+
+        if let Err(e) = self.embedding_cache.flush().await {
+            warn!(error = %e, "failed to flush embedding cache");
+        }
+        self.vector_store.flush().await?;
+
+        let version = match staging {
+            Some((version, _)) if cancelled => {
+                self.discard_version(&version).await;
+                None
+            }
+            Some((version, store)) => {
+                let stats = BuildStats {
+                    files: indexed_files,
+                    failed_files,
+                    chunks: total_chunks,
+                    successful_chunks,
+                    failed_chunks,
+                };
+                self.promote_version(&version, store.as_ref(), &stats)
+                    .await?;
+                Some(version)
+            }
+            None => None,
+        };
 
         let duration_ms = start.elapsed().as_millis();
 
@@ -160,6 +554,7 @@ impl MarkdownIndexer {
             successful_chunks,
             failed_chunks,
             deleted_chunks,
+            cancelled,
             duration_ms = duration_ms,
             "indexing complete"
         );
@@ -174,9 +569,59 @@ impl MarkdownIndexer {
             failed_chunks,
             deleted_chunks,
             duration_ms,
+            files,
+            version,
+            cancelled,
         })
     }
 
+    /// 校验新版本并切换，随后只保留最近的若干旧版本
+    async fn promote_version(
+        &self,
+        version: &str,
+        store: &dyn VectorStore,
+        stats: &BuildStats,
+    ) -> IndexerResult<()> {
+        let rows = store.count().await?;
+        if let Err(reason) = stats.validate(rows, self.versioning.max_failed_ratio) {
+            warn!(version = %version, reason = %reason, "discarding new index version");
+            self.discard_version(version).await;
+            return Err(IndexerError::ValidationFailed(reason));
+        }
+
+        self.vector_store.activate_version(version).await?;
+        info!(version = %version, rows, "index version activated");
+
+        let previous: Vec<String> = self
+            .vector_store
+            .list_versions()
+            .await?
+            .into_iter()
+            .filter(|v| !v.active)
+            .map(|v| v.name)
+            .collect();
+        let excess = previous.len().saturating_sub(self.versioning.keep_versions);
+        for name in &previous[..excess] {
+            if let Err(e) = self.vector_store.drop_version(name).await {
+                warn!(version = %name, error = %e, "failed to drop old index version");
+            }
+        }
+        Ok(())
+    }
+
+    async fn discard_version(&self, version: &str) {
+        if let Err(e) = self.vector_store.drop_version(version).await {
+            warn!(version = %version, error = %e, "failed to drop discarded index version");
+        }
+    }
+
+    fn relative_path(&self, path: &Path) -> String {
+        path.strip_prefix(&self.knowledge_dir)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .to_string()
+    }
+
     async fn scan_markdown_files(&self) -> IndexerResult<Vec<(PathBuf, String)>> {
         let mut files = Vec::new();
 
@@ -188,16 +633,25 @@ impl MarkdownIndexer {
             return Ok(files);
         }
 
-        let entries = fs::read_dir(&self.knowledge_dir)?;
+        // 递归扫描子目录，跳过隐藏文件与目录
+        let mut dirs = vec![self.knowledge_dir.clone()];
 
-        for entry in entries {
-            let entry = entry?;
-            let path = entry.path();
+        while let Some(dir) = dirs.pop() {
+            for entry in fs::read_dir(&dir)? {
+                let entry = entry?;
+                let path = entry.path();
+
+                if entry.file_name().to_string_lossy().starts_with('.') {
+                    continue;
+                }
 
-            if path.is_file() && path.extension().is_some_and(|ext| ext == "md") {
-                let content = fs::read_to_string(&path)?;
-                let hash = compute_hash(&content);
-                files.push((path, hash));
+                if path.is_dir() {
+                    dirs.push(path);
+                } else if path.is_file() && path.extension().is_some_and(|ext| ext == "md") {
+                    let content = fs::read_to_string(&path)?;
+                    let hash = compute_hash(&content);
+                    files.push((path, hash));
+                }
             }
         }
 
@@ -238,7 +692,58 @@ impl MarkdownIndexer {
         files_to_index
     }
 
-    async fn process_file(&self, path: &Path, _hash: &str) -> IndexerResult<(usize, usize, usize)> {
+    /// 将知识库内的相对路径解析为绝对路径，拒绝目录穿越、绝对路径及非 Markdown 文件
+    pub fn resolve_document_path(&self, relative_path: &str) -> IndexerResult<PathBuf> {
+        let relative = sanitize_relative_path(relative_path)
+            .ok_or_else(|| IndexerError::InvalidPath(relative_path.to_string()))?;
+        Ok(self.knowledge_dir.join(relative))
+    }
+
+    /// 仅重新索引单个文件（路径须位于知识库目录内）
+    pub async fn index_document(
+        &self,
+        path: &Path,
+        control: &IndexControl,
+    ) -> IndexerResult<DocumentIndexResult> {
+        let content = fs::read_to_string(path)?;
+        let hash = compute_hash(&content);
+        control.start(1);
+        control.begin_file(&self.relative_path(path));
+        let processed = self
+            .process_file(path, &hash, self.vector_store.as_ref(), false, control)
+            .await;
+        control.finish_file();
+        // 处理失败前已写入的片段同样落盘
+        self.vector_store.flush().await?;
+        let result = processed?;
+
+        if let Err(e) = self.embedding_cache.flush().await {
+            warn!(error = %e, "failed to flush embedding cache");
+        }
+
+        info!(
+            doc_id = %result.doc_id,
+            chunks = result.chunk_ids.len(),
+            failed = result.failed_chunks,
+            "document indexed"
+        );
+        Ok(result)
+    }
+
+    /// 分块、向量化并写入单个文件
+    ///
+    /// 片段按 `point_id`（doc_id + chunk_id + 内容哈希）upsert；`force` 为 false 时
+    /// 文档内内容哈希相同的已有片段直接复用向量，不重新向量化，但仍按新的位置、
+    /// 标题路径和文档哈希重写。写入完成后才删除该文档的过期片段，
+    /// 过程中查询始终能看到该文档。
+    async fn process_file(
+        &self,
+        path: &Path,
+        hash: &str,
+        store: &dyn VectorStore,
+        force: bool,
+        control: &IndexControl,
+    ) -> IndexerResult<DocumentIndexResult> {
         let content = fs::read_to_string(path)?;
         let relative_path = path
             .strip_prefix(&self.knowledge_dir)
@@ -247,21 +752,47 @@ impl MarkdownIndexer {
             .to_string();
         let doc_id = compute_doc_id(&relative_path);
 
-        // First, delete all existing chunks for this file
-        self.vector_store.delete_by_doc_id(&doc_id).await?;
+        // Vectors of existing chunks keyed by content hash, reused regardless of position
+        let existing = if force {
+            HashMap::new()
+        } else {
+            store.chunk_vectors(&doc_id).await?
+        };
 
         // Parse and chunk the markdown
         let chunks = self.parse_and_chunk(&content, &relative_path, &doc_id)?;
         let total_chunks = chunks.len();
 
-        // Embed then batch-upsert
+        // Embed changed chunks then batch-upsert
+        let indexed_at = chrono::Utc::now().to_rfc3339();
         let mut records = Vec::new();
+        let mut chunk_ids = Vec::new();
+        let mut keep_point_ids = Vec::new();
+        let mut reused = 0usize;
         let mut failed = 0usize;
 
         for chunk in chunks {
-            match self.provider.embed(&chunk.text).await {
+            let point_id = compute_point_id(&chunk.doc_id, &chunk.chunk_id, &chunk.hash);
+            let embedded = match existing.get(&chunk.hash) {
+                Some(vector) => {
+                    reused += 1;
+                    Ok(vector.clone())
+                }
+                None => {
+                    let embedded =
+                        embed_cached(self.provider.as_ref(), &self.embedding_cache, &chunk.text)
+                            .await;
+                    if embedded.is_ok() {
+                        control.chunk_embedded();
+                    }
+                    embedded
+                }
+            };
+
+            match embedded {
                 Ok(vector) => {
-                    let point_id = format!("{}|{}|{}", chunk.doc_id, chunk.chunk_id, chunk.hash);
+                    chunk_ids.push(chunk.chunk_id.clone());
+                    keep_point_ids.push(point_id.clone());
                     records.push(StoredChunk {
                         point_id,
                         doc_id: chunk.doc_id,
@@ -271,6 +802,8 @@ impl MarkdownIndexer {
                         section: chunk.section,
                         text: chunk.text,
                         hash: chunk.hash,
+                        doc_hash: hash.to_string(),
+                        indexed_at: indexed_at.clone(),
                         vector,
                     });
                 }
@@ -282,11 +815,19 @@ impl MarkdownIndexer {
         }
 
         if !records.is_empty() {
-            self.vector_store.upsert_chunks(records).await?;
+            store.upsert_chunks(records).await?;
         }
-
-        let successful = total_chunks - failed;
-        Ok((successful, failed, total_chunks))
+        let stale = store.delete_stale_chunks(&doc_id, &keep_point_ids).await?;
+        debug!(doc_id = %doc_id, reused, stale, failed, "file processed");
+
+        Ok(DocumentIndexResult {
+            doc_id,
+            path: relative_path,
+            chunk_ids,
+            reused_chunks: reused,
+            total_chunks,
+            failed_chunks: failed,
+        })
     }
 
     fn parse_and_chunk(
@@ -363,6 +904,8 @@ impl MarkdownIndexer {
 
     fn create_overlapping_chunks(&self, chunks: Vec<Chunk>) -> IndexerResult<Vec<Chunk>> {
         let mut result = Vec::new();
+        // 片段序号在整篇文档内递增，保证 chunk_id 唯一
+        let mut chunk_num = 0;
 
         for chunk in chunks {
             let chars: Vec<char> = chunk.text.chars().collect();
@@ -370,14 +913,14 @@ impl MarkdownIndexer {
 
             if total_chars <= self.chunk_size {
                 result.push(Chunk {
-                    chunk_id: format!("{}_chunk_0", chunk.doc_id),
+                    chunk_id: format!("{}_chunk_{}", chunk.doc_id, chunk_num),
                     ..chunk
                 });
+                chunk_num += 1;
                 continue;
             }
 
             let mut start = 0;
-            let mut chunk_num = 0;
 
             while start < total_chars {
                 let end = (start + self.chunk_size).min(total_chars);
@@ -394,45 +937,80 @@ impl MarkdownIndexer {
                     hash,
                 });
 
+                chunk_num += 1;
                 if end == total_chars {
                     break;
                 }
 
                 start += self.chunk_size - self.overlap;
-                chunk_num += 1;
             }
         }
 
         Ok(result)
     }
 
-    async fn delete_obsolete_chunks(
+    /// 索引中属于范围内、但源文件已不存在的文档
+    async fn obsolete_documents(
         &self,
-        existing_doc_ids: HashSet<String>,
+        scope: &IndexScope,
         current_files: &[(PathBuf, String)],
-    ) -> IndexerResult<usize> {
+    ) -> IndexerResult<Vec<DocumentSummary>> {
         let current_doc_ids: HashSet<String> = current_files
             .iter()
-            .map(|(path, _)| {
-                let relative_path = path
-                    .strip_prefix(&self.knowledge_dir)
-                    .unwrap_or(path)
-                    .to_string_lossy()
-                    .to_string();
-                compute_doc_id(&relative_path)
-            })
+            .map(|(path, _)| compute_doc_id(&self.relative_path(path)))
             .collect();
 
-        let mut deleted_count = 0usize;
+        Ok(self
+            .vector_store
+            .list_documents()
+            .await?
+            .into_iter()
+            .filter(|document| {
+                !current_doc_ids.contains(&document.doc_id) && scope.contains(&document.path)
+            })
+            .collect())
+    }
+}
 
-        for doc_id in existing_doc_ids {
-            if !current_doc_ids.contains(&doc_id) {
-                self.vector_store.delete_by_doc_id(&doc_id).await?;
-                deleted_count += 1;
-            }
+/// 蓝绿构建的统计，用于切换前校验
+struct BuildStats {
+    files: usize,
+    failed_files: usize,
+    chunks: usize,
+    successful_chunks: usize,
+    failed_chunks: usize,
+}
+
+impl BuildStats {
+    fn validate(&self, rows: usize, max_failed_ratio: f32) -> Result<(), String> {
+        if self.successful_chunks == 0 {
+            return Err("no chunks were indexed".to_string());
+        }
+        if rows != self.successful_chunks {
+            return Err(format!(
+                "row count mismatch: table has {rows}, expected {}",
+                self.successful_chunks
+            ));
         }
 
-        Ok(deleted_count)
+        let ratio = |failed: usize, total: usize| {
+            if total == 0 {
+                0.0
+            } else {
+                failed as f32 / total as f32
+            }
+        };
+        let file_ratio = ratio(self.failed_files, self.files);
+        let chunk_ratio = ratio(self.failed_chunks, self.chunks);
+        if file_ratio > max_failed_ratio || chunk_ratio > max_failed_ratio {
+            return Err(format!(
+                "failure ratio too high: files {:.1}%, chunks {:.1}% (limit {:.1}%)",
+                file_ratio * 100.0,
+                chunk_ratio * 100.0,
+                max_failed_ratio * 100.0
+            ));
+        }
+        Ok(())
     }
 }
 
@@ -442,6 +1020,293 @@ fn compute_hash(content: &str) -> String {
     format!("{:x}", hasher.finalize())
 }
 
+fn compute_point_id(doc_id: &str, chunk_id: &str, hash: &str) -> String {
+    format!("{doc_id}|{chunk_id}|{hash}")
+}
+
+/// 文档 ID 为相对路径（统一为 `/` 分隔）的 SHA-256，与 Python 后端一致，
+/// 避免 `a/b.md` 与 `a_b.md` 之类的路径映射到同一文档
 fn compute_doc_id(path: &str) -> String {
-    path.replace('/', "_").replace('\\', "_")
+    compute_hash(&path.replace('\\', "/"))
+}
+
+/// 规范化用户提供的相对路径：只允许普通路径段，禁止 `..`、绝对路径与隐藏文件，
+/// 且必须为 `.md` 文件
+pub fn sanitize_relative_path(path: &str) -> Option<PathBuf> {
+    let normalized = path.trim().replace('\\', "/");
+    if normalized.is_empty() || normalized.chars().any(char::is_control) {
+        return None;
+    }
+
+    let mut relative = PathBuf::new();
+    for component in Path::new(&normalized).components() {
+        match component {
+            Component::Normal(part) if !part.to_string_lossy().starts_with('.') => {
+                relative.push(part)
+            }
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+
+    if relative.extension().is_some_and(|ext| ext == "md") {
+        Some(relative)
+    } else {
+        None
+    }
+}
+
+/// 规范化路径前缀：禁止 `..`、绝对路径，统一使用 `/` 分隔
+fn sanitize_prefix(prefix: &str) -> Option<String> {
+    let normalized = prefix.trim().replace('\\', "/");
+    let normalized = normalized.trim_start_matches("./");
+    if normalized.is_empty()
+        || normalized.starts_with('/')
+        || normalized.chars().any(char::is_control)
+        || normalized.split('/').any(|part| part == "..")
+    {
+        return None;
+    }
+    Some(normalized.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::{
+            DistanceMetric, EmbeddingCacheConfig, MockFault, MockFaultTarget, MockProviderConfig,
+        },
+        provider::mock::MockProvider,
+        vector_store::{IndexSpec, memory_store::MemoryStore},
+    };
+
+    const VECTOR_SIZE: usize = 16;
+
+    fn test_indexer(knowledge_dir: &Path) -> (MarkdownIndexer, Arc<dyn VectorStore>) {
+        let provider: Arc<dyn InferenceProvider> = Arc::new(MockProvider::new(
+            MockProviderConfig {
+                latency_ms: 0,
+                fault: MockFault::None,
+                fault_rate: 0.0,
+                fault_target: MockFaultTarget::All,
+            },
+            VECTOR_SIZE,
+        ));
+        let cache = Arc::new(EmbeddingCache::new(
+            &EmbeddingCacheConfig {
+                enabled: false,
+                path: String::new(),
+                max_entries: 0,
+                flush_interval_secs: 0,
+            },
+            provider.embed_model(),
+        ));
+        let spec = IndexSpec {
+            embed_model: provider.embed_model().to_string(),
+            vector_size: VECTOR_SIZE,
+            metric: DistanceMetric::Cosine,
+        };
+        let store: Arc<dyn VectorStore> = Arc::new(MemoryStore::new(spec, None).unwrap());
+        let indexer = MarkdownIndexer::new(
+            provider,
+            cache,
+            store.clone(),
+            &knowledge_dir.to_string_lossy(),
+            &IndexVersioningConfig {
+                blue_green: false,
+                keep_versions: 0,
+                max_failed_ratio: 0.0,
+            },
+        )
+        .unwrap();
+        (indexer, store)
+    }
+
+    #[test]
+    fn chunk_ids_are_unique_within_a_document() {
+        let (indexer, _) = test_indexer(Path::new("."));
+        let long = "曝光".repeat(DEFAULT_CHUNK_SIZE);
+        let content = format!("# 手册\n\n## 一\n\n短段落\n\n## 二\n\n{long}\n\n## 三\n\n另一段\n");
+
+        let chunks = indexer
+            .parse_and_chunk(&content, "runbook.md", "doc")
+            .unwrap();
+        let ids: Vec<&str> = chunks.iter().map(|c| c.chunk_id.as_str()).collect();
+        let unique: HashSet<&str> = ids.iter().copied().collect();
+        assert!(chunks.len() > 3);
+        assert_eq!(unique.len(), ids.len(), "{ids:?}");
+        assert_eq!(ids[0], "doc_chunk_0");
+    }
+
+    #[tokio::test]
+    async fn reuses_shifted_chunks_and_refreshes_their_metadata() {
+        let dir = std::env::temp_dir().join(format!("engineqa-indexer-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("runbook.md");
+        fs::write(&path, "# 手册\n\n## 一\n\n段落一\n\n## 二\n\n段落二\n").unwrap();
+
+        let (indexer, store) = test_indexer(&dir);
+        let control = IndexControl::default();
+        let first = indexer.index_document(&path, &control).await.unwrap();
+        assert_eq!(first.total_chunks, 2);
+        assert_eq!(first.reused_chunks, 0);
+
+        let content = "# 运维手册\n\n## 零\n\n新增段落\n\n## 一\n\n段落一\n\n## 二\n\n段落二\n";
+        fs::write(&path, content).unwrap();
+        let second = indexer.index_document(&path, &control).await.unwrap();
+        assert_eq!(second.total_chunks, 3);
+        assert_eq!(second.reused_chunks, 2);
+
+        let chunks = store.list_chunks(&second.doc_id).await.unwrap();
+        let ids: Vec<&str> = chunks.iter().map(|c| c.chunk_id.as_str()).collect();
+        assert_eq!(ids, second.chunk_ids);
+        assert_eq!(chunks[1].text, "段落一");
+        assert_eq!(chunks[1].title_path, "运维手册 / 一");
+        assert_eq!(
+            store.list_doc_hashes().await.unwrap()[&second.doc_id],
+            compute_hash(content)
+        );
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn doc_ids_do_not_collide_across_directories() {
+        let nested = compute_doc_id("runbooks/cpm.md");
+        assert_ne!(nested, compute_doc_id("runbooks_cpm.md"));
+        assert_eq!(nested, compute_doc_id("runbooks\\cpm.md"));
+        assert_eq!(nested, compute_hash("runbooks/cpm.md"));
+    }
+
+    #[tokio::test]
+    async fn indexes_colliding_paths_as_separate_documents() {
+        let dir = std::env::temp_dir().join(format!("engineqa-indexer-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(dir.join("runbooks")).unwrap();
+        fs::write(dir.join("runbooks/cpm.md"), "# CPM\n\nCPM 告警排查\n").unwrap();
+        fs::write(dir.join("runbooks_cpm.md"), "# CPM 汇总\n\nCPM 指标口径\n").unwrap();
+
+        let (indexer, store) = test_indexer(&dir);
+        let result = indexer.index(true).await.unwrap();
+        assert_eq!(result.indexed_files, 2);
+
+        let documents = store.list_documents().await.unwrap();
+        let mut paths: Vec<&str> = documents.iter().map(|doc| doc.path.as_str()).collect();
+        paths.sort();
+        assert_eq!(paths, vec!["runbooks/cpm.md", "runbooks_cpm.md"]);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn cancelled_index_returns_partial_result() {
+        let dir = std::env::temp_dir().join(format!("engineqa-indexer-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("a.md"), "# A\n\n出价策略\n").unwrap();
+        let (indexer, store) = test_indexer(&dir);
+
+        let control = IndexControl::default();
+        control.cancel();
+        let result = indexer
+            .index_scoped(false, &IndexScope::default(), &control)
+            .await
+            .unwrap();
+        assert!(result.cancelled);
+        assert_eq!(result.indexed_files, 0);
+        assert_eq!(store.count().await.unwrap(), 0);
+
+        let result = indexer.index(false).await.unwrap();
+        assert!(!result.cancelled);
+        assert_eq!(result.indexed_files, 1);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn sanitizes_relative_paths() {
+        assert_eq!(
+            sanitize_relative_path("runbooks/cpm.md"),
+            Some(PathBuf::from("runbooks/cpm.md"))
+        );
+        assert_eq!(
+            sanitize_relative_path("./runbooks\\cpm.md"),
+            Some(PathBuf::from("runbooks/cpm.md"))
+        );
+
+        for path in [
+            "",
+            "../secret.md",
+            "runbooks/../../etc/passwd.md",
+            "/etc/passwd.md",
+            ".hidden.md",
+            "runbooks/.git/config.md",
+            "notes.txt",
+            "runbooks/",
+        ] {
+            assert_eq!(sanitize_relative_path(path), None, "{path}");
+        }
+    }
+
+    #[test]
+    fn scope_matches_paths_and_prefix() {
+        let scope = IndexScope::new(&["alerts/cpm.md".to_string()], Some("runbooks/")).unwrap();
+        assert!(scope.contains("alerts/cpm.md"));
+        assert!(scope.contains("runbooks/deploy.md"));
+        assert!(!scope.contains("alerts/ctr.md"));
+        assert!(!scope.contains("runbooks.md"));
+
+        assert!(IndexScope::default().contains("anything.md"));
+        assert!(IndexScope::new(&[], Some("../")).is_err());
+        assert!(IndexScope::new(&["../x.md".to_string()], None).is_err());
+    }
+
+    #[test]
+    fn build_stats_validation() {
+        let stats = BuildStats {
+            files: 10,
+            failed_files: 1,
+            chunks: 100,
+            successful_chunks: 95,
+            failed_chunks: 5,
+        };
+        assert!(stats.validate(95, 0.1).is_ok());
+        assert!(stats.validate(90, 0.1).unwrap_err().contains("row count"));
+        assert!(
+            stats
+                .validate(95, 0.05)
+                .unwrap_err()
+                .contains("failure ratio")
+        );
+
+        let empty = BuildStats {
+            files: 1,
+            failed_files: 0,
+            chunks: 0,
+            successful_chunks: 0,
+            failed_chunks: 0,
+        };
+        assert!(empty.validate(0, 1.0).is_err());
+    }
+
+    #[test]
+    fn control_reports_progress_and_cancellation() {
+        let control = IndexControl::default();
+        let shared = control.clone();
+        assert!(control.progress().eta_secs.is_none());
+
+        control.start(4);
+        control.begin_file("a.md");
+        control.chunk_embedded();
+        assert_eq!(shared.progress().current_file.as_deref(), Some("a.md"));
+
+        control.finish_file();
+        let progress = shared.progress();
+        assert_eq!(progress.files_done, 1);
+        assert_eq!(progress.chunks_embedded, 1);
+        assert!(progress.current_file.is_none());
+        assert!(progress.eta_secs.is_some());
+
+        shared.cancel();
+        assert!(control.is_cancelled());
+    }
 }
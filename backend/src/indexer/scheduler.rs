@@ -0,0 +1,84 @@
+use chrono::{DateTime, Local};
+use cron::Schedule;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use crate::{
+    AppState,
+    api::reindex::{ReindexError, submit_reindex},
+    config::ReindexScheduleConfig,
+    indexer::IndexScope,
+};
+
+/// 按 cron 表达式定时提交索引任务（本地时区）
+pub struct ReindexScheduler {
+    schedule: Option<Schedule>,
+    full: bool,
+}
+
+impl ReindexScheduler {
+    pub fn new(config: &ReindexScheduleConfig) -> Self {
+        Self {
+            schedule: config.schedule.clone(),
+            full: config.full,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.schedule.is_some()
+    }
+
+    /// 下一次计划运行时间，未启用时为 None
+    pub fn next_run(&self) -> Option<DateTime<Local>> {
+        self.schedule.as_ref()?.upcoming(Local).next()
+    }
+
+    /// 启动后台调度循环；到点时若已有任务运行则跳过本次
+    pub fn spawn(state: Arc<AppState>) {
+        if !state.scheduler.is_enabled() {
+            return;
+        }
+
+        tokio::spawn(async move {
+            while let Some(next) = state.scheduler.next_run() {
+                info!(next_run = %next.to_rfc3339(), full = state.scheduler.full, "next scheduled reindex");
+                let wait = (next - Local::now()).to_std().unwrap_or_default();
+                tokio::time::sleep(wait).await;
+
+                match submit_reindex(state.clone(), state.scheduler.full, IndexScope::default())
+                    .await
+                {
+                    Ok(job_id) => info!(job_id = %job_id, "scheduled reindex job submitted"),
+                    Err(ReindexError::JobInProgress | ReindexError::MaintenanceInProgress) => {
+                        info!("reindex job or table maintenance running, skipping scheduled run")
+                    }
+                    Err(e) => warn!(error = %e, "failed to submit scheduled reindex"),
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn next_run_follows_schedule() {
+        let disabled = ReindexScheduler::new(&ReindexScheduleConfig {
+            schedule: None,
+            full: true,
+        });
+        assert!(!disabled.is_enabled());
+        assert!(disabled.next_run().is_none());
+
+        let scheduler = ReindexScheduler::new(&ReindexScheduleConfig {
+            schedule: Some(Schedule::from_str("0 0 3 * * Sun").unwrap()),
+            full: false,
+        });
+        let next = scheduler.next_run().expect("schedule has upcoming runs");
+        assert!(next > Local::now());
+        assert_eq!(next.format("%a %H:%M:%S").to_string(), "Sun 03:00:00");
+    }
+}
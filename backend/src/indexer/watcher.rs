@@ -0,0 +1,183 @@
+use notify::{
+    Event, EventKind, RecursiveMode, Watcher,
+    event::{AccessKind, AccessMode, ModifyKind},
+};
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+
+use crate::{
+    AppState,
+    api::reindex::{ReindexError, submit_reindex},
+    indexer::{IndexScope, sanitize_relative_path},
+};
+
+/// 去抖窗口内累积的变更
+#[derive(Debug, Default)]
+struct PendingChanges {
+    /// 变更的 Markdown 文件（知识库目录内的相对路径）
+    paths: BTreeSet<String>,
+    /// 目录级变更（如目录重命名、删除）无法定位到具体文件，退化为整库增量索引
+    whole_dir: bool,
+}
+
+impl PendingChanges {
+    fn is_empty(&self) -> bool {
+        self.paths.is_empty() && !self.whole_dir
+    }
+
+    fn record(&mut self, root: &Path, path: &Path) {
+        let Ok(relative) = path.strip_prefix(root) else {
+            return;
+        };
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        if relative.is_empty() || relative.split('/').any(|part| part.starts_with('.')) {
+            return;
+        }
+
+        if sanitize_relative_path(&relative).is_some() {
+            self.paths.insert(relative);
+        } else if path.is_dir() || (!path.exists() && path.extension().is_none()) {
+            self.whole_dir = true;
+        }
+    }
+
+    fn scope(&self) -> IndexScope {
+        if self.whole_dir {
+            return IndexScope::default();
+        }
+        let paths: Vec<String> = self.paths.iter().cloned().collect();
+        IndexScope::new(&paths, None).unwrap_or_default()
+    }
+}
+
+/// 只关心会改变文件内容或存在性的事件
+fn is_relevant(kind: &EventKind) -> bool {
+    matches!(
+        kind,
+        EventKind::Create(_)
+            | EventKind::Remove(_)
+            | EventKind::Modify(ModifyKind::Data(_) | ModifyKind::Name(_) | ModifyKind::Any)
+            | EventKind::Access(AccessKind::Close(AccessMode::Write))
+    )
+}
+
+/// 监听知识库目录，变更静默 `debounce_ms` 后通过 JobManager 提交增量索引任务
+pub fn spawn_knowledge_watcher(state: Arc<AppState>) {
+    let root: PathBuf = match std::fs::canonicalize(&state.config.knowledge_dir) {
+        Ok(root) => root,
+        Err(e) => {
+            warn!(
+                dir = %state.config.knowledge_dir,
+                error = %e,
+                "knowledge directory unavailable, watcher disabled"
+            );
+            return;
+        }
+    };
+
+    let (tx, mut rx) = mpsc::unbounded_channel::<notify::Result<Event>>();
+    let mut watcher = match notify::recommended_watcher(move |event| {
+        let _ = tx.send(event);
+    }) {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            warn!(error = %e, "failed to create knowledge watcher");
+            return;
+        }
+    };
+    if let Err(e) = watcher.watch(&root, RecursiveMode::Recursive) {
+        warn!(error = %e, "failed to watch knowledge directory");
+        return;
+    }
+
+    let debounce = Duration::from_millis(state.config.knowledge_watch.debounce_ms);
+    info!(dir = %root.display(), debounce_ms = debounce.as_millis() as u64, "knowledge watcher started");
+
+    tokio::spawn(async move {
+        // watcher 离开作用域即停止监听，随任务一起持有
+        let _watcher = watcher;
+        let mut pending = PendingChanges::default();
+
+        loop {
+            let next = if pending.is_empty() {
+                rx.recv().await
+            } else {
+                match tokio::time::timeout(debounce, rx.recv()).await {
+                    Ok(next) => next,
+                    Err(_) => {
+                        submit_pending(&state, &mut pending).await;
+                        continue;
+                    }
+                }
+            };
+
+            match next {
+                Some(Ok(event)) if is_relevant(&event.kind) => {
+                    for path in &event.paths {
+                        pending.record(&root, path);
+                    }
+                }
+                Some(Ok(_)) => {}
+                Some(Err(e)) => warn!(error = %e, "knowledge watcher error"),
+                None => break,
+            }
+        }
+    });
+}
+
+/// 提交累积的变更；已有任务运行时保留变更，下一个去抖周期重试
+async fn submit_pending(state: &Arc<AppState>, pending: &mut PendingChanges) {
+    match submit_reindex(state.clone(), false, pending.scope()).await {
+        Ok(job_id) => {
+            info!(
+                job_id = %job_id,
+                files = pending.paths.len(),
+                whole_dir = pending.whole_dir,
+                "knowledge change detected, reindex job submitted"
+            );
+            *pending = PendingChanges::default();
+        }
+        Err(ReindexError::JobInProgress | ReindexError::MaintenanceInProgress) => {
+            debug!("index busy, deferring knowledge changes");
+        }
+        Err(e) => {
+            warn!(error = %e, "failed to submit reindex for knowledge changes");
+            *pending = PendingChanges::default();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_markdown_changes_and_ignores_noise() {
+        let root = Path::new("/kb");
+        let mut pending = PendingChanges::default();
+
+        pending.record(root, Path::new("/kb/runbooks/cpm.md"));
+        pending.record(root, Path::new("/kb/runbooks/.cpm.md.swp"));
+        pending.record(root, Path::new("/kb/.git/index"));
+        pending.record(root, Path::new("/kb/notes.txt"));
+        pending.record(root, Path::new("/elsewhere/a.md"));
+
+        assert_eq!(
+            pending.paths.iter().collect::<Vec<_>>(),
+            vec!["runbooks/cpm.md"]
+        );
+        assert!(!pending.whole_dir);
+        assert!(!pending.scope().is_all());
+
+        // 已删除的目录没有扩展名
+        pending.record(root, Path::new("/kb/archived"));
+        assert!(pending.whole_dir);
+        assert!(pending.scope().is_all());
+    }
+}
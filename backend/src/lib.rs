@@ -1,41 +1,64 @@
 pub mod api;
+pub mod cache;
 pub mod config;
 pub mod indexer;
 pub mod observability;
+pub mod prompt;
 pub mod provider;
 pub mod rag;
 pub mod vector_store;
 
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use crate::{
-    api::feedback::FeedbackStore, api::reindex::JobManager, indexer::MarkdownIndexer,
-    provider::InternalApiProvider, vector_store::VectorStore,
+    api::feedback::FeedbackStore, api::reindex::JobManager, cache::answer::AnswerCache,
+    cache::embedding::EmbeddingCache, indexer::MarkdownIndexer,
+    indexer::scheduler::ReindexScheduler, prompt::PromptRegistry, provider::InferenceProvider,
+    rag::grounding::GroundingChecker, rag::rewrite::QueryRewriter, vector_store::VectorStore,
+    vector_store::maintenance::IndexMaintainer,
 };
 
 pub struct AppState {
     pub config: config::AppConfig,
-    pub provider: InternalApiProvider,
+    pub provider: Arc<dyn InferenceProvider>,
+    pub embedding_cache: Arc<EmbeddingCache>,
+    pub answer_cache: AnswerCache,
+    pub prompts: Arc<PromptRegistry>,
+    pub grounding: GroundingChecker,
+    pub rewriter: QueryRewriter,
     pub retriever: rag::VectorRetriever,
     pub indexer: MarkdownIndexer,
     pub job_manager: JobManager,
+    pub scheduler: ReindexScheduler,
+    pub maintainer: IndexMaintainer,
     pub vector_store: Arc<dyn VectorStore>,
     pub feedback_store: FeedbackStore,
 }
 
 pub fn create_app(
     config: &config::AppConfig,
-    provider: InternalApiProvider,
+    provider: Arc<dyn InferenceProvider>,
     retriever: rag::VectorRetriever,
     vector_store: Arc<dyn VectorStore>,
 ) -> axum::Router {
     let router = api::router::<Arc<AppState>>(config);
 
+    // Initialize embedding cache
+    let embedding_cache = Arc::new(EmbeddingCache::new(
+        &config.embedding_cache,
+        provider.embed_model(),
+    ));
+    embedding_cache.clone().spawn_flusher(Duration::from_secs(
+        config.embedding_cache.flush_interval_secs,
+    ));
+
     // Initialize indexer
     let indexer = match MarkdownIndexer::new(
-        config.internal_api.clone(),
+        provider.clone(),
+        embedding_cache.clone(),
         vector_store.clone(),
         &config.knowledge_dir,
+        &config.index_versioning,
     ) {
         Ok(indexer) => indexer,
         Err(e) => {
@@ -47,8 +70,30 @@ pub fn create_app(
         }
     };
 
+    // Initialize answer cache
+    let answer_cache = AnswerCache::new(&config.answer_cache);
+
+    // Initialize prompt templates
+    let prompts = Arc::new(PromptRegistry::new(&config.prompt));
+    prompts
+        .clone()
+        .spawn_watcher(Duration::from_secs(config.prompt.reload_interval_secs));
+
+    // Initialize grounding checker
+    let grounding =
+        GroundingChecker::new(&config.grounding, provider.clone(), embedding_cache.clone());
+
+    // Initialize query rewriter
+    let rewriter = QueryRewriter::new(&config.query_rewrite, provider.clone());
+
     // Initialize job manager
-    let job_manager = JobManager::new();
+    let job_manager = JobManager::with_history(&config.job_history);
+
+    // Initialize reindex scheduler
+    let scheduler = ReindexScheduler::new(&config.reindex_schedule);
+
+    // Initialize table maintenance
+    let maintainer = IndexMaintainer::new(&config.index_maintenance);
 
     // Initialize feedback store
     let feedback_store = FeedbackStore::new();
@@ -56,13 +101,31 @@ pub fn create_app(
     let state = Arc::new(AppState {
         config: config.clone(),
         provider,
+        embedding_cache,
+        answer_cache,
+        prompts,
+        grounding,
+        rewriter,
         retriever,
         indexer,
         job_manager,
+        scheduler,
+        maintainer,
         vector_store,
         feedback_store,
     });
 
+    ReindexScheduler::spawn(state.clone());
+    IndexMaintainer::spawn(state.clone());
+
+    if config.index_spec_policy == config::IndexSpecPolicy::Migrate {
+        spawn_spec_migration(state.clone());
+    }
+
+    if config.knowledge_watch.enabled {
+        indexer::watcher::spawn_knowledge_watcher(state.clone());
+    }
+
     router
         .route("/api/query", axum::routing::post(api::query::handle_query))
         .route(
@@ -70,13 +133,333 @@ pub fn create_app(
             axum::routing::post(api::reindex::handle_reindex)
                 .get(api::reindex::handle_reindex_status),
         )
+        .route(
+            "/api/reindex/jobs",
+            axum::routing::get(api::reindex::handle_list_jobs),
+        )
+        .route(
+            "/api/reindex/jobs/{job_id}",
+            axum::routing::get(api::reindex::handle_get_job)
+                .delete(api::reindex::handle_cancel_job),
+        )
+        .route(
+            "/api/admin/index/versions",
+            axum::routing::get(api::admin::handle_list_versions),
+        )
+        .route(
+            "/api/admin/index/rollback",
+            axum::routing::post(api::admin::handle_rollback),
+        )
+        .route(
+            "/api/admin/index/maintenance",
+            axum::routing::post(api::admin::handle_maintenance)
+                .get(api::admin::handle_maintenance_status),
+        )
         .route(
             "/api/status",
             axum::routing::get(api::status::handle_status),
         )
+        .route(
+            "/api/prompts",
+            axum::routing::get(api::prompts::handle_list_prompts),
+        )
+        .route(
+            "/api/documents",
+            axum::routing::get(api::documents::handle_list_documents)
+                .post(api::documents::handle_upload_document),
+        )
+        .route(
+            "/api/documents/{doc_id}",
+            axum::routing::delete(api::documents::handle_delete_document),
+        )
+        .route(
+            "/api/documents/{doc_id}/chunks",
+            axum::routing::get(api::documents::handle_document_chunks),
+        )
         .route(
             "/api/feedback",
             axum::routing::post(api::feedback::handle_feedback),
         )
+        .fallback(api::error_response::handle_not_found)
         .with_state(state)
 }
+
+/// 索引的 embedding 模型或维度与配置不一致时，提交全量重建到新版本表
+fn spawn_spec_migration(state: Arc<AppState>) {
+    tokio::spawn(async move {
+        match vector_store::spec_mismatch(state.vector_store.as_ref()).await {
+            Ok(Some(stored)) => {
+                match api::reindex::submit_reindex(
+                    state.clone(),
+                    true,
+                    indexer::IndexScope::default(),
+                )
+                .await
+                {
+                    Ok(job_id) => tracing::info!(
+                        job_id = %job_id,
+                        from = %stored,
+                        to = %state.vector_store.spec(),
+                        "index spec migration submitted"
+                    ),
+                    Err(e) => tracing::warn!(error = %e, "failed to submit index spec migration"),
+                }
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!(error = %e, "failed to read stored index spec"),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Router,
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use serde_json::{Value, json};
+    use std::{collections::HashMap, path::PathBuf, sync::Arc, time::Duration};
+    use tower::ServiceExt;
+
+    use crate::{
+        config::AppConfig,
+        create_app,
+        provider::{InferenceProvider, registry::ProviderRegistry},
+        rag::VectorRetriever,
+        vector_store::{IndexSpec, VectorStore, memory_store::MemoryStore},
+    };
+
+    struct TestApp {
+        app: Router,
+        store: Arc<dyn VectorStore>,
+        knowledge_dir: PathBuf,
+        root: PathBuf,
+    }
+
+    impl Drop for TestApp {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.root);
+        }
+    }
+
+    fn test_app(docs: &[(&str, &str)]) -> TestApp {
+        test_app_with(docs, &[])
+    }
+
+    /// 以 mock 提供方与内存向量存储组装完整应用，`vars` 覆盖默认配置
+    fn test_app_with(docs: &[(&str, &str)], vars: &[(&str, &str)]) -> TestApp {
+        let root = std::env::temp_dir().join(format!("engineqa-e2e-{}", uuid::Uuid::new_v4()));
+        let knowledge_dir = root.join("knowledge");
+        for (path, content) in docs {
+            let file = knowledge_dir.join(path);
+            std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+            std::fs::write(file, content).unwrap();
+        }
+
+        let path = |name: &str| root.join(name).to_string_lossy().into_owned();
+        let mut env = HashMap::from([
+            ("INFER_PROVIDER".to_string(), "mock".to_string()),
+            ("VECTOR_STORE".to_string(), "memory".to_string()),
+            ("EMBEDDING_VECTOR_SIZE".to_string(), "64".to_string()),
+            ("VECTOR_SCORE_THRESHOLD".to_string(), "0".to_string()),
+            ("KNOWLEDGE_DIR".to_string(), path("knowledge")),
+            ("PROMPT_TEMPLATE_DIR".to_string(), path("prompts")),
+            ("REINDEX_JOB_HISTORY_PATH".to_string(), path("jobs.json")),
+            ("EMBED_CACHE_ENABLED".to_string(), "false".to_string()),
+            (
+                "INDEX_MAINTENANCE_INTERVAL_SECS".to_string(),
+                "0".to_string(),
+            ),
+        ]);
+        env.extend(
+            vars.iter()
+                .map(|(key, value)| (key.to_string(), value.to_string())),
+        );
+        let config = AppConfig::from_map(&env).expect("config should load");
+
+        let provider = Arc::new(ProviderRegistry::from_config(&config));
+        let spec = IndexSpec {
+            embed_model: provider.embed_model().to_string(),
+            vector_size: config.embedding_vector_size,
+            metric: config.distance_metric,
+        };
+        let store: Arc<dyn VectorStore> = Arc::new(MemoryStore::new(spec, None).unwrap());
+        let retriever = VectorRetriever::new(store.clone(), config.vector_score_threshold);
+        let app = create_app(&config, provider, retriever, store.clone());
+
+        TestApp {
+            app,
+            store,
+            knowledge_dir,
+            root,
+        }
+    }
+
+    async fn send(
+        app: &Router,
+        method: &str,
+        uri: &str,
+        body: Option<Value>,
+    ) -> (StatusCode, Value) {
+        let request = Request::builder().method(method).uri(uri);
+        let request = match body {
+            Some(body) => request
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string())),
+            None => request.body(Body::empty()),
+        }
+        .expect("request should be built");
+
+        let response = app
+            .clone()
+            .oneshot(request)
+            .await
+            .expect("request should succeed");
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("body should be readable");
+        (
+            status,
+            serde_json::from_slice(&bytes).unwrap_or(Value::Null),
+        )
+    }
+
+    /// 提交索引任务并等待其完成
+    async fn reindex(app: &Router, full: bool) -> Value {
+        let (status, body) = send(app, "POST", "/api/reindex", Some(json!({ "full": full }))).await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+        wait_for_job(app, body["job_id"].as_str().unwrap()).await
+    }
+
+    async fn wait_for_job(app: &Router, job_id: &str) -> Value {
+        for _ in 0..200 {
+            let (_, job) = send(app, "GET", &format!("/api/reindex/jobs/{job_id}"), None).await;
+            match job["status"].as_str() {
+                Some("running") => tokio::time::sleep(Duration::from_millis(20)).await,
+                Some("completed") => return job,
+                _ => panic!("reindex job did not complete: {job}"),
+            }
+        }
+        panic!("reindex job timed out");
+    }
+
+    #[tokio::test]
+    async fn indexes_knowledge_and_answers_queries() {
+        let test = test_app(&[
+            (
+                "runbooks/cpm.md",
+                "# CPM 告警处理\n\n## 排查步骤\n\nCPM 告警时先检查出价竞争、底价配置与流量质量。\n",
+            ),
+            (
+                "guides/fill-rate.md",
+                "# 填充率为零\n\n## 定向检查\n\n填充率为零时检查广告位状态与地域定向是否匹配。\n",
+            ),
+        ]);
+
+        let job = reindex(&test.app, true).await;
+        assert_eq!(job["result"]["indexed_files"], 2, "{job}");
+        assert!(test.store.count().await.unwrap() >= 2);
+
+        let (status, body) = send(
+            &test.app,
+            "POST",
+            "/api/query",
+            Some(json!({ "question": "CPM 告警 出价竞争 底价配置", "top_k": 1 })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+        assert_eq!(body["degraded"], false, "{body}");
+        let sources = body["sources"].as_array().unwrap();
+        assert_eq!(sources.len(), 1);
+        assert_eq!(sources[0]["path"], "runbooks/cpm.md");
+    }
+
+    #[tokio::test]
+    async fn uploads_are_serialized_with_reindex_jobs() {
+        let test = test_app_with(
+            &[(
+                "runbooks/cpm.md",
+                "# CPM 告警\n\n## 排查\n\n检查出价与预算消耗。\n",
+            )],
+            &[("MOCK_LATENCY_MS", "200")],
+        );
+        let upload = json!({
+            "path": "runbooks/ctr.md",
+            "content": "# CTR 下跌\n\n## 排查\n\n检查素材审核状态。\n",
+        });
+
+        let (status, job) = send(&test.app, "POST", "/api/reindex", Some(json!({}))).await;
+        assert_eq!(status, StatusCode::OK, "{job}");
+        let (status, body) = send(&test.app, "POST", "/api/documents", Some(upload.clone())).await;
+        assert_eq!(status, StatusCode::CONFLICT, "{body}");
+        assert!(!test.knowledge_dir.join("runbooks/ctr.md").exists());
+        wait_for_job(&test.app, job["job_id"].as_str().unwrap()).await;
+
+        let (status, body) = send(&test.app, "POST", "/api/documents", Some(upload)).await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+        assert_eq!(body["created"], true);
+
+        let (_, jobs) = send(&test.app, "GET", "/api/reindex/jobs", None).await;
+        let latest = &jobs["jobs"][0];
+        assert_eq!(latest["status"], "completed", "{jobs}");
+        assert_eq!(latest["result"]["files"][0]["path"], "runbooks/ctr.md");
+    }
+
+    #[tokio::test]
+    async fn rejects_unavailable_query_options() {
+        let test = test_app(&[]);
+
+        for (option, value) in [
+            ("prompt_template", "nope"),
+            // 查询改写未启用
+            ("retrieval_mode", "multi_query"),
+        ] {
+            let (status, body) = send(
+                &test.app,
+                "POST",
+                "/api/query",
+                Some(json!({ "question": "CPM 告警", option: value })),
+            )
+            .await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+            assert_eq!(body["error_code"], "INVALID_REQUEST");
+            assert_eq!(body["details"]["field"], option);
+            assert!(body["trace_id"].is_string());
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_out_of_range_prune_age() {
+        let test = test_app(&[]);
+        let (status, body) = send(
+            &test.app,
+            "POST",
+            "/api/admin/index/maintenance",
+            Some(json!({ "prune_older_than_secs": u64::MAX })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+        assert_eq!(body["error_code"], "INVALID_REQUEST");
+        assert_eq!(body["details"]["field"], "prune_older_than_secs");
+    }
+
+    #[tokio::test]
+    async fn incremental_reindex_removes_deleted_documents() {
+        let test = test_app(&[
+            ("a.md", "# 文档A\n\n## 概述\n\n频次控制过低导致曝光疲劳。\n"),
+            ("b.md", "# 文档B\n\n## 概述\n\n转化回传延迟。\n"),
+        ]);
+        reindex(&test.app, true).await;
+
+        std::fs::remove_file(test.knowledge_dir.join("b.md")).unwrap();
+        reindex(&test.app, false).await;
+
+        let (status, body) = send(&test.app, "GET", "/api/documents", None).await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+        assert_eq!(body["total"], 1, "{body}");
+        assert_eq!(body["documents"][0]["path"], "a.md");
+        assert_eq!(body["documents"][0]["title"], "文档A");
+    }
+}
@@ -1,8 +1,14 @@
 use tokio::net::TcpListener;
 
 use engineqa_backend::{
-    config::AppConfig, create_app, observability, provider::InternalApiProvider,
-    rag::VectorRetriever, vector_store::lancedb_store::LanceDbStore,
+    config::AppConfig,
+    create_app, observability,
+    provider::{InferenceProvider, registry::ProviderRegistry},
+    rag::VectorRetriever,
+    vector_store::{
+        IndexSpec, LANCEDB, MEMORY, QDRANT, VectorStore, lancedb_store::LanceDbStore,
+        memory_store::MemoryStore, qdrant_store::QdrantStore, verify_index_spec,
+    },
 };
 use std::sync::Arc;
 
@@ -20,32 +26,72 @@ async fn main() {
     };
 
     tracing::info!(
-        infer_provider = %config.infer_provider,
+        chat_providers = %config.chat_providers.join(","),
+        embed_providers = %config.embed_providers.join(","),
         upstream_base = %config.internal_api.base_url,
         vector_store = %config.vector_store,
-        lancedb_uri = %config.lancedb_uri,
-        lancedb_table = %config.lancedb_table,
+        vector_table = %config.vector_table(),
         "configuration loaded"
     );
 
-    // Initialize provider
-    let provider = InternalApiProvider::new(config.internal_api.clone());
+    // Initialize provider chains
+    let provider = Arc::new(ProviderRegistry::from_config(&config));
 
     // Initialize vector store
-    let vector_store = match LanceDbStore::new(
-        &config.lancedb_uri,
-        &config.lancedb_table,
-        config.embedding_vector_size,
-    )
-    .await
-    {
-        Ok(store) => Arc::new(store),
-        Err(err) => {
-            tracing::error!(error = %err, "failed to initialize LanceDB vector store");
-            std::process::exit(1);
+    let spec = IndexSpec {
+        embed_model: provider.embed_model().to_string(),
+        vector_size: config.embedding_vector_size,
+        metric: config.distance_metric,
+    };
+    let vector_store: Arc<dyn VectorStore> = match config.vector_store.as_str() {
+        MEMORY => match MemoryStore::new(spec, config.memory_store_path.as_deref()) {
+            Ok(store) => Arc::new(store),
+            Err(err) => {
+                tracing::error!(error = %err, "failed to initialize in-memory vector store");
+                std::process::exit(1);
+            }
+        },
+        QDRANT => match QdrantStore::new(&config.qdrant, spec).await {
+            Ok(store) => Arc::new(store),
+            Err(err) => {
+                tracing::error!(error = %err, "failed to initialize Qdrant vector store");
+                std::process::exit(1);
+            }
+        },
+        #[cfg(feature = "pgvector")]
+        engineqa_backend::vector_store::PGVECTOR => {
+            use engineqa_backend::vector_store::pgvector_store::PgVectorStore;
+
+            match PgVectorStore::new(&config.pgvector, spec, config.vector_index.clone()).await {
+                Ok(store) => Arc::new(store),
+                Err(err) => {
+                    tracing::error!(error = %err, "failed to initialize pgvector vector store");
+                    std::process::exit(1);
+                }
+            }
         }
+        LANCEDB => match LanceDbStore::new(
+            &config.lancedb_uri,
+            &config.lancedb_table,
+            spec,
+            config.vector_index.clone(),
+        )
+        .await
+        {
+            Ok(store) => Arc::new(store),
+            Err(err) => {
+                tracing::error!(error = %err, "failed to initialize LanceDB vector store");
+                std::process::exit(1);
+            }
+        },
+        other => unreachable!("unknown vector store {other} passed config validation"),
     };
 
+    if let Err(err) = verify_index_spec(vector_store.as_ref(), config.index_spec_policy).await {
+        tracing::error!(error = %err, "vector index does not match embedding config");
+        std::process::exit(1);
+    }
+
     // Initialize retriever
     let retriever = VectorRetriever::new(vector_store.clone(), config.vector_score_threshold);
 
@@ -0,0 +1,404 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+use tokio::sync::RwLock;
+
+use crate::{config::PromptConfig, provider::ChatMessage, rag::RetrievedChunk};
+
+/// 内置默认模板名
+pub const DEFAULT_TEMPLATE: &str = "default";
+
+const BUILTIN_DEFAULT: &str = include_str!("default.toml");
+
+/// 提示词模板（由 TOML 文件定义，变量以 `{{name}}` 表示）
+#[derive(Debug, Clone, Deserialize)]
+pub struct PromptTemplate {
+    #[serde(skip)]
+    pub name: String,
+    pub version: String,
+    pub system: String,
+    pub user: String,
+    pub context_item: String,
+    pub context_separator: String,
+    pub no_match_answer: String,
+    pub degraded_answer: String,
+    pub degraded_with_sources_answer: String,
+    pub degraded_sources_header: String,
+    pub degraded_source_item: String,
+    pub degraded_no_sources: String,
+}
+
+/// 模板摘要，用于列表展示与日志
+#[derive(Debug, Clone, Serialize)]
+pub struct PromptTemplateInfo {
+    pub name: String,
+    pub version: String,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum PromptError {
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Template parse error in {path}: {message}")]
+    ParseError { path: String, message: String },
+
+    #[error("Unknown prompt template: {0}")]
+    NotFound(String),
+}
+
+impl PromptTemplate {
+    fn parse(name: &str, raw: &str, path: &str) -> Result<Self, PromptError> {
+        let mut template: PromptTemplate =
+            toml::from_str(raw).map_err(|err| PromptError::ParseError {
+                path: path.to_string(),
+                message: err.to_string(),
+            })?;
+        template.name = name.to_string();
+        Ok(template)
+    }
+
+    fn builtin() -> Self {
+        Self::parse(DEFAULT_TEMPLATE, BUILTIN_DEFAULT, "<builtin>")
+            .expect("builtin prompt template must be valid")
+    }
+
+    pub fn info(&self) -> PromptTemplateInfo {
+        PromptTemplateInfo {
+            name: self.name.clone(),
+            version: self.version.clone(),
+        }
+    }
+
+    pub fn build_context(&self, chunks: &[RetrievedChunk]) -> String {
+        chunks
+            .iter()
+            .enumerate()
+            .map(|(i, chunk)| {
+                render(
+                    &self.context_item,
+                    &[
+                        ("index", &(i + 1).to_string()),
+                        ("title", &chunk.metadata.title_path),
+                        ("path", &chunk.metadata.path),
+                        ("content", &chunk.snippet),
+                    ],
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(&self.context_separator)
+    }
+
+    pub fn build_messages(&self, question: &str, chunks: &[RetrievedChunk]) -> Vec<ChatMessage> {
+        let context = self.build_context(chunks);
+        let sources = chunks
+            .iter()
+            .map(|chunk| chunk.metadata.title_path.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let date = chrono::Local::now().format("%Y-%m-%d").to_string();
+        let vars = [
+            ("question", question),
+            ("context", context.as_str()),
+            ("sources", sources.as_str()),
+            ("date", date.as_str()),
+        ];
+
+        vec![
+            ChatMessage {
+                role: "system".to_string(),
+                content: render(&self.system, &vars),
+            },
+            ChatMessage {
+                role: "user".to_string(),
+                content: render(&self.user, &vars),
+            },
+        ]
+    }
+
+    pub fn degraded_answer(&self, description: &str) -> String {
+        render(&self.degraded_answer, &[("description", description)])
+    }
+
+    /// `sources` 为 (标题, 路径) 列表
+    pub fn degraded_with_sources_answer(
+        &self,
+        description: &str,
+        sources: &[(&str, &str)],
+    ) -> String {
+        let sources_text = if sources.is_empty() {
+            self.degraded_no_sources.clone()
+        } else {
+            let items = sources
+                .iter()
+                .map(|(title, path)| {
+                    render(
+                        &self.degraded_source_item,
+                        &[("title", title), ("path", path)],
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            format!("{}\n{}", self.degraded_sources_header, items)
+        };
+
+        render(
+            &self.degraded_with_sources_answer,
+            &[("description", description), ("sources", &sources_text)],
+        )
+    }
+}
+
+/// 将 `{{name}}` 替换为对应的值；未知变量保持原样
+pub fn render(template: &str, vars: &[(&str, &str)]) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        match after.find("}}") {
+            Some(end) => {
+                let key = after[..end].trim();
+                match vars.iter().find(|(name, _)| *name == key) {
+                    Some((_, value)) => output.push_str(value),
+                    None => output.push_str(&rest[start..start + 2 + end + 2]),
+                }
+                rest = &after[end + 2..];
+            }
+            None => {
+                output.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+
+    output.push_str(rest);
+    output
+}
+
+/// 模板注册表：内置默认模板 + 模板目录中的 `*.toml` 文件，支持热加载
+pub struct PromptRegistry {
+    dir: PathBuf,
+    default_name: String,
+    templates: RwLock<HashMap<String, Arc<PromptTemplate>>>,
+    fingerprint: RwLock<Vec<(PathBuf, SystemTime)>>,
+}
+
+impl PromptRegistry {
+    pub fn new(config: &PromptConfig) -> Self {
+        let dir = PathBuf::from(&config.dir);
+        let fingerprint = scan_fingerprint(&dir);
+        let templates = load_templates(&dir);
+
+        if !templates.contains_key(&config.default_template) {
+            tracing::warn!(
+                template = %config.default_template,
+                "configured default prompt template not found, using builtin"
+            );
+        }
+
+        Self {
+            dir,
+            default_name: config.default_template.clone(),
+            templates: RwLock::new(templates),
+            fingerprint: RwLock::new(fingerprint),
+        }
+    }
+
+    /// 获取模板；`name` 为空时使用默认模板（缺失时回退到内置模板），
+    /// 显式指定的模板不存在时返回 `NotFound`
+    pub async fn get(&self, name: Option<&str>) -> Result<Arc<PromptTemplate>, PromptError> {
+        let templates = self.templates.read().await;
+
+        if let Some(name) = name {
+            return templates
+                .get(name)
+                .cloned()
+                .ok_or_else(|| PromptError::NotFound(name.to_string()));
+        }
+
+        Ok(templates
+            .get(&self.default_name)
+            .or_else(|| templates.get(DEFAULT_TEMPLATE))
+            .cloned()
+            .unwrap_or_else(|| Arc::new(PromptTemplate::builtin())))
+    }
+
+    pub async fn list(&self) -> Vec<PromptTemplateInfo> {
+        let mut list: Vec<PromptTemplateInfo> = self
+            .templates
+            .read()
+            .await
+            .values()
+            .map(|template| template.info())
+            .collect();
+        list.sort_by(|a, b| a.name.cmp(&b.name));
+        list
+    }
+
+    /// 模板文件有变化时重新加载，返回是否发生了重载
+    pub async fn reload_if_changed(&self) -> bool {
+        let fingerprint = scan_fingerprint(&self.dir);
+        if *self.fingerprint.read().await == fingerprint {
+            return false;
+        }
+
+        let templates = load_templates(&self.dir);
+        tracing::info!(
+            dir = %self.dir.display(),
+            templates = templates.len(),
+            "prompt templates reloaded"
+        );
+        *self.templates.write().await = templates;
+        *self.fingerprint.write().await = fingerprint;
+        true
+    }
+
+    /// 定期检查模板目录的后台任务
+    pub fn spawn_watcher(self: Arc<Self>, interval: Duration) {
+        if interval.is_zero() {
+            return;
+        }
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                self.reload_if_changed().await;
+            }
+        });
+    }
+}
+
+fn template_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "toml"))
+        .collect();
+    files.sort();
+    files
+}
+
+fn scan_fingerprint(dir: &Path) -> Vec<(PathBuf, SystemTime)> {
+    template_files(dir)
+        .into_iter()
+        .filter_map(|path| {
+            let modified = fs::metadata(&path).and_then(|meta| meta.modified()).ok()?;
+            Some((path, modified))
+        })
+        .collect()
+}
+
+fn load_templates(dir: &Path) -> HashMap<String, Arc<PromptTemplate>> {
+    let mut templates = HashMap::new();
+    templates.insert(
+        DEFAULT_TEMPLATE.to_string(),
+        Arc::new(PromptTemplate::builtin()),
+    );
+
+    for path in template_files(dir) {
+        let Some(name) = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+        else {
+            continue;
+        };
+
+        let result = fs::read_to_string(&path)
+            .map_err(PromptError::from)
+            .and_then(|raw| PromptTemplate::parse(&name, &raw, &path.to_string_lossy()));
+
+        match result {
+            Ok(template) => {
+                templates.insert(name, Arc::new(template));
+            }
+            Err(err) => {
+                tracing::warn!(path = %path.display(), error = %err, "skipping invalid prompt template");
+            }
+        }
+    }
+
+    templates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rag::test_support::retrieved_chunk;
+
+    #[test]
+    fn render_replaces_known_variables_only() {
+        assert_eq!(
+            render("Q: {{question}} / {{ unknown }}", &[("question", "CPM")]),
+            "Q: CPM / {{ unknown }}"
+        );
+        assert_eq!(render("tail {{open", &[]), "tail {{open");
+    }
+
+    #[test]
+    fn builtin_template_matches_legacy_layout() {
+        let template = PromptTemplate::builtin();
+        let chunks = vec![RetrievedChunk {
+            snippet: "内容A".to_string(),
+            ..retrieved_chunk("a", "a#0", "排查手册")
+        }];
+
+        assert_eq!(
+            template.build_context(&chunks),
+            "[来源1] 排查手册\n路径: a.md\n内容: 内容A\n"
+        );
+
+        let messages = template.build_messages("CPM 暴涨", &chunks);
+        assert_eq!(messages[0].role, "system");
+        assert!(messages[0].content.contains("绝对不要编造"));
+        assert_eq!(
+            messages[1].content,
+            "问题: CPM 暴涨\n\n参考资料:\n[来源1] 排查手册\n路径: a.md\n内容: 内容A\n"
+        );
+
+        assert_eq!(
+            template.degraded_with_sources_answer("超时", &[("T", "p.md")]),
+            "AI 生成服务暂时不可用：超时。\n\n以下是一些相关的参考文档，您可以自行查阅：\n- [T] p.md"
+        );
+    }
+
+    #[tokio::test]
+    async fn loads_and_hot_reloads_templates_from_dir() {
+        let dir = std::env::temp_dir().join(format!("engineqa-prompts-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let registry = PromptRegistry::new(&PromptConfig {
+            dir: dir.to_string_lossy().to_string(),
+            default_template: DEFAULT_TEMPLATE.to_string(),
+            reload_interval_secs: 0,
+        });
+        assert!(matches!(
+            registry.get(Some("short")).await,
+            Err(PromptError::NotFound(name)) if name == "short"
+        ));
+        assert_eq!(registry.get(None).await.unwrap().name, DEFAULT_TEMPLATE);
+
+        let custom = BUILTIN_DEFAULT.replace("builtin-2", "short-2");
+        fs::write(dir.join("short.toml"), custom).unwrap();
+        assert!(registry.reload_if_changed().await);
+
+        let template = registry.get(Some("short")).await.unwrap();
+        assert_eq!(template.name, "short");
+        assert_eq!(template.version, "short-2");
+        assert_eq!(registry.list().await.len(), 2);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,263 @@
+use std::time::Duration;
+
+use rand::Rng;
+use reqwest::StatusCode;
+
+use crate::{
+    config::{MockFault, MockFaultTarget, MockProviderConfig},
+    provider::{ChatMessage, InferenceProvider, ProviderError, ProviderResult, registry::MOCK},
+};
+
+const MOCK_CHAT_MODEL: &str = "mock-chat";
+pub const MOCK_EMBED_MODEL: &str = "mock-embed";
+
+/// 确定性的本地提供方：
+/// - embedding：字符 unigram/bigram 特征哈希后归一化，相同文本得到相同向量，相近文本相似度更高
+/// - chat：按模板回显问题与参考资料中的 `[来源N]` 条目
+/// - 可按配置注入延迟、429、500 与超时，用于验证降级链路
+pub struct MockProvider {
+    config: MockProviderConfig,
+    vector_size: usize,
+}
+
+impl MockProvider {
+    pub fn new(config: MockProviderConfig, vector_size: usize) -> Self {
+        Self {
+            config,
+            vector_size,
+        }
+    }
+
+    async fn simulate(&self, target: MockFaultTarget) -> ProviderResult<()> {
+        if self.config.latency_ms > 0 {
+            tokio::time::sleep(Duration::from_millis(self.config.latency_ms)).await;
+        }
+
+        let targeted = matches!(
+            (self.config.fault_target, target),
+            (MockFaultTarget::All, _)
+                | (MockFaultTarget::Chat, MockFaultTarget::Chat)
+                | (MockFaultTarget::Embed, MockFaultTarget::Embed)
+        );
+        if !targeted || self.config.fault == MockFault::None {
+            return Ok(());
+        }
+
+        let triggered =
+            self.config.fault_rate >= 1.0 || rand::rng().random::<f32>() < self.config.fault_rate;
+        if !triggered {
+            return Ok(());
+        }
+
+        match self.config.fault {
+            MockFault::None => Ok(()),
+            MockFault::RateLimit => Err(ProviderError::ApiError {
+                status: StatusCode::TOO_MANY_REQUESTS,
+                message: "mock rate limit".to_string(),
+                retry_after: Some(Duration::from_secs(1)),
+            }),
+            MockFault::ServerError => Err(ProviderError::ApiError {
+                status: StatusCode::INTERNAL_SERVER_ERROR,
+                message: "mock server error".to_string(),
+                retry_after: None,
+            }),
+            MockFault::Timeout => Err(ProviderError::Timeout),
+        }
+    }
+}
+
+/// FNV-1a，跨平台与版本稳定
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash = 0xcbf29ce484222325u64;
+    for byte in bytes {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+fn hash_embedding(text: &str, size: usize) -> Vec<f32> {
+    let mut vector = vec![0.0f32; size];
+    if size == 0 {
+        return vector;
+    }
+
+    let chars: Vec<char> = text
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+
+    let mut add_feature = |feature: &str| {
+        let hash = fnv1a(feature.as_bytes());
+        let idx = (hash % size as u64) as usize;
+        let sign = if hash >> 63 == 0 { 1.0 } else { -1.0 };
+        vector[idx] += sign;
+    };
+
+    for c in &chars {
+        add_feature(&c.to_string());
+    }
+    for pair in chars.windows(2) {
+        add_feature(&pair.iter().collect::<String>());
+    }
+
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm == 0.0 {
+        // 空文本：退化为基于整体哈希的单位向量，避免零向量导致余弦距离无意义
+        let idx = (fnv1a(text.as_bytes()) % size as u64) as usize;
+        vector[idx] = 1.0;
+        return vector;
+    }
+
+    vector.iter_mut().for_each(|v| *v /= norm);
+    vector
+}
+
+fn templated_answer(messages: &[ChatMessage]) -> String {
+    let user = messages
+        .iter()
+        .rev()
+        .find(|message| message.role == "user")
+        .map(|message| message.content.as_str())
+        .unwrap_or_default();
+
+    let question = user
+        .lines()
+        .find_map(|line| line.strip_prefix("问题:"))
+        .map(str::trim)
+        .unwrap_or_default();
+
+    let sources: Vec<&str> = user
+        .lines()
+        .map(str::trim)
+        .filter(|line| line.starts_with("[来源"))
+        .collect();
+
+    let mut answer = format!("【模拟回答】问题：{question}\n");
+    if sources.is_empty() {
+        answer.push_str("\n根据现有资料，我不确定。");
+    } else {
+        answer.push_str("\n根据参考资料，相关内容见：\n");
+        for source in sources {
+            answer.push_str("- ");
+            answer.push_str(source);
+            answer.push('\n');
+        }
+    }
+    answer
+}
+
+#[async_trait::async_trait]
+impl InferenceProvider for MockProvider {
+    fn name(&self) -> &str {
+        MOCK
+    }
+
+    fn chat_model(&self) -> &str {
+        MOCK_CHAT_MODEL
+    }
+
+    fn embed_model(&self) -> &str {
+        MOCK_EMBED_MODEL
+    }
+
+    async fn embed(&self, text: &str) -> ProviderResult<Vec<f32>> {
+        self.simulate(MockFaultTarget::Embed).await?;
+        Ok(hash_embedding(text, self.vector_size))
+    }
+
+    async fn chat(
+        &self,
+        messages: Vec<ChatMessage>,
+        _temperature: f32,
+        _max_tokens: u32,
+    ) -> ProviderResult<String> {
+        self.simulate(MockFaultTarget::Chat).await?;
+        Ok(templated_answer(&messages))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{error_code::ErrorCode, error_mapping};
+
+    fn provider(fault: MockFault, fault_target: MockFaultTarget) -> MockProvider {
+        MockProvider::new(
+            MockProviderConfig {
+                latency_ms: 0,
+                fault,
+                fault_rate: 1.0,
+                fault_target,
+            },
+            64,
+        )
+    }
+
+    fn cosine(a: &[f32], b: &[f32]) -> f32 {
+        a.iter().zip(b).map(|(x, y)| x * y).sum()
+    }
+
+    #[tokio::test]
+    async fn embeddings_are_deterministic_and_normalized() {
+        let mock = provider(MockFault::None, MockFaultTarget::All);
+        let a = mock.embed("CPM 暴涨怎么排查").await.unwrap();
+        let b = mock.embed("CPM 暴涨怎么排查").await.unwrap();
+
+        assert_eq!(a.len(), 64);
+        assert_eq!(a, b);
+        assert!((cosine(&a, &a) - 1.0).abs() < 1e-5);
+
+        let empty = mock.embed("").await.unwrap();
+        assert!((cosine(&empty, &empty) - 1.0).abs() < 1e-5);
+    }
+
+    #[tokio::test]
+    async fn similar_texts_score_higher() {
+        let mock = provider(MockFault::None, MockFaultTarget::All);
+        let query = mock.embed("CPM 暴涨").await.unwrap();
+        let near = mock.embed("CPM 暴涨排查手册").await.unwrap();
+        let far = mock.embed("发布流程与回滚").await.unwrap();
+
+        assert!(cosine(&query, &near) > cosine(&query, &far));
+    }
+
+    #[tokio::test]
+    async fn answer_echoes_cited_sources() {
+        let mock = provider(MockFault::None, MockFaultTarget::All);
+        let messages = vec![ChatMessage {
+            role: "user".to_string(),
+            content: "问题: CPM 暴涨\n\n参考资料:\n[来源1] 排查手册\n路径: a.md\n内容: ..."
+                .to_string(),
+        }];
+
+        let answer = mock.chat(messages, 0.2, 100).await.unwrap();
+        assert!(answer.contains("CPM 暴涨"));
+        assert!(answer.contains("[来源1] 排查手册"));
+    }
+
+    #[tokio::test]
+    async fn injected_faults_map_to_error_codes() {
+        let cases = [
+            (MockFault::RateLimit, ErrorCode::UpstreamRateLimit),
+            (MockFault::ServerError, ErrorCode::UpstreamUnavailable),
+            (MockFault::Timeout, ErrorCode::UpstreamTimeout),
+        ];
+
+        for (fault, expected) in cases {
+            let err = provider(fault, MockFaultTarget::All)
+                .chat(vec![], 0.2, 100)
+                .await
+                .unwrap_err();
+            assert_eq!(error_mapping::map_provider_error(&err), expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn fault_target_limits_injection() {
+        let mock = provider(MockFault::ServerError, MockFaultTarget::Chat);
+        assert!(mock.embed("q").await.is_ok());
+        assert!(mock.chat(vec![], 0.2, 100).await.is_err());
+    }
+}
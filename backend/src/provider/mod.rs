@@ -1,9 +1,15 @@
+pub mod mock;
+pub mod registry;
+pub mod retry;
+
 use crate::config::InternalApiConfig;
 use reqwest::{Client, StatusCode};
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 use uuid::Uuid;
 
+use self::retry::RetryPolicy;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatMessage {
     pub role: String,
@@ -52,7 +58,12 @@ pub enum ProviderError {
     RequestError(#[from] reqwest::Error),
 
     #[error("API error: {status} - {message}")]
-    ApiError { status: StatusCode, message: String },
+    ApiError {
+        status: StatusCode,
+        message: String,
+        /// 上游通过 `Retry-After` 指示的等待时长（仅 429/503）
+        retry_after: Option<Duration>,
+    },
 
     #[error("Serialization error: {0}")]
     SerializationError(#[from] serde_json::Error),
@@ -63,8 +74,22 @@ pub enum ProviderError {
 
 pub type ProviderResult<T> = Result<T, ProviderError>;
 
+/// 实际处理请求的提供方与模型
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ServedBy {
+    pub provider: String,
+    pub model: String,
+}
+
 #[async_trait::async_trait]
 pub trait InferenceProvider: Send + Sync {
+    /// 提供方名称（与配置中的名称一致）
+    fn name(&self) -> &str;
+
+    fn chat_model(&self) -> &str;
+
+    fn embed_model(&self) -> &str;
+
     async fn embed(&self, text: &str) -> ProviderResult<Vec<f32>>;
 
     async fn chat(
@@ -73,24 +98,86 @@ pub trait InferenceProvider: Send + Sync {
         temperature: f32,
         max_tokens: u32,
     ) -> ProviderResult<String>;
+
+    /// 生成向量并返回实际服务的提供方
+    async fn embed_routed(&self, text: &str) -> ProviderResult<(Vec<f32>, ServedBy)> {
+        let vector = self.embed(text).await?;
+        Ok((
+            vector,
+            ServedBy {
+                provider: self.name().to_string(),
+                model: self.embed_model().to_string(),
+            },
+        ))
+    }
+
+    /// 生成回答并返回实际服务的提供方
+    async fn chat_routed(
+        &self,
+        messages: Vec<ChatMessage>,
+        temperature: f32,
+        max_tokens: u32,
+    ) -> ProviderResult<(String, ServedBy)> {
+        let answer = self.chat(messages, temperature, max_tokens).await?;
+        Ok((
+            answer,
+            ServedBy {
+                provider: self.name().to_string(),
+                model: self.chat_model().to_string(),
+            },
+        ))
+    }
 }
 
 pub struct InternalApiProvider {
+    name: String,
     config: InternalApiConfig,
     client: Client,
 }
 
 impl InternalApiProvider {
     pub fn new(config: InternalApiConfig) -> Self {
+        Self::named(registry::INTERNAL_API, config)
+    }
+
+    /// 以指定名称创建，用于同一协议的多个端点（如 OpenAI 兼容端点）
+    pub fn named(name: &str, config: InternalApiConfig) -> Self {
         let client = Client::builder()
             .timeout(Duration::from_millis(config.llm_timeout_ms))
             .build()
             .expect("Failed to create HTTP client");
 
-        Self { config, client }
+        Self {
+            name: name.to_string(),
+            config,
+            client,
+        }
+    }
+
+    fn chat_retry_policy(&self) -> RetryPolicy {
+        RetryPolicy {
+            max_retries: self.config.retry_chat_max,
+            base_delay: Duration::from_millis(self.config.retry_base_delay_ms),
+            max_delay: Duration::from_millis(self.config.retry_max_delay_ms),
+            budget: Duration::from_millis(self.config.retry_chat_budget_ms),
+        }
     }
 
-    async fn do_request<R, S>(&self, path: &str, request: &R, timeout_ms: u64) -> ProviderResult<S>
+    fn embed_retry_policy(&self) -> RetryPolicy {
+        RetryPolicy {
+            max_retries: self.config.retry_embed_max,
+            base_delay: Duration::from_millis(self.config.retry_base_delay_ms),
+            max_delay: Duration::from_millis(self.config.retry_max_delay_ms),
+            budget: Duration::from_millis(self.config.retry_embed_budget_ms),
+        }
+    }
+
+    async fn do_request<R, S>(
+        &self,
+        path: &str,
+        request: &R,
+        timeout: Duration,
+    ) -> ProviderResult<S>
     where
         R: Serialize + ?Sized,
         S: for<'de> Deserialize<'de>,
@@ -104,7 +191,7 @@ impl InternalApiProvider {
             .header("Authorization", format!("Bearer {}", self.config.token))
             .header("X-Request-Id", &trace_id)
             .header("Content-Type", "application/json")
-            .timeout(Duration::from_millis(timeout_ms))
+            .timeout(timeout)
             .json(request)
             .send()
             .await?;
@@ -114,6 +201,12 @@ impl InternalApiProvider {
         if status.is_success() {
             response.json().await.map_err(ProviderError::from)
         } else {
+            let retry_after = match status {
+                StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE => {
+                    retry::parse_retry_after(response.headers())
+                }
+                _ => None,
+            };
             let error_text = response
                 .text()
                 .await
@@ -121,30 +214,15 @@ impl InternalApiProvider {
             Err(ProviderError::ApiError {
                 status,
                 message: error_text,
+                retry_after,
             })
         }
     }
 
     async fn embed_with_retry(&self, text: &str) -> ProviderResult<Vec<f32>> {
-        let max_retries = self.config.retry_embed_max as usize;
-
-        for attempt in 0..=max_retries {
-            match self.embed_once(text).await {
-                Ok(result) => return Ok(result),
-                Err(e) if attempt < max_retries => {
-                    tracing::warn!(
-                        attempt = attempt + 1,
-                        max_retries = max_retries,
-                        error = %e,
-                        "embed request failed, retrying"
-                    );
-                    tokio::time::sleep(Duration::from_millis(500 * (attempt as u64 + 1))).await;
-                }
-                Err(e) => return Err(e),
-            }
-        }
-
-        unreachable!()
+        self.embed_retry_policy()
+            .run("embed", |remaining| self.embed_once(text, remaining))
+            .await
     }
 
     async fn chat_with_retry(
@@ -153,31 +231,14 @@ impl InternalApiProvider {
         temperature: f32,
         max_tokens: u32,
     ) -> ProviderResult<String> {
-        let max_retries = self.config.retry_chat_max as usize;
-
-        for attempt in 0..=max_retries {
-            match self
-                .chat_once(messages.clone(), temperature, max_tokens)
-                .await
-            {
-                Ok(result) => return Ok(result),
-                Err(e) if attempt < max_retries => {
-                    tracing::warn!(
-                        attempt = attempt + 1,
-                        max_retries = max_retries,
-                        error = %e,
-                        "chat request failed, retrying"
-                    );
-                    tokio::time::sleep(Duration::from_millis(500 * (attempt as u64 + 1))).await;
-                }
-                Err(e) => return Err(e),
-            }
-        }
-
-        unreachable!()
+        self.chat_retry_policy()
+            .run("chat", |remaining| {
+                self.chat_once(messages.clone(), temperature, max_tokens, remaining)
+            })
+            .await
     }
 
-    async fn embed_once(&self, text: &str) -> ProviderResult<Vec<f32>> {
+    async fn embed_once(&self, text: &str, remaining: Duration) -> ProviderResult<Vec<f32>> {
         let request = EmbeddingRequest {
             model: self.config.embed_model.clone(),
             input: text.to_string(),
@@ -187,7 +248,7 @@ impl InternalApiProvider {
             .do_request(
                 &self.config.embed_path,
                 &request,
-                self.config.embed_timeout_ms,
+                Duration::from_millis(self.config.embed_timeout_ms).min(remaining),
             )
             .await?;
 
@@ -198,6 +259,7 @@ impl InternalApiProvider {
             .ok_or_else(|| ProviderError::ApiError {
                 status: StatusCode::UNPROCESSABLE_ENTITY,
                 message: "No embedding data returned".to_string(),
+                retry_after: None,
             })
     }
 
@@ -206,6 +268,7 @@ impl InternalApiProvider {
         messages: Vec<ChatMessage>,
         temperature: f32,
         max_tokens: u32,
+        remaining: Duration,
     ) -> ProviderResult<String> {
         let request = ChatRequest {
             model: self.config.chat_model.clone(),
@@ -215,7 +278,11 @@ impl InternalApiProvider {
         };
 
         let response: ChatResponse = self
-            .do_request(&self.config.chat_path, &request, self.config.llm_timeout_ms)
+            .do_request(
+                &self.config.chat_path,
+                &request,
+                Duration::from_millis(self.config.llm_timeout_ms).min(remaining),
+            )
             .await?;
 
         response
@@ -225,12 +292,25 @@ impl InternalApiProvider {
             .ok_or_else(|| ProviderError::ApiError {
                 status: StatusCode::UNPROCESSABLE_ENTITY,
                 message: "No chat response returned".to_string(),
+                retry_after: None,
             })
     }
 }
 
 #[async_trait::async_trait]
 impl InferenceProvider for InternalApiProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn chat_model(&self) -> &str {
+        &self.config.chat_model
+    }
+
+    fn embed_model(&self) -> &str {
+        &self.config.embed_model
+    }
+
     async fn embed(&self, text: &str) -> ProviderResult<Vec<f32>> {
         self.embed_with_retry(text).await
     }
@@ -0,0 +1,277 @@
+use std::{collections::HashMap, sync::Arc};
+
+use crate::{
+    api::{error_code::ErrorCode, error_mapping},
+    config::AppConfig,
+    provider::{
+        ChatMessage, InferenceProvider, InternalApiProvider, ProviderError, ProviderResult,
+        ServedBy, mock::MockProvider,
+    },
+};
+
+/// 公司内部推理 API
+pub const INTERNAL_API: &str = "internal_api";
+/// OpenAI 兼容端点（作为备用提供方）
+pub const OPENAI_COMPAT: &str = "openai_compat";
+/// 本地确定性 mock（离线开发与测试）
+pub const MOCK: &str = "mock";
+
+/// 已支持的提供方名称
+pub const KNOWN_PROVIDERS: &[&str] = &[INTERNAL_API, OPENAI_COMPAT, MOCK];
+
+/// 提供方注册表：chat 与 embedding 各自维护有序的提供方链，
+/// 上游不可用或超时时按顺序回退到下一个提供方
+pub struct ProviderRegistry {
+    chat: Vec<Arc<dyn InferenceProvider>>,
+    embed: Vec<Arc<dyn InferenceProvider>>,
+}
+
+impl ProviderRegistry {
+    pub fn new(
+        chat: Vec<Arc<dyn InferenceProvider>>,
+        embed: Vec<Arc<dyn InferenceProvider>>,
+    ) -> Self {
+        assert!(!chat.is_empty(), "chat provider chain must not be empty");
+        assert!(!embed.is_empty(), "embed provider chain must not be empty");
+        Self { chat, embed }
+    }
+
+    /// 根据配置构建注册表，同名提供方在 chat/embed 链之间共享同一实例
+    pub fn from_config(config: &AppConfig) -> Self {
+        let mut instances: HashMap<String, Arc<dyn InferenceProvider>> = HashMap::new();
+        let mut resolve = |names: &[String]| -> Vec<Arc<dyn InferenceProvider>> {
+            names
+                .iter()
+                .filter_map(|name| {
+                    if let Some(provider) = instances.get(name) {
+                        return Some(provider.clone());
+                    }
+                    let provider = build_provider(name, config)?;
+                    instances.insert(name.clone(), provider.clone());
+                    Some(provider)
+                })
+                .collect()
+        };
+
+        let chat = resolve(&config.chat_providers);
+        let embed = resolve(&config.embed_providers);
+        Self::new(chat, embed)
+    }
+
+    fn primary_chat(&self) -> &Arc<dyn InferenceProvider> {
+        &self.chat[0]
+    }
+}
+
+fn build_provider(name: &str, config: &AppConfig) -> Option<Arc<dyn InferenceProvider>> {
+    match name {
+        INTERNAL_API => Some(Arc::new(InternalApiProvider::new(
+            config.internal_api.clone(),
+        ))),
+        OPENAI_COMPAT => config
+            .openai_compat
+            .clone()
+            .map(|api| Arc::new(InternalApiProvider::named(OPENAI_COMPAT, api)) as _),
+        MOCK => Some(Arc::new(MockProvider::new(
+            config.mock.clone(),
+            config.embedding_vector_size,
+        ))),
+        _ => {
+            tracing::warn!(provider = %name, "unknown inference provider, skipping");
+            None
+        }
+    }
+}
+
+/// 仅在上游不可用或超时时回退；认证、参数错误等换提供方也无济于事
+fn should_fallback(error: &ProviderError) -> bool {
+    matches!(
+        error_mapping::map_provider_error(error),
+        ErrorCode::UpstreamUnavailable | ErrorCode::UpstreamTimeout
+    )
+}
+
+#[async_trait::async_trait]
+impl InferenceProvider for ProviderRegistry {
+    fn name(&self) -> &str {
+        self.primary_chat().name()
+    }
+
+    fn chat_model(&self) -> &str {
+        self.primary_chat().chat_model()
+    }
+
+    fn embed_model(&self) -> &str {
+        self.embed[0].embed_model()
+    }
+
+    async fn embed(&self, text: &str) -> ProviderResult<Vec<f32>> {
+        self.embed_routed(text).await.map(|(vector, _)| vector)
+    }
+
+    async fn chat(
+        &self,
+        messages: Vec<ChatMessage>,
+        temperature: f32,
+        max_tokens: u32,
+    ) -> ProviderResult<String> {
+        self.chat_routed(messages, temperature, max_tokens)
+            .await
+            .map(|(answer, _)| answer)
+    }
+
+    async fn embed_routed(&self, text: &str) -> ProviderResult<(Vec<f32>, ServedBy)> {
+        let mut last_error = None;
+
+        for (idx, provider) in self.embed.iter().enumerate() {
+            match provider.embed_routed(text).await {
+                Ok(result) => return Ok(result),
+                Err(err) if idx + 1 < self.embed.len() && should_fallback(&err) => {
+                    tracing::warn!(
+                        provider = %provider.name(),
+                        next = %self.embed[idx + 1].name(),
+                        error = %err,
+                        "embed provider failed, falling back"
+                    );
+                    last_error = Some(err);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+
+        Err(last_error.unwrap_or(ProviderError::Timeout))
+    }
+
+    async fn chat_routed(
+        &self,
+        messages: Vec<ChatMessage>,
+        temperature: f32,
+        max_tokens: u32,
+    ) -> ProviderResult<(String, ServedBy)> {
+        let mut last_error = None;
+
+        for (idx, provider) in self.chat.iter().enumerate() {
+            match provider
+                .chat_routed(messages.clone(), temperature, max_tokens)
+                .await
+            {
+                Ok(result) => return Ok(result),
+                Err(err) if idx + 1 < self.chat.len() && should_fallback(&err) => {
+                    tracing::warn!(
+                        provider = %provider.name(),
+                        next = %self.chat[idx + 1].name(),
+                        error = %err,
+                        "chat provider failed, falling back"
+                    );
+                    last_error = Some(err);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+
+        Err(last_error.unwrap_or(ProviderError::Timeout))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::StatusCode;
+
+    struct StubProvider {
+        name: &'static str,
+        status: Option<StatusCode>,
+    }
+
+    #[async_trait::async_trait]
+    impl InferenceProvider for StubProvider {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn chat_model(&self) -> &str {
+            "stub-chat"
+        }
+
+        fn embed_model(&self) -> &str {
+            "stub-embed"
+        }
+
+        async fn embed(&self, _text: &str) -> ProviderResult<Vec<f32>> {
+            match self.status {
+                Some(status) => Err(ProviderError::ApiError {
+                    status,
+                    message: String::new(),
+                    retry_after: None,
+                }),
+                None => Ok(vec![1.0]),
+            }
+        }
+
+        async fn chat(
+            &self,
+            _messages: Vec<ChatMessage>,
+            _temperature: f32,
+            _max_tokens: u32,
+        ) -> ProviderResult<String> {
+            match self.status {
+                Some(status) => Err(ProviderError::ApiError {
+                    status,
+                    message: String::new(),
+                    retry_after: None,
+                }),
+                None => Ok(self.name.to_string()),
+            }
+        }
+    }
+
+    fn stub(name: &'static str, status: Option<StatusCode>) -> Arc<dyn InferenceProvider> {
+        Arc::new(StubProvider { name, status })
+    }
+
+    #[tokio::test]
+    async fn falls_back_when_primary_unavailable() {
+        let registry = ProviderRegistry::new(
+            vec![
+                stub("primary", Some(StatusCode::SERVICE_UNAVAILABLE)),
+                stub("secondary", None),
+            ],
+            vec![stub("primary", None)],
+        );
+
+        let (answer, served_by) = registry.chat_routed(vec![], 0.0, 1).await.unwrap();
+        assert_eq!(answer, "secondary");
+        assert_eq!(served_by.provider, "secondary");
+        assert_eq!(served_by.model, "stub-chat");
+    }
+
+    #[tokio::test]
+    async fn does_not_fall_back_on_auth_error() {
+        let registry = ProviderRegistry::new(
+            vec![
+                stub("primary", Some(StatusCode::UNAUTHORIZED)),
+                stub("secondary", None),
+            ],
+            vec![stub("primary", None)],
+        );
+
+        assert!(registry.chat_routed(vec![], 0.0, 1).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn returns_last_error_when_chain_exhausted() {
+        let registry = ProviderRegistry::new(
+            vec![stub("primary", None)],
+            vec![
+                stub("primary", Some(StatusCode::BAD_GATEWAY)),
+                stub("secondary", Some(StatusCode::GATEWAY_TIMEOUT)),
+            ],
+        );
+
+        let err = registry.embed_routed("q").await.unwrap_err();
+        assert_eq!(
+            error_mapping::map_provider_error(&err),
+            ErrorCode::UpstreamTimeout
+        );
+    }
+}
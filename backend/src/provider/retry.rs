@@ -0,0 +1,226 @@
+use std::{future::Future, time::Duration};
+
+use rand::Rng;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use tokio::time::Instant;
+
+use crate::{
+    api::error_mapping,
+    provider::{ProviderError, ProviderResult},
+};
+
+/// 上游调用的重试策略：仅重试瞬时错误，指数退避 + 抖动，并受总时长预算约束
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// 最大重试次数（不含首次请求）
+    pub max_retries: u32,
+    /// 退避基准时长
+    pub base_delay: Duration,
+    /// 单次退避上限
+    pub max_delay: Duration,
+    /// 单次调用（含所有重试）的总时长预算
+    pub budget: Duration,
+}
+
+impl RetryPolicy {
+    /// 执行带重试的上游调用，`op` 接收本次尝试可用的超时时长
+    pub async fn run<T, F, Fut>(&self, op_name: &'static str, mut op: F) -> ProviderResult<T>
+    where
+        F: FnMut(Duration) -> Fut,
+        Fut: Future<Output = ProviderResult<T>>,
+    {
+        let deadline = Instant::now() + self.budget;
+        let mut attempt = 0u32;
+
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(ProviderError::Timeout);
+            }
+
+            let err = match op(remaining).await {
+                Ok(result) => return Ok(result),
+                Err(err) => err,
+            };
+
+            if attempt >= self.max_retries || !is_retryable(&err) {
+                return Err(err);
+            }
+
+            let delay = match retry_after(&err) {
+                Some(retry_after) => retry_after,
+                None => self.backoff_delay(attempt, rand::rng().random::<f64>()),
+            };
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if delay >= remaining {
+                tracing::warn!(
+                    op = op_name,
+                    attempt = attempt + 1,
+                    delay_ms = delay.as_millis() as u64,
+                    remaining_ms = remaining.as_millis() as u64,
+                    error = %err,
+                    "retry budget exhausted, giving up"
+                );
+                return Err(err);
+            }
+
+            attempt += 1;
+            tracing::warn!(
+                op = op_name,
+                attempt,
+                max_retries = self.max_retries,
+                delay_ms = delay.as_millis() as u64,
+                error = %err,
+                "upstream request failed, retrying"
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// 计算第 `attempt` 次重试前的退避时长（full jitter），`jitter` 取值 [0, 1)
+    fn backoff_delay(&self, attempt: u32, jitter: f64) -> Duration {
+        let exp = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay);
+        exp.mul_f64(jitter.clamp(0.0, 1.0))
+    }
+}
+
+/// 是否为可重试的瞬时错误（超时、限流、上游不可用）
+pub fn is_retryable(error: &ProviderError) -> bool {
+    error_mapping::is_transient(error_mapping::map_provider_error(error))
+}
+
+fn retry_after(error: &ProviderError) -> Option<Duration> {
+    match error {
+        ProviderError::ApiError { retry_after, .. } => *retry_after,
+        _ => None,
+    }
+}
+
+/// 解析 `Retry-After` 响应头，支持秒数与 HTTP-date 两种格式
+pub fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let raw = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+
+    if let Ok(seconds) = raw.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let at = chrono::DateTime::parse_from_rfc2822(raw).ok()?;
+    let delta = at.with_timezone(&chrono::Utc) - chrono::Utc::now();
+    Some(delta.to_std().unwrap_or(Duration::ZERO))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::{StatusCode, header::HeaderValue};
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn policy(max_retries: u32, budget_ms: u64) -> RetryPolicy {
+        RetryPolicy {
+            max_retries,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(5),
+            budget: Duration::from_millis(budget_ms),
+        }
+    }
+
+    fn api_error(status: StatusCode) -> ProviderError {
+        ProviderError::ApiError {
+            status,
+            message: String::new(),
+            retry_after: None,
+        }
+    }
+
+    #[test]
+    fn backoff_grows_exponentially_and_caps() {
+        let policy = RetryPolicy {
+            max_retries: 5,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(1000),
+            budget: Duration::from_secs(10),
+        };
+        assert_eq!(policy.backoff_delay(0, 1.0), Duration::from_millis(100));
+        assert_eq!(policy.backoff_delay(2, 1.0), Duration::from_millis(400));
+        assert_eq!(policy.backoff_delay(10, 1.0), Duration::from_millis(1000));
+        assert_eq!(policy.backoff_delay(2, 0.5), Duration::from_millis(200));
+        assert_eq!(policy.backoff_delay(2, 0.0), Duration::ZERO);
+    }
+
+    #[test]
+    fn only_transient_errors_are_retryable() {
+        assert!(is_retryable(&api_error(StatusCode::TOO_MANY_REQUESTS)));
+        assert!(is_retryable(&api_error(StatusCode::SERVICE_UNAVAILABLE)));
+        assert!(is_retryable(&ProviderError::Timeout));
+        assert!(!is_retryable(&api_error(StatusCode::UNAUTHORIZED)));
+        assert!(!is_retryable(&api_error(StatusCode::BAD_REQUEST)));
+    }
+
+    #[test]
+    fn parses_retry_after_seconds() {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("3"));
+        assert_eq!(parse_retry_after(&headers), Some(Duration::from_secs(3)));
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("not-a-date"));
+        assert_eq!(parse_retry_after(&headers), None);
+    }
+
+    #[tokio::test]
+    async fn does_not_retry_auth_errors() {
+        let calls = AtomicU32::new(0);
+        let result: ProviderResult<()> = policy(3, 1000)
+            .run("test", |_| {
+                calls.fetch_add(1, Ordering::SeqCst);
+                async { Err(api_error(StatusCode::UNAUTHORIZED)) }
+            })
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn retries_transient_errors_until_success() {
+        let calls = AtomicU32::new(0);
+        let result = policy(3, 1000)
+            .run("test", |_| {
+                let n = calls.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    if n < 2 {
+                        Err(api_error(StatusCode::BAD_GATEWAY))
+                    } else {
+                        Ok(n)
+                    }
+                }
+            })
+            .await;
+
+        assert_eq!(result.unwrap(), 2);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn gives_up_when_retry_after_exceeds_budget() {
+        let calls = AtomicU32::new(0);
+        let result: ProviderResult<()> = policy(3, 50)
+            .run("test", |_| {
+                calls.fetch_add(1, Ordering::SeqCst);
+                async {
+                    Err(ProviderError::ApiError {
+                        status: StatusCode::TOO_MANY_REQUESTS,
+                        message: String::new(),
+                        retry_after: Some(Duration::from_secs(5)),
+                    })
+                }
+            })
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
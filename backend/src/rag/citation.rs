@@ -0,0 +1,265 @@
+use serde::Serialize;
+
+const MARKER_PREFIX: &str = "[来源";
+
+/// 句子边界：这些字符结束一个答案片段
+fn is_sentence_boundary(c: char) -> bool {
+    matches!(c, '。' | '！' | '？' | '；' | '!' | '?' | ';' | '\n')
+}
+
+/// 答案中的一处 `[来源N]` 引用
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Citation {
+    /// 引用编号 N（从 1 开始，对应上下文中的 `[来源N]`）
+    pub number: usize,
+    /// 对应 `sources` 的下标；编号不存在时为空
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_index: Option<usize>,
+    /// 被引用的知识片段 ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chunk_id: Option<String>,
+    /// 编号是否存在于本次检索结果中
+    pub valid: bool,
+    /// 引用所依附的答案片段，按字符偏移（左闭右开）
+    pub span_start: usize,
+    pub span_end: usize,
+    /// 片段文本（已去除引用标记）
+    pub text: String,
+}
+
+/// 引用解析结果
+#[derive(Debug, Clone, Default)]
+pub struct CitationReport {
+    pub citations: Vec<Citation>,
+    /// 被引用过的 `sources` 下标
+    pub cited_sources: Vec<usize>,
+    /// 不存在的引用编号（去重、升序）
+    pub invalid_numbers: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Marker {
+    numbers_start: usize,
+    start: usize,
+    end: usize,
+}
+
+/// 解析答案中的 `[来源N]` 标记（兼容 `[来源 N]`、`[来源1,2]`、`[来源1、2]`），
+/// `chunk_ids` 与返回给客户端的 `sources` 一一对应
+pub fn extract_citations(answer: &str, chunk_ids: &[&str]) -> CitationReport {
+    let chars: Vec<char> = answer.chars().collect();
+    let markers = find_markers(&chars);
+    if markers.is_empty() {
+        return CitationReport::default();
+    }
+
+    let segments = split_segments(&chars, &markers);
+    let mut report = CitationReport::default();
+
+    for marker in &markers {
+        let (seg_start, seg_end) = segments
+            .iter()
+            .copied()
+            .find(|(start, end)| *start <= marker.start && marker.start < *end)
+            .unwrap_or((marker.start, marker.end));
+        let text = segment_text(&chars, seg_start, seg_end, &markers);
+
+        for number in parse_numbers(&chars[marker.numbers_start..marker.end - 1]) {
+            let source_index = number.checked_sub(1).filter(|idx| *idx < chunk_ids.len());
+            let valid = source_index.is_some();
+
+            if let Some(idx) = source_index {
+                if !report.cited_sources.contains(&idx) {
+                    report.cited_sources.push(idx);
+                }
+            } else if !report.invalid_numbers.contains(&number) {
+                report.invalid_numbers.push(number);
+            }
+
+            report.citations.push(Citation {
+                number,
+                source_index,
+                chunk_id: source_index.map(|idx| chunk_ids[idx].to_string()),
+                valid,
+                span_start: seg_start,
+                span_end: seg_end,
+                text: text.clone(),
+            });
+        }
+    }
+
+    report.cited_sources.sort_unstable();
+    report.invalid_numbers.sort_unstable();
+    report
+}
+
+/// 去除文本中的引用标记
+pub fn strip_markers(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let markers = find_markers(&chars);
+    segment_text(&chars, 0, chars.len(), &markers)
+}
+
+fn find_markers(chars: &[char]) -> Vec<Marker> {
+    let prefix: Vec<char> = MARKER_PREFIX.chars().collect();
+    let mut markers = Vec::new();
+    let mut i = 0;
+
+    while i + prefix.len() < chars.len() {
+        if chars[i..].starts_with(&prefix) {
+            let numbers_start = i + prefix.len();
+            let close = chars[numbers_start..]
+                .iter()
+                .take_while(|c| c.is_ascii_digit() || matches!(c, ' ' | ',' | '，' | '、'))
+                .count()
+                + numbers_start;
+
+            if close < chars.len()
+                && chars[close] == ']'
+                && chars[numbers_start..close].iter().any(char::is_ascii_digit)
+            {
+                markers.push(Marker {
+                    numbers_start,
+                    start: i,
+                    end: close + 1,
+                });
+                i = close + 1;
+                continue;
+            }
+        }
+        i += 1;
+    }
+
+    markers
+}
+
+fn parse_numbers(chars: &[char]) -> Vec<usize> {
+    chars
+        .split(|c| !c.is_ascii_digit())
+        .filter(|digits| !digits.is_empty())
+        .filter_map(|digits| digits.iter().collect::<String>().parse().ok())
+        .collect()
+}
+
+/// 按句子切分答案；紧跟在句末标点之后的引用标记归属前一句
+fn split_segments(chars: &[char], markers: &[Marker]) -> Vec<(usize, usize)> {
+    let mut segments = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+
+    while i < chars.len() {
+        if let Some(marker) = markers.iter().find(|m| m.start == i) {
+            i = marker.end;
+            continue;
+        }
+
+        if is_sentence_boundary(chars[i]) {
+            let mut end = i + 1;
+            // 吸收句末之后的引用标记（允许空白间隔，不跨行）
+            loop {
+                let next = (end..chars.len())
+                    .find(|&j| !chars[j].is_whitespace() || chars[j] == '\n')
+                    .unwrap_or(chars.len());
+                match markers.iter().find(|m| m.start == next) {
+                    Some(marker) => end = marker.end,
+                    None => break,
+                }
+            }
+            segments.push((start, end));
+            start = end;
+            i = end;
+            continue;
+        }
+
+        i += 1;
+    }
+
+    if start < chars.len() {
+        segments.push((start, chars.len()));
+    }
+
+    segments
+        .into_iter()
+        .map(|(start, end)| {
+            let leading = chars[start..end]
+                .iter()
+                .take_while(|c| c.is_whitespace())
+                .count();
+            (start + leading, end)
+        })
+        .filter(|(start, end)| start < end)
+        .collect()
+}
+
+fn segment_text(chars: &[char], start: usize, end: usize, markers: &[Marker]) -> String {
+    let mut text = String::new();
+    let mut i = start;
+    while i < end {
+        if let Some(marker) = markers.iter().find(|m| m.start == i) {
+            i = marker.end;
+            continue;
+        }
+        text.push(chars[i]);
+        i += 1;
+    }
+    text.trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_markers_to_sources_and_spans() {
+        let answer = "先检查出价配置[来源1]。然后回滚策略。[来源2]";
+        let report = extract_citations(answer, &["c1", "c2"]);
+
+        assert_eq!(report.citations.len(), 2);
+        assert_eq!(report.citations[0].number, 1);
+        assert_eq!(report.citations[0].chunk_id.as_deref(), Some("c1"));
+        assert_eq!(report.citations[0].text, "先检查出价配置。");
+        assert_eq!(report.citations[1].text, "然后回滚策略。");
+        assert_eq!(report.cited_sources, vec![0, 1]);
+        assert!(report.invalid_numbers.is_empty());
+
+        let span: String = answer
+            .chars()
+            .skip(report.citations[1].span_start)
+            .take(report.citations[1].span_end - report.citations[1].span_start)
+            .collect();
+        assert_eq!(span, "然后回滚策略。[来源2]");
+    }
+
+    #[test]
+    fn flags_nonexistent_numbers() {
+        let report = extract_citations("见文档[来源3]", &["c1"]);
+
+        assert_eq!(report.invalid_numbers, vec![3]);
+        assert!(!report.citations[0].valid);
+        assert!(report.citations[0].source_index.is_none());
+        assert!(report.cited_sources.is_empty());
+    }
+
+    #[test]
+    fn supports_grouped_and_spaced_markers() {
+        let report = extract_citations("两处均有说明[来源1、2]，另见[来源 1]", &["c1", "c2"]);
+
+        let numbers: Vec<usize> = report.citations.iter().map(|c| c.number).collect();
+        assert_eq!(numbers, vec![1, 2, 1]);
+        assert_eq!(report.cited_sources, vec![0, 1]);
+    }
+
+    #[test]
+    fn strips_markers_from_text() {
+        assert_eq!(
+            strip_markers(" 重启服务[来源1]即可[来源2、3]。"),
+            "重启服务即可。"
+        );
+    }
+
+    #[test]
+    fn ignores_text_without_markers() {
+        let report = extract_citations("[来源] 不是引用 [来源x]", &["c1"]);
+        assert!(report.citations.is_empty());
+    }
+}
@@ -0,0 +1,328 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::{
+    cache::{
+        answer::cosine_similarity,
+        embedding::{EmbeddingCache, embed_cached},
+    },
+    config::{GroundingConfig, GroundingMode},
+    provider::{ChatMessage, InferenceProvider, ProviderError},
+    rag::{RetrievedChunk, citation::strip_markers},
+};
+
+/// 少于该字符数的句子（如“综上：”）不参与校验
+const MIN_SENTENCE_CHARS: usize = 6;
+
+const VERIFY_TEMPERATURE: f32 = 0.0;
+const VERIFY_MAX_TOKENS: u32 = 1024;
+
+const VERIFY_SYSTEM_PROMPT: &str = r#"你是一个严格的事实核查助手。
+给定参考资料和若干编号句子，逐句判断该句内容是否能由参考资料直接支持。
+
+只输出判定结果，每行一句，格式为 "编号: 是" 或 "编号: 否"，不要输出任何解释。"#;
+
+/// 未通过校验的句子
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UnsupportedSentence {
+    pub text: String,
+    /// 与最相近片段的相似度（仅向量校验时提供）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub similarity: Option<f32>,
+}
+
+/// 答案事实性校验结果
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GroundingReport {
+    pub mode: String,
+    /// 有依据句子占比，取值 [0, 1]
+    pub score: f32,
+    pub checked_sentences: usize,
+    pub unsupported: Vec<UnsupportedSentence>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum GroundingError {
+    #[error("Provider error: {0}")]
+    ProviderError(#[from] ProviderError),
+
+    #[error("Invalid verification output: {0}")]
+    InvalidVerdict(String),
+}
+
+pub type GroundingResult<T> = Result<T, GroundingError>;
+
+/// 生成后校验：逐句检查答案是否能由检索片段支持
+pub struct GroundingChecker {
+    config: GroundingConfig,
+    provider: Arc<dyn InferenceProvider>,
+    embedding_cache: Arc<EmbeddingCache>,
+}
+
+impl GroundingChecker {
+    pub fn new(
+        config: &GroundingConfig,
+        provider: Arc<dyn InferenceProvider>,
+        embedding_cache: Arc<EmbeddingCache>,
+    ) -> Self {
+        Self {
+            config: config.clone(),
+            provider,
+            embedding_cache,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// 得分是否低于配置下限且需要降级
+    pub fn should_degrade(&self, report: &GroundingReport) -> bool {
+        self.config.degrade_on_low_score && report.score < self.config.min_score
+    }
+
+    pub async fn check(
+        &self,
+        answer: &str,
+        chunks: &[RetrievedChunk],
+    ) -> GroundingResult<GroundingReport> {
+        let sentences = split_sentences(answer, chunks);
+        let mode = self.config.mode;
+
+        if sentences.is_empty() {
+            return Ok(build_report(mode, 0, vec![]));
+        }
+
+        // 每句对应的判定：Some(similarity) 表示向量校验的相似度
+        let mut verdicts: Vec<(bool, Option<f32>)> = match mode {
+            GroundingMode::Llm => vec![(false, None); sentences.len()],
+            GroundingMode::Embedding | GroundingMode::Hybrid => {
+                let similarities = self.embedding_similarities(&sentences, chunks).await?;
+                similarities
+                    .into_iter()
+                    .map(|sim| (sim >= self.config.sentence_threshold, Some(sim)))
+                    .collect()
+            }
+        };
+
+        if matches!(mode, GroundingMode::Llm | GroundingMode::Hybrid) {
+            let pending: Vec<usize> = (0..sentences.len())
+                .filter(|&idx| !verdicts[idx].0)
+                .collect();
+            if !pending.is_empty() {
+                let to_verify: Vec<&str> =
+                    pending.iter().map(|&idx| sentences[idx].as_str()).collect();
+                let supported = self.llm_verdicts(&to_verify, chunks).await?;
+                for (idx, ok) in pending.into_iter().zip(supported) {
+                    verdicts[idx].0 = ok;
+                }
+            }
+        }
+
+        let checked = sentences.len();
+        let unsupported = sentences
+            .into_iter()
+            .zip(verdicts)
+            .filter(|(_, (supported, _))| !supported)
+            .map(|(text, (_, similarity))| UnsupportedSentence { text, similarity })
+            .collect::<Vec<_>>();
+
+        Ok(build_report(mode, checked, unsupported))
+    }
+
+    async fn embedding_similarities(
+        &self,
+        sentences: &[String],
+        chunks: &[RetrievedChunk],
+    ) -> GroundingResult<Vec<f32>> {
+        let mut chunk_vectors = Vec::with_capacity(chunks.len());
+        for chunk in chunks {
+            chunk_vectors.push(
+                embed_cached(
+                    self.provider.as_ref(),
+                    &self.embedding_cache,
+                    &chunk.snippet,
+                )
+                .await?,
+            );
+        }
+
+        let mut similarities = Vec::with_capacity(sentences.len());
+        for sentence in sentences {
+            let vector =
+                embed_cached(self.provider.as_ref(), &self.embedding_cache, sentence).await?;
+            let best = chunk_vectors
+                .iter()
+                .map(|chunk| cosine_similarity(&vector, chunk))
+                .fold(0.0_f32, f32::max);
+            similarities.push(best);
+        }
+
+        Ok(similarities)
+    }
+
+    async fn llm_verdicts(
+        &self,
+        sentences: &[&str],
+        chunks: &[RetrievedChunk],
+    ) -> GroundingResult<Vec<bool>> {
+        let context = chunks
+            .iter()
+            .enumerate()
+            .map(|(i, chunk)| {
+                format!(
+                    "[资料{}] {}\n{}",
+                    i + 1,
+                    chunk.metadata.title_path,
+                    chunk.snippet
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        let numbered = sentences
+            .iter()
+            .enumerate()
+            .map(|(i, sentence)| format!("{}. {}", i + 1, sentence))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let messages = vec![
+            ChatMessage {
+                role: "system".to_string(),
+                content: VERIFY_SYSTEM_PROMPT.to_string(),
+            },
+            ChatMessage {
+                role: "user".to_string(),
+                content: format!("参考资料:\n{context}\n\n待核查句子:\n{numbered}"),
+            },
+        ];
+
+        let output = self
+            .provider
+            .chat(messages, VERIFY_TEMPERATURE, VERIFY_MAX_TOKENS)
+            .await?;
+        parse_verdicts(&output, sentences.len())
+    }
+}
+
+fn build_report(
+    mode: GroundingMode,
+    checked_sentences: usize,
+    unsupported: Vec<UnsupportedSentence>,
+) -> GroundingReport {
+    let score = if checked_sentences == 0 {
+        1.0
+    } else {
+        1.0 - unsupported.len() as f32 / checked_sentences as f32
+    };
+
+    GroundingReport {
+        mode: mode.as_str().to_string(),
+        score,
+        checked_sentences,
+        unsupported,
+    }
+}
+
+/// 将答案切分为待校验的句子：去除引用标记与列表符号，
+/// 跳过过短的句子、小标题以及仅列出来源标题的行
+pub fn split_sentences(answer: &str, chunks: &[RetrievedChunk]) -> Vec<String> {
+    let mut sentences = Vec::new();
+    let mut current = String::new();
+
+    for c in answer.chars() {
+        current.push(c);
+        if matches!(c, '。' | '！' | '？' | '；' | '!' | '?' | ';' | '\n') {
+            sentences.push(std::mem::take(&mut current));
+        }
+    }
+    sentences.push(current);
+
+    sentences
+        .into_iter()
+        .map(|sentence| {
+            strip_markers(&sentence)
+                .trim_start_matches(['-', '*', '#', ' ', '>'])
+                .trim()
+                .to_string()
+        })
+        .filter(|sentence| sentence.chars().count() >= MIN_SENTENCE_CHARS)
+        .filter(|sentence| !sentence.ends_with('：') && !sentence.ends_with(':'))
+        .filter(|sentence| {
+            let bare = sentence.trim_matches(['[', ']', ' ']);
+            !chunks.iter().any(|chunk| chunk.metadata.title_path == bare)
+        })
+        .collect()
+}
+
+/// 解析 "编号: 是/否" 格式的判定结果；缺失的编号视为无依据
+fn parse_verdicts(output: &str, count: usize) -> GroundingResult<Vec<bool>> {
+    let mut verdicts = vec![false; count];
+    let mut parsed = 0;
+
+    for line in output.lines() {
+        let Some((number, verdict)) = line.split_once([':', '：']) else {
+            continue;
+        };
+        let Ok(number) = number.trim().trim_end_matches('.').parse::<usize>() else {
+            continue;
+        };
+        if number == 0 || number > count {
+            continue;
+        }
+
+        let verdict = verdict.trim();
+        if verdict.starts_with('是') || verdict.eq_ignore_ascii_case("yes") {
+            verdicts[number - 1] = true;
+            parsed += 1;
+        } else if verdict.starts_with('否') || verdict.eq_ignore_ascii_case("no") {
+            parsed += 1;
+        }
+    }
+
+    if parsed == 0 {
+        return Err(GroundingError::InvalidVerdict(
+            output.chars().take(200).collect(),
+        ));
+    }
+
+    Ok(verdicts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rag::test_support::retrieved_chunk;
+
+    #[test]
+    fn splits_answer_into_checkable_sentences() {
+        let answer = "根据参考资料，问题的答案是：\n先检查出价配置是否生效[来源1]。然后回滚策略。\n\n相关参考：\n- [排查手册]\n好的。";
+        let sentences = split_sentences(answer, &[retrieved_chunk("d", "d#0", "排查手册")]);
+
+        assert_eq!(
+            sentences,
+            vec!["先检查出价配置是否生效。", "然后回滚策略。"]
+        );
+    }
+
+    #[test]
+    fn parses_llm_verdicts() {
+        let verdicts = parse_verdicts("1: 是\n2：否\n3. : 是\n9: 是", 3).unwrap();
+        assert_eq!(verdicts, vec![true, false, true]);
+
+        assert!(parse_verdicts("无法判断", 2).is_err());
+    }
+
+    #[test]
+    fn score_is_ratio_of_supported_sentences() {
+        let unsupported = vec![UnsupportedSentence {
+            text: "x".to_string(),
+            similarity: Some(0.1),
+        }];
+        let report = build_report(GroundingMode::Embedding, 4, unsupported);
+        assert_eq!(report.score, 0.75);
+        assert_eq!(report.mode, "embedding");
+
+        assert_eq!(build_report(GroundingMode::Llm, 0, vec![]).score, 1.0);
+    }
+}
@@ -1,12 +1,23 @@
-use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, sync::Arc};
 
-use crate::vector_store::{VectorStore, VectorStoreError};
+pub mod citation;
+pub mod grounding;
+pub mod rewrite;
+#[cfg(test)]
+pub(crate) mod test_support;
+
+use crate::vector_store::{ChunkFilter, VectorStore, VectorStoreError};
 
 const DEFAULT_TOP_K: u64 = 6;
 
+/// 设置了 `max_per_doc` 时向向量库多取的倍数，以便去重后仍能凑满 top_k
+const PER_DOC_OVERFETCH: u64 = 3;
+
 #[derive(Debug, Clone)]
 pub struct ChunkMetadata {
     pub doc_id: String,
+    pub chunk_id: String,
     pub path: String,
     pub title_path: String,
     pub section: String,
@@ -19,6 +30,37 @@ pub struct RetrievedChunk {
     pub score: f32,
 }
 
+/// 检索方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetrievalMode {
+    /// 仅用原始问题做向量检索
+    Vector,
+    /// 查询改写后多路检索并融合
+    MultiQuery,
+}
+
+impl RetrievalMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RetrievalMode::Vector => "vector",
+            RetrievalMode::MultiQuery => "multi_query",
+        }
+    }
+}
+
+/// 单次检索参数，未设置的项使用全局配置
+#[derive(Debug, Clone, Default)]
+pub struct RetrievalOptions {
+    pub top_k: Option<u64>,
+    /// 覆盖 `VECTOR_SCORE_THRESHOLD`
+    pub score_threshold: Option<f32>,
+    /// 同一文档最多返回的片段数
+    pub max_per_doc: Option<usize>,
+    /// 只在符合条件的文档中检索
+    pub filter: ChunkFilter,
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum RetrieverError {
     #[error("Vector store error: {0}")]
@@ -47,10 +89,28 @@ impl VectorRetriever {
     pub async fn retrieve(
         &self,
         query_vector: Vec<f32>,
-        top_k: Option<u64>,
+        options: &RetrievalOptions,
     ) -> RetrieverResult<Vec<RetrievedChunk>> {
-        let top_k = top_k.unwrap_or(DEFAULT_TOP_K);
-        let hits = self.store.search(query_vector, top_k).await?;
+        let top_k = options.top_k.unwrap_or(DEFAULT_TOP_K);
+        let score_threshold = options.score_threshold.unwrap_or(self.score_threshold);
+        let fetch = match options.max_per_doc {
+            Some(_) => top_k * PER_DOC_OVERFETCH,
+            None => top_k,
+        };
+        // 索引仍是旧维度（如迁移尚未完成）时直接报错，不返回无意义的相似度
+        let stored = self.store.stored_spec().await?;
+        if stored.vector_size != query_vector.len() {
+            return Err(VectorStoreError::DimensionMismatch {
+                stored: stored.vector_size,
+                expected: query_vector.len(),
+            }
+            .into());
+        }
+
+        let hits = self
+            .store
+            .search_filtered(query_vector, fetch, &options.filter)
+            .await?;
 
         if hits.is_empty() {
             return Ok(vec![]);
@@ -58,10 +118,11 @@ impl VectorRetriever {
 
         let chunks: Vec<RetrievedChunk> = hits
             .into_iter()
-            .filter(|hit| hit.score >= self.score_threshold)
+            .filter(|hit| hit.score >= score_threshold)
             .map(|hit| RetrievedChunk {
                 metadata: ChunkMetadata {
                     doc_id: hit.doc_id,
+                    chunk_id: hit.chunk_id,
                     path: hit.path,
                     title_path: hit.title_path,
                     section: hit.section,
@@ -75,6 +136,12 @@ impl VectorRetriever {
             return Err(RetrieverError::NoResultsAboveThreshold);
         }
 
+        let mut chunks = match options.max_per_doc {
+            Some(max_per_doc) => limit_per_doc(chunks, max_per_doc),
+            None => chunks,
+        };
+        chunks.truncate(top_k as usize);
+
         Ok(chunks)
     }
 
@@ -84,9 +151,66 @@ impl VectorRetriever {
     }
 }
 
+/// 按原有顺序保留每个文档的前 `max_per_doc` 个片段
+pub fn limit_per_doc(chunks: Vec<RetrievedChunk>, max_per_doc: usize) -> Vec<RetrievedChunk> {
+    let mut per_doc: HashMap<String, usize> = HashMap::new();
+    chunks
+        .into_iter()
+        .filter(|chunk| {
+            let count = per_doc.entry(chunk.metadata.doc_id.clone()).or_insert(0);
+            *count += 1;
+            *count <= max_per_doc
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{
+        config::DistanceMetric,
+        rag::test_support::retrieved_chunk,
+        vector_store::{IndexSpec, memory_store::MemoryStore},
+    };
+
+    #[tokio::test]
+    async fn rejects_queries_with_a_different_dimension_than_the_index() {
+        let spec = IndexSpec {
+            embed_model: "m".to_string(),
+            vector_size: 2,
+            metric: DistanceMetric::Cosine,
+        };
+        let store = Arc::new(MemoryStore::new(spec, None).unwrap());
+        let retriever = VectorRetriever::new(store, 0.0);
+
+        let result = retriever
+            .retrieve(vec![0.0, 0.6, 0.8], &RetrievalOptions::default())
+            .await;
+        assert!(matches!(
+            result,
+            Err(RetrieverError::VectorStoreError(
+                VectorStoreError::DimensionMismatch {
+                    stored: 2,
+                    expected: 3
+                }
+            ))
+        ));
+    }
+
+    #[test]
+    fn limit_per_doc_keeps_best_chunks_of_each_document() {
+        let chunks = vec![
+            retrieved_chunk("a", "a#0", "a"),
+            retrieved_chunk("a", "a#1", "a"),
+            retrieved_chunk("b", "b#0", "b"),
+            retrieved_chunk("a", "a#2", "a"),
+        ];
+        let ids: Vec<String> = limit_per_doc(chunks, 1)
+            .into_iter()
+            .map(|c| c.metadata.chunk_id)
+            .collect();
+        assert_eq!(ids, vec!["a#0", "b#0"]);
+    }
 
     #[test]
     fn test_default_top_k_constant() {
@@ -0,0 +1,265 @@
+use serde::Serialize;
+use std::{collections::HashMap, sync::Arc};
+
+use crate::{
+    config::{QueryRewriteConfig, RewriteMode},
+    provider::{ChatMessage, InferenceProvider, ProviderResult},
+    rag::RetrievedChunk,
+};
+
+const REWRITE_TEMPERATURE: f32 = 0.3;
+const REWRITE_MAX_TOKENS: u32 = 512;
+const HYDE_MAX_TOKENS: u32 = 1024;
+
+const PARAPHRASE_SYSTEM_PROMPT: &str = r#"你是广告引擎运维知识库的检索助手。
+将用户的口语化问题改写为更规范、便于检索的表述，补全隐含的技术术语。
+
+每行输出一个改写后的问题，不要编号，不要输出其他内容。"#;
+
+const HYDE_SYSTEM_PROMPT: &str = r#"你是广告引擎维优专家。
+请针对用户的问题，写一段可能出现在内部排查手册中的简短回答（100 字以内），
+用于辅助检索，不需要保证完全准确。只输出回答正文。"#;
+
+/// 查询的来源类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryKind {
+    /// 用户原始问题
+    Original,
+    /// 改写后的问题
+    Paraphrase,
+    /// HyDE 假设性答案
+    Hyde,
+}
+
+/// 用于检索的一条查询
+#[derive(Debug, Clone, Serialize)]
+pub struct RewrittenQuery {
+    pub kind: QueryKind,
+    pub text: String,
+}
+
+/// 检索前的查询改写：生成若干改写问题和/或 HyDE 假设性答案
+pub struct QueryRewriter {
+    config: QueryRewriteConfig,
+    provider: Arc<dyn InferenceProvider>,
+}
+
+impl QueryRewriter {
+    pub fn new(config: &QueryRewriteConfig, provider: Arc<dyn InferenceProvider>) -> Self {
+        Self {
+            config: config.clone(),
+            provider,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    pub fn rrf_k(&self) -> f32 {
+        self.config.rrf_k
+    }
+
+    /// 返回改写后的查询（不含原始问题）；未启用改写时不调用模型
+    pub async fn rewrite(&self, question: &str) -> ProviderResult<Vec<RewrittenQuery>> {
+        let mut queries = Vec::new();
+        if !self.config.enabled {
+            return Ok(queries);
+        }
+
+        if matches!(
+            self.config.mode,
+            RewriteMode::Paraphrase | RewriteMode::Both
+        ) && self.config.num_rewrites > 0
+        {
+            let output = self
+                .ask(
+                    PARAPHRASE_SYSTEM_PROMPT,
+                    format!(
+                        "请给出 {} 种改写。\n问题: {}",
+                        self.config.num_rewrites, question
+                    ),
+                    REWRITE_MAX_TOKENS,
+                )
+                .await?;
+            queries.extend(
+                parse_rewrites(&output, question, self.config.num_rewrites)
+                    .into_iter()
+                    .map(|text| RewrittenQuery {
+                        kind: QueryKind::Paraphrase,
+                        text,
+                    }),
+            );
+        }
+
+        if matches!(self.config.mode, RewriteMode::Hyde | RewriteMode::Both) {
+            let output = self
+                .ask(
+                    HYDE_SYSTEM_PROMPT,
+                    format!("问题: {question}"),
+                    HYDE_MAX_TOKENS,
+                )
+                .await?;
+            let text = output.trim();
+            if !text.is_empty() {
+                queries.push(RewrittenQuery {
+                    kind: QueryKind::Hyde,
+                    text: text.to_string(),
+                });
+            }
+        }
+
+        Ok(queries)
+    }
+
+    async fn ask(&self, system: &str, user: String, max_tokens: u32) -> ProviderResult<String> {
+        let messages = vec![
+            ChatMessage {
+                role: "system".to_string(),
+                content: system.to_string(),
+            },
+            ChatMessage {
+                role: "user".to_string(),
+                content: user,
+            },
+        ];
+        self.provider
+            .chat(messages, REWRITE_TEMPERATURE, max_tokens)
+            .await
+    }
+}
+
+/// 解析改写结果：每行一个问题，去除编号与列表符号，去重并排除与原问题相同的行
+fn parse_rewrites(output: &str, question: &str, limit: usize) -> Vec<String> {
+    let mut rewrites: Vec<String> = Vec::new();
+
+    for line in output.lines() {
+        let text = strip_list_marker(line.trim()).trim();
+        if text.is_empty() || text == question.trim() || rewrites.iter().any(|r| r == text) {
+            continue;
+        }
+        rewrites.push(text.to_string());
+        if rewrites.len() >= limit {
+            break;
+        }
+    }
+
+    rewrites
+}
+
+/// 去除行首的列表标记：`-`、`*` 或 `1.`、`2、`、`3)` 这类编号；
+/// 问题本身以数字开头（如 `2024年Q3`、`3.5 倍`）时保持原样
+fn strip_list_marker(line: &str) -> &str {
+    if let Some(rest) = line.strip_prefix(['-', '*']) {
+        return rest;
+    }
+
+    let rest = line.trim_start_matches(|c: char| c.is_ascii_digit());
+    if rest.len() == line.len() {
+        return line;
+    }
+    match rest.strip_prefix(['.', '、', ')', '）']) {
+        // `3.5` 是小数而不是编号
+        Some(text)
+            if !(rest.starts_with('.') && text.starts_with(|c: char| c.is_ascii_digit())) =>
+        {
+            text
+        }
+        _ => line,
+    }
+}
+
+/// 倒数排名融合（RRF）：按 chunk_id 合并多路检索结果，保留每个片段的最高相似度
+pub fn fuse_results(
+    result_lists: Vec<Vec<RetrievedChunk>>,
+    rrf_k: f32,
+    top_k: usize,
+) -> Vec<RetrievedChunk> {
+    let mut fused: HashMap<String, (f32, RetrievedChunk)> = HashMap::new();
+
+    for list in result_lists {
+        for (rank, chunk) in list.into_iter().enumerate() {
+            let contribution = 1.0 / (rrf_k + rank as f32 + 1.0);
+            fused
+                .entry(chunk.metadata.chunk_id.clone())
+                .and_modify(|(score, existing)| {
+                    *score += contribution;
+                    if chunk.score > existing.score {
+                        existing.score = chunk.score;
+                    }
+                })
+                .or_insert((contribution, chunk));
+        }
+    }
+
+    let mut ranked: Vec<(f32, RetrievedChunk)> = fused.into_values().collect();
+    ranked.sort_by(|a, b| {
+        b.0.total_cmp(&a.0)
+            .then_with(|| b.1.score.total_cmp(&a.1.score))
+    });
+    ranked
+        .into_iter()
+        .take(top_k)
+        .map(|(_, chunk)| chunk)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rag::test_support::retrieved_chunk;
+
+    fn scored(id: &str, score: f32) -> RetrievedChunk {
+        RetrievedChunk {
+            score,
+            ..retrieved_chunk("d", id, id)
+        }
+    }
+
+    #[test]
+    fn parses_numbered_rewrites() {
+        let output = "1. CPM 突然升高如何排查\n2、CPM 异常上涨的原因\n\n- CPM 暴涨咋办\n3) CPM 异常上涨的原因\n4. 千次展示成本飙升处理";
+        let rewrites = parse_rewrites(output, "CPM 暴涨咋办", 3);
+
+        assert_eq!(
+            rewrites,
+            vec![
+                "CPM 突然升高如何排查",
+                "CPM 异常上涨的原因",
+                "千次展示成本飙升处理"
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_leading_numbers_that_are_not_list_markers() {
+        let output = "1. 2024年Q3 CPM 下降原因\n2024年Q3 CPM 下降怎么排查\n2) 3.5 倍出价是否过高\n3.5 倍出价是否过高";
+        let rewrites = parse_rewrites(output, "Q3 CPM 下降", 5);
+
+        assert_eq!(
+            rewrites,
+            vec![
+                "2024年Q3 CPM 下降原因",
+                "2024年Q3 CPM 下降怎么排查",
+                "3.5 倍出价是否过高"
+            ]
+        );
+    }
+
+    #[test]
+    fn fuses_results_by_reciprocal_rank() {
+        let fused = fuse_results(
+            vec![
+                vec![scored("a", 0.8), scored("b", 0.7)],
+                vec![scored("b", 0.9), scored("c", 0.6)],
+            ],
+            60.0,
+            2,
+        );
+
+        let ids: Vec<&str> = fused.iter().map(|c| c.metadata.chunk_id.as_str()).collect();
+        assert_eq!(ids, vec!["b", "a"]);
+        assert_eq!(fused[0].score, 0.9);
+    }
+}
@@ -0,0 +1,16 @@
+use crate::rag::{ChunkMetadata, RetrievedChunk};
+
+/// 位于 `<doc_id>.md` 的检索结果，其余字段按需用结构体更新语法覆盖
+pub(crate) fn retrieved_chunk(doc_id: &str, chunk_id: &str, title_path: &str) -> RetrievedChunk {
+    RetrievedChunk {
+        metadata: ChunkMetadata {
+            doc_id: doc_id.to_string(),
+            chunk_id: chunk_id.to_string(),
+            path: format!("{doc_id}.md"),
+            title_path: title_path.to_string(),
+            section: String::new(),
+        },
+        snippet: "内容".to_string(),
+        score: 0.9,
+    }
+}
@@ -1,4 +1,7 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, RwLock},
+};
 
 use arrow_array::{
     Array, ArrayRef, FixedSizeListArray, Float32Array, Float64Array, RecordBatch,
@@ -9,36 +12,173 @@ use futures::TryStreamExt;
 use lancedb::{
     DistanceType, Table,
     connection::Connection,
-    index::Index,
-    query::{ExecutableQuery, QueryBase},
+    database::CreateTableMode,
+    index::{
+        Index,
+        vector::{IvfHnswPqIndexBuilder, IvfHnswSqIndexBuilder, IvfPqIndexBuilder},
+    },
+    query::{ExecutableQuery, QueryBase, Select},
+    table::{CompactionOptions, NewColumnTransform, OptimizeAction, OptimizeOptions},
 };
 
-use crate::vector_store::{SearchHit, StoredChunk, VectorStore, VectorStoreResult};
+use crate::{
+    config::{DistanceMetric, VectorIndexConfig, VectorIndexType},
+    vector_store::{
+        ChunkFilter, ChunkRecord, CompactionStats, DocumentSummary, IndexSpec, IndexVersion,
+        MaintenanceOptions, MaintenanceReport, PruneStats, SearchHit, StoredChunk,
+        VectorIndexStats, VectorStore, VectorStoreError, VectorStoreResult, chunk_ordinal,
+        distance_to_score,
+    },
+};
 
 const DISTANCE_COLUMN: &str = "_distance";
 const VECTOR_COLUMN: &str = "vector";
 
+/// 后续版本新增的列，旧表在启动时以空字符串补齐
+const ADDED_COLUMNS: &[&str] = &["doc_hash", "indexed_at"];
+
+/// 记录当前版本的指针表后缀：`<table>__active`
+const POINTER_SUFFIX: &str = "__active";
+/// 版本表名：`<table>__v<创建时间>`
+const VERSION_MARKER: &str = "__v";
+const VERSION_TIME_FORMAT: &str = "%Y%m%dT%H%M%S%3f";
+
+/// 表 schema metadata 中记录生成参数的键
+const META_EMBED_MODEL: &str = "engineqa:embed_model";
+const META_VECTOR_SIZE: &str = "engineqa:vector_size";
+const META_DISTANCE: &str = "engineqa:distance";
+
+/// LanceDB 向量存储
+///
+/// 查询与写入作用于“当前版本”表：未做过蓝绿构建时即 `LANCEDB_TABLE` 本身，
+/// 之后由指针表记录，切换版本只需覆盖写入指针表这一次提交。
 pub struct LanceDbStore {
     connection: Connection,
-    table_name: String,
-    vector_size: usize,
+    base_table: String,
+    active_table: Arc<RwLock<String>>,
+    spec: IndexSpec,
+    /// 当前版本记录的生成参数，启动与切换版本时读取，查询时直接比对
+    stored_spec: RwLock<IndexSpec>,
+    index: VectorIndexConfig,
 }
 
 impl LanceDbStore {
-    pub async fn new(uri: &str, table_name: &str, vector_size: usize) -> VectorStoreResult<Self> {
+    pub async fn new(
+        uri: &str,
+        table_name: &str,
+        spec: IndexSpec,
+        index: VectorIndexConfig,
+    ) -> VectorStoreResult<Self> {
         let connection = lancedb::connect(uri).execute().await?;
-        let store = Self {
+        let mut store = Self {
             connection,
-            table_name: table_name.to_string(),
-            vector_size,
+            base_table: table_name.to_string(),
+            active_table: Arc::new(RwLock::new(table_name.to_string())),
+            stored_spec: RwLock::new(spec.clone()),
+            spec,
+            index,
         };
+        if let Some(active) = store.read_pointer().await? {
+            tracing::info!(table = %active, "using active index version");
+            store.active_table = Arc::new(RwLock::new(active));
+        }
         store.ensure_ready().await?;
+        let stored = Self::read_spec(&store.open_table().await?).await?;
+        store.set_stored_spec(stored);
         Ok(store)
     }
 
+    /// 当前版本的表名
+    fn table_name(&self) -> String {
+        self.active_table
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    fn set_stored_spec(&self, spec: IndexSpec) {
+        *self.stored_spec.write().unwrap_or_else(|e| e.into_inner()) = spec;
+    }
+
+    fn pointer_table(&self) -> String {
+        format!("{}{POINTER_SUFFIX}", self.base_table)
+    }
+
+    fn is_version_table(&self, name: &str) -> bool {
+        name == self.base_table
+            || name
+                .strip_prefix(&self.base_table)
+                .is_some_and(|rest| rest.starts_with(VERSION_MARKER))
+    }
+
+    /// 从版本表名解析创建时间
+    fn version_created_at(&self, name: &str) -> Option<String> {
+        let stamp = name
+            .strip_prefix(&self.base_table)?
+            .strip_prefix(VERSION_MARKER)?;
+        chrono::NaiveDateTime::parse_from_str(stamp, VERSION_TIME_FORMAT)
+            .ok()
+            .map(|time| time.and_utc().to_rfc3339())
+    }
+
+    fn pointer_schema() -> Arc<Schema> {
+        Arc::new(Schema::new(vec![Field::new(
+            "table_name",
+            DataType::Utf8,
+            false,
+        )]))
+    }
+
+    async fn read_pointer(&self) -> VectorStoreResult<Option<String>> {
+        let pointer = self.pointer_table();
+        let names = self.connection.table_names().execute().await?;
+        if !names.contains(&pointer) {
+            return Ok(None);
+        }
+
+        let table = self.connection.open_table(&pointer).execute().await?;
+        let batches: Vec<RecordBatch> = table.query().execute().await?.try_collect().await?;
+        for batch in &batches {
+            if batch.num_rows() > 0 {
+                let name = self.column_as_string(batch, "table_name")?.value(0);
+                if names.iter().any(|n| n == name) {
+                    return Ok(Some(name.to_string()));
+                }
+                tracing::warn!(table = %name, "active index version missing, falling back");
+            }
+        }
+        Ok(None)
+    }
+
+    async fn write_pointer(&self, table_name: &str) -> VectorStoreResult<()> {
+        let schema = Self::pointer_schema();
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(StringArray::from(vec![table_name])) as ArrayRef],
+        )?;
+        let reader = RecordBatchIterator::new(vec![Ok(batch)].into_iter(), schema);
+        self.connection
+            .create_table(self.pointer_table(), Box::new(reader))
+            .mode(CreateTableMode::Overwrite)
+            .execute()
+            .await?;
+        Ok(())
+    }
+
+    fn spec_metadata(&self) -> HashMap<String, String> {
+        HashMap::from([
+            (META_EMBED_MODEL.to_string(), self.spec.embed_model.clone()),
+            (
+                META_VECTOR_SIZE.to_string(),
+                self.spec.vector_size.to_string(),
+            ),
+            (META_DISTANCE.to_string(), self.spec.metric.to_string()),
+        ])
+    }
+
     fn schema(&self) -> Arc<Schema> {
         let vector_field = Field::new("item", DataType::Float32, false);
-        Arc::new(Schema::new(vec![
+        let schema = Schema::new(vec![
             Field::new("point_id", DataType::Utf8, false),
             Field::new("doc_id", DataType::Utf8, false),
             Field::new("chunk_id", DataType::Utf8, false),
@@ -47,32 +187,118 @@ impl LanceDbStore {
             Field::new("section", DataType::Utf8, false),
             Field::new("text", DataType::Utf8, false),
             Field::new("hash", DataType::Utf8, false),
+            Field::new("doc_hash", DataType::Utf8, true),
+            Field::new("indexed_at", DataType::Utf8, true),
             Field::new(
                 "vector",
-                DataType::FixedSizeList(Arc::new(vector_field), self.vector_size as i32),
+                DataType::FixedSizeList(Arc::new(vector_field), self.spec.vector_size as i32),
                 false,
             ),
-        ]))
+        ]);
+        Arc::new(schema.with_metadata(self.spec_metadata()))
+    }
+
+    /// 读取表记录的生成参数；旧表未记录的项返回空模型名、实际向量维度与 cosine
+    async fn read_spec(table: &Table) -> VectorStoreResult<IndexSpec> {
+        let schema = table.schema().await?;
+        let metadata = schema.metadata();
+        let vector_size = match schema.field_with_name(VECTOR_COLUMN)?.data_type() {
+            DataType::FixedSizeList(_, size) => *size as usize,
+            other => {
+                return Err(VectorStoreError::InvalidPayload(format!(
+                    "unexpected vector column type: {other}"
+                )));
+            }
+        };
+
+        let metric = match metadata.get(META_DISTANCE) {
+            Some(name) => DistanceMetric::from_name(name).ok_or_else(|| {
+                VectorStoreError::InvalidPayload(format!("unknown distance metric: {name}"))
+            })?,
+            None => DistanceMetric::Cosine,
+        };
+
+        Ok(IndexSpec {
+            embed_model: metadata.get(META_EMBED_MODEL).cloned().unwrap_or_default(),
+            vector_size,
+            metric,
+        })
+    }
+
+    /// 旧表缺少部分记录：其余参数与当前一致时视为当前参数生成并补记，否则保持原样由启动检查处理
+    async fn stamp_legacy_spec(&self, table: &Table) -> VectorStoreResult<()> {
+        let metadata = table.schema().await?.metadata().clone();
+        if [META_EMBED_MODEL, META_DISTANCE]
+            .iter()
+            .all(|key| metadata.contains_key(*key))
+        {
+            return Ok(());
+        }
+
+        let stored = Self::read_spec(table).await?;
+        let same_model =
+            stored.embed_model.is_empty() || stored.embed_model == self.spec.embed_model;
+        if !same_model
+            || stored.vector_size != self.spec.vector_size
+            || stored.metric != self.spec.metric
+        {
+            return Ok(());
+        }
+        let Some(native) = table.as_native() else {
+            return Ok(());
+        };
+
+        native.replace_schema_metadata(self.spec_metadata()).await?;
+        tracing::info!(
+            table = %self.table_name(),
+            spec = %self.spec,
+            "recorded embedding spec for existing index"
+        );
+        Ok(())
     }
 
     async fn create_table_if_absent(&self) -> VectorStoreResult<()> {
+        let table_name = self.table_name();
         let names = self.connection.table_names().execute().await?;
-        if names.iter().any(|name| name == &self.table_name) {
+        if names.iter().any(|name| name == &table_name) {
             return Ok(());
         }
 
         self.connection
-            .create_empty_table(&self.table_name, self.schema())
+            .create_empty_table(&table_name, self.schema())
             .execute()
             .await?;
 
         Ok(())
     }
 
+    async fn add_missing_columns(&self, table: &Table) -> VectorStoreResult<()> {
+        let schema = table.schema().await?;
+        let missing: Vec<(String, String)> = ADDED_COLUMNS
+            .iter()
+            .filter(|name| schema.field_with_name(name).is_err())
+            .map(|name| (name.to_string(), "''".to_string()))
+            .collect();
+
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        tracing::info!(
+            table = %self.table_name(),
+            columns = ?missing.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>(),
+            "adding missing columns to vector table"
+        );
+        table
+            .add_columns(NewColumnTransform::SqlExpressions(missing), None)
+            .await?;
+        Ok(())
+    }
+
     async fn open_table(&self) -> VectorStoreResult<Table> {
         let table = self
             .connection
-            .open_table(&self.table_name)
+            .open_table(self.table_name())
             .execute()
             .await?;
         Ok(table)
@@ -86,15 +312,13 @@ impl LanceDbStore {
         let idx = batch
             .schema_ref()
             .index_of(name)
-            .map_err(|e| crate::vector_store::VectorStoreError::InvalidPayload(e.to_string()))?;
+            .map_err(|e| VectorStoreError::InvalidPayload(e.to_string()))?;
         batch
             .column(idx)
             .as_any()
             .downcast_ref::<StringArray>()
             .ok_or_else(|| {
-                crate::vector_store::VectorStoreError::InvalidPayload(format!(
-                    "column `{name}` is not StringArray"
-                ))
+                VectorStoreError::InvalidPayload(format!("column `{name}` is not StringArray"))
             })
     }
 
@@ -102,7 +326,7 @@ impl LanceDbStore {
         let idx = batch
             .schema_ref()
             .index_of(DISTANCE_COLUMN)
-            .map_err(|e| crate::vector_store::VectorStoreError::InvalidPayload(e.to_string()))?;
+            .map_err(|e| VectorStoreError::InvalidPayload(e.to_string()))?;
 
         let array = batch.column(idx);
         if let Some(values) = array.as_any().downcast_ref::<Float32Array>() {
@@ -112,19 +336,57 @@ impl LanceDbStore {
             return Ok(values.value(row) as f32);
         }
 
-        Err(crate::vector_store::VectorStoreError::InvalidPayload(
+        Err(VectorStoreError::InvalidPayload(
             "distance column type is neither Float32 nor Float64".to_string(),
         ))
     }
 
-    fn distance_to_score(distance: f32) -> f32 {
-        (1.0 - (distance / 2.0)).clamp(0.0, 1.0)
+    fn distance_type(&self) -> DistanceType {
+        match self.spec.metric {
+            DistanceMetric::Cosine => DistanceType::Cosine,
+            DistanceMetric::L2 => DistanceType::L2,
+            DistanceMetric::Dot => DistanceType::Dot,
+        }
     }
 
     fn escape_sql_literal(value: &str) -> String {
         value.replace('\'', "''")
     }
 
+    fn doc_id_predicate(doc_id: &str) -> String {
+        format!("doc_id = '{}'", Self::escape_sql_literal(doc_id))
+    }
+
+    fn filter_predicate(filter: &ChunkFilter) -> Option<String> {
+        let mut clauses = Vec::new();
+        if !filter.doc_ids.is_empty() {
+            let ids: Vec<String> = filter
+                .doc_ids
+                .iter()
+                .map(|id| format!("'{}'", Self::escape_sql_literal(id)))
+                .collect();
+            clauses.push(format!("doc_id IN ({})", ids.join(", ")));
+        }
+        if let Some(prefix) = &filter.path_prefix {
+            clauses.push(format!("path LIKE '{}%'", Self::escape_sql_literal(prefix)));
+        }
+        (!clauses.is_empty()).then(|| clauses.join(" AND "))
+    }
+
+    async fn query_columns(
+        &self,
+        columns: &[&str],
+        filter: Option<String>,
+    ) -> VectorStoreResult<Vec<RecordBatch>> {
+        let table = self.open_table().await?;
+        let mut query = table.query().select(Select::columns(columns));
+        if let Some(filter) = filter {
+            query = query.only_if(filter);
+        }
+        let stream = query.execute().await?;
+        Ok(stream.try_collect().await?)
+    }
+
     async fn try_ensure_vector_index(&self, table: &Table) -> VectorStoreResult<()> {
         let row_count = table.count_rows(None).await?;
         if row_count == 0 {
@@ -141,12 +403,90 @@ impl LanceDbStore {
             return Ok(());
         }
 
+        self.create_vector_index(table, false).await
+    }
+
+    /// 按配置的索引类型与参数构建向量索引，距离度量与查询一致
+    /// （`Index::Auto` 固定使用 L2，因此 auto 也显式构建 IVF-PQ）
+    fn vector_index(&self) -> Index {
+        let config = &self.index;
+        let distance_type = self.distance_type();
+        match config.index_type {
+            VectorIndexType::Auto => {
+                Index::IvfPq(IvfPqIndexBuilder::default().distance_type(distance_type))
+            }
+            VectorIndexType::IvfPq => {
+                let mut builder = IvfPqIndexBuilder::default().distance_type(distance_type);
+                if let Some(n) = config.num_partitions {
+                    builder = builder.num_partitions(n);
+                }
+                if let Some(n) = config.num_sub_vectors {
+                    builder = builder.num_sub_vectors(n);
+                }
+                Index::IvfPq(builder)
+            }
+            VectorIndexType::IvfHnswPq => {
+                let mut builder = IvfHnswPqIndexBuilder::default().distance_type(distance_type);
+                if let Some(n) = config.num_partitions {
+                    builder = builder.num_partitions(n);
+                }
+                if let Some(n) = config.num_sub_vectors {
+                    builder = builder.num_sub_vectors(n);
+                }
+                if let Some(m) = config.hnsw_m {
+                    builder = builder.num_edges(m);
+                }
+                if let Some(ef) = config.hnsw_ef_construction {
+                    builder = builder.ef_construction(ef);
+                }
+                Index::IvfHnswPq(builder)
+            }
+            VectorIndexType::IvfHnswSq => {
+                let mut builder = IvfHnswSqIndexBuilder::default().distance_type(distance_type);
+                if let Some(n) = config.num_partitions {
+                    builder = builder.num_partitions(n);
+                }
+                if let Some(m) = config.hnsw_m {
+                    builder = builder.num_edges(m);
+                }
+                if let Some(ef) = config.hnsw_ef_construction {
+                    builder = builder.ef_construction(ef);
+                }
+                Index::IvfHnswSq(builder)
+            }
+        }
+    }
+
+    async fn create_vector_index(&self, table: &Table, replace: bool) -> VectorStoreResult<()> {
         table
-            .create_index(&[VECTOR_COLUMN], Index::Auto)
+            .create_index(&[VECTOR_COLUMN], self.vector_index())
+            .replace(replace)
             .execute()
             .await?;
         Ok(())
     }
+
+    async fn vector_index_stats(
+        &self,
+        table: &Table,
+    ) -> VectorStoreResult<Option<VectorIndexStats>> {
+        let Some(index) = table
+            .list_indices()
+            .await?
+            .into_iter()
+            .find(|index| index.columns.iter().any(|column| column == VECTOR_COLUMN))
+        else {
+            return Ok(None);
+        };
+
+        let stats = table.index_stats(&index.name).await?;
+        Ok(stats.map(|stats| VectorIndexStats {
+            name: index.name,
+            index_type: stats.index_type.to_string(),
+            indexed_rows: stats.num_indexed_rows,
+            unindexed_rows: stats.num_unindexed_rows,
+        }))
+    }
 }
 
 #[async_trait::async_trait]
@@ -155,9 +495,11 @@ impl VectorStore for LanceDbStore {
         self.create_table_if_absent().await?;
 
         let table = self.open_table().await?;
+        self.add_missing_columns(&table).await?;
+        self.stamp_legacy_spec(&table).await?;
         if let Err(err) = self.try_ensure_vector_index(&table).await {
             tracing::warn!(
-                table = %self.table_name,
+                table = %self.table_name(),
                 error = %err,
                 "failed to create or reuse vector index"
             );
@@ -170,35 +512,62 @@ impl VectorStore for LanceDbStore {
         &self,
         query_vector: Vec<f32>,
         top_k: u64,
+    ) -> VectorStoreResult<Vec<SearchHit>> {
+        self.search_filtered(query_vector, top_k, &ChunkFilter::default())
+            .await
+    }
+
+    /// 过滤条件作为预过滤谓词下推；LIKE 中的 `_`、`%` 会多匹配，结果再按前缀精确过滤
+    async fn search_filtered(
+        &self,
+        query_vector: Vec<f32>,
+        top_k: u64,
+        filter: &ChunkFilter,
     ) -> VectorStoreResult<Vec<SearchHit>> {
         let table = self.open_table().await?;
-        let stream = table
+        let mut query = table
             .query()
             .nearest_to(query_vector)?
-            .distance_type(DistanceType::Cosine)
-            .limit(top_k as usize)
-            .execute()
-            .await?;
+            .distance_type(self.distance_type())
+            .limit(top_k as usize);
+        if let Some(predicate) = Self::filter_predicate(filter) {
+            query = query.only_if(predicate);
+        }
+        if let Some(nprobes) = self.index.nprobes {
+            query = query.nprobes(nprobes);
+        }
+        if let Some(refine_factor) = self.index.refine_factor {
+            query = query.refine_factor(refine_factor);
+        }
+        if let Some(ef) = self.index.ef {
+            query = query.ef(ef);
+        }
+        let stream = query.execute().await?;
 
         let batches: Vec<RecordBatch> = stream.try_collect().await?;
         let mut results = Vec::new();
 
         for batch in batches {
             let doc_id = self.column_as_string(&batch, "doc_id")?;
+            let chunk_id = self.column_as_string(&batch, "chunk_id")?;
             let path = self.column_as_string(&batch, "path")?;
             let title_path = self.column_as_string(&batch, "title_path")?;
             let section = self.column_as_string(&batch, "section")?;
             let text = self.column_as_string(&batch, "text")?;
 
             for row in 0..batch.num_rows() {
+                if !filter.matches(doc_id.value(row), path.value(row)) {
+                    continue;
+                }
                 let distance = self.distance_for_row(&batch, row)?;
                 results.push(SearchHit {
                     doc_id: doc_id.value(row).to_string(),
+                    chunk_id: chunk_id.value(row).to_string(),
                     path: path.value(row).to_string(),
                     title_path: title_path.value(row).to_string(),
                     section: section.value(row).to_string(),
                     snippet: text.value(row).to_string(),
-                    score: Self::distance_to_score(distance),
+                    score: distance_to_score(self.spec.metric, distance),
                 });
             }
         }
@@ -211,16 +580,14 @@ impl VectorStore for LanceDbStore {
             return Ok(());
         }
 
-        let mut vectors = Vec::with_capacity(chunks.len() * self.vector_size);
+        let mut vectors = Vec::with_capacity(chunks.len() * self.spec.vector_size);
         for chunk in &chunks {
-            if chunk.vector.len() != self.vector_size {
-                return Err(crate::vector_store::VectorStoreError::InvalidPayload(
-                    format!(
-                        "vector size mismatch: got {}, expected {}",
-                        chunk.vector.len(),
-                        self.vector_size
-                    ),
-                ));
+            if chunk.vector.len() != self.spec.vector_size {
+                return Err(VectorStoreError::InvalidPayload(format!(
+                    "vector size mismatch: got {}, expected {}",
+                    chunk.vector.len(),
+                    self.spec.vector_size
+                )));
             }
             vectors.extend_from_slice(&chunk.vector);
         }
@@ -275,11 +642,23 @@ impl VectorStore for LanceDbStore {
                 .map(|chunk| chunk.hash.as_str())
                 .collect::<Vec<_>>(),
         )) as ArrayRef;
+        let doc_hash = Arc::new(StringArray::from(
+            chunks
+                .iter()
+                .map(|chunk| chunk.doc_hash.as_str())
+                .collect::<Vec<_>>(),
+        )) as ArrayRef;
+        let indexed_at = Arc::new(StringArray::from(
+            chunks
+                .iter()
+                .map(|chunk| chunk.indexed_at.as_str())
+                .collect::<Vec<_>>(),
+        )) as ArrayRef;
 
         let vector_values = Arc::new(Float32Array::from(vectors)) as ArrayRef;
         let vector = Arc::new(FixedSizeListArray::new(
             Arc::new(Field::new("item", DataType::Float32, false)),
-            self.vector_size as i32,
+            self.spec.vector_size as i32,
             vector_values,
             None,
         )) as ArrayRef;
@@ -287,16 +666,21 @@ impl VectorStore for LanceDbStore {
         let batch = RecordBatch::try_new(
             schema.clone(),
             vec![
-                point_id, doc_id, chunk_id, path, title_path, section, text, hash, vector,
+                point_id, doc_id, chunk_id, path, title_path, section, text, hash, doc_hash,
+                indexed_at, vector,
             ],
         )?;
         let reader = RecordBatchIterator::new(vec![Ok(batch)].into_iter(), schema);
         let table = self.open_table().await?;
 
-        table.add(Box::new(reader)).execute().await?;
+        let mut merge = table.merge_insert(&["point_id"]);
+        merge
+            .when_matched_update_all(None)
+            .when_not_matched_insert_all();
+        merge.execute(Box::new(reader)).await?;
         if let Err(err) = self.try_ensure_vector_index(&table).await {
             tracing::warn!(
-                table = %self.table_name,
+                table = %self.table_name(),
                 error = %err,
                 "failed to create or reuse vector index"
             );
@@ -304,48 +688,551 @@ impl VectorStore for LanceDbStore {
         Ok(())
     }
 
-    async fn delete_by_doc_id(&self, doc_id: &str) -> VectorStoreResult<()> {
+    async fn delete_by_doc_id(&self, doc_id: &str) -> VectorStoreResult<usize> {
         let table = self.open_table().await?;
-        let escaped = Self::escape_sql_literal(doc_id);
-        let predicate = format!("doc_id = '{escaped}'");
-        table.delete(&predicate).await?;
-        Ok(())
+        let predicate = Self::doc_id_predicate(doc_id);
+        let existing = table.count_rows(Some(predicate.clone())).await?;
+        if existing > 0 {
+            table.delete(&predicate).await?;
+        }
+        Ok(existing)
     }
 
-    async fn list_doc_hashes(&self) -> VectorStoreResult<HashMap<String, String>> {
+    async fn delete_stale_chunks(
+        &self,
+        doc_id: &str,
+        keep_point_ids: &[String],
+    ) -> VectorStoreResult<usize> {
+        let mut predicate = Self::doc_id_predicate(doc_id);
+        if !keep_point_ids.is_empty() {
+            let keep = keep_point_ids
+                .iter()
+                .map(|id| format!("'{}'", Self::escape_sql_literal(id)))
+                .collect::<Vec<_>>()
+                .join(", ");
+            predicate = format!("{predicate} AND point_id NOT IN ({keep})");
+        }
+
         let table = self.open_table().await?;
-        let stream = table.query().execute().await?;
-        let batches: Vec<RecordBatch> = stream.try_collect().await?;
+        let stale = table.count_rows(Some(predicate.clone())).await?;
+        if stale > 0 {
+            table.delete(&predicate).await?;
+        }
+        Ok(stale)
+    }
+
+    async fn chunk_vectors(&self, doc_id: &str) -> VectorStoreResult<HashMap<String, Vec<f32>>> {
+        let batches = self
+            .query_columns(
+                &["hash", VECTOR_COLUMN],
+                Some(Self::doc_id_predicate(doc_id)),
+            )
+            .await?;
+        let mut vectors = HashMap::new();
+
+        for batch in batches {
+            let hash = self.column_as_string(&batch, "hash")?;
+            let idx = batch
+                .schema_ref()
+                .index_of(VECTOR_COLUMN)
+                .map_err(|e| VectorStoreError::InvalidPayload(e.to_string()))?;
+            let lists = batch
+                .column(idx)
+                .as_any()
+                .downcast_ref::<FixedSizeListArray>()
+                .ok_or_else(|| {
+                    VectorStoreError::InvalidPayload(
+                        "vector column is not FixedSizeListArray".to_string(),
+                    )
+                })?;
+
+            for row in 0..batch.num_rows() {
+                let values = lists.value(row);
+                let values = values
+                    .as_any()
+                    .downcast_ref::<Float32Array>()
+                    .ok_or_else(|| {
+                        VectorStoreError::InvalidPayload(
+                            "vector values are not Float32".to_string(),
+                        )
+                    })?;
+                vectors.insert(hash.value(row).to_string(), values.values().to_vec());
+            }
+        }
+
+        Ok(vectors)
+    }
+
+    async fn list_doc_hashes(&self) -> VectorStoreResult<HashMap<String, String>> {
+        let batches = self.query_columns(&["doc_id", "doc_hash"], None).await?;
         let mut map = HashMap::new();
 
         for batch in batches {
             let doc_id = self.column_as_string(&batch, "doc_id")?;
-            let hash = self.column_as_string(&batch, "hash")?;
+            let doc_hash = self.column_as_string(&batch, "doc_hash")?;
             for row in 0..batch.num_rows() {
-                map.insert(doc_id.value(row).to_string(), hash.value(row).to_string());
+                // 旧数据没有文档哈希，视为需要重新索引
+                let hash = if doc_hash.is_null(row) {
+                    ""
+                } else {
+                    doc_hash.value(row)
+                };
+                map.insert(doc_id.value(row).to_string(), hash.to_string());
             }
         }
 
         Ok(map)
     }
 
+    async fn list_documents(&self) -> VectorStoreResult<Vec<DocumentSummary>> {
+        let batches = self
+            .query_columns(
+                &["doc_id", "path", "title_path", "doc_hash", "indexed_at"],
+                None,
+            )
+            .await?;
+        let mut documents: BTreeMap<String, DocumentSummary> = BTreeMap::new();
+
+        for batch in batches {
+            let doc_id = self.column_as_string(&batch, "doc_id")?;
+            let path = self.column_as_string(&batch, "path")?;
+            let title_path = self.column_as_string(&batch, "title_path")?;
+            let doc_hash = self.column_as_string(&batch, "doc_hash")?;
+            let indexed_at = self.column_as_string(&batch, "indexed_at")?;
+
+            for row in 0..batch.num_rows() {
+                let optional = |array: &StringArray| {
+                    Some(array)
+                        .filter(|array| !array.is_null(row))
+                        .map(|array| array.value(row).to_string())
+                        .filter(|value| !value.is_empty())
+                };
+
+                let entry = documents
+                    .entry(doc_id.value(row).to_string())
+                    .or_insert_with(|| DocumentSummary {
+                        doc_id: doc_id.value(row).to_string(),
+                        path: path.value(row).to_string(),
+                        title: String::new(),
+                        chunk_count: 0,
+                        hash: String::new(),
+                        indexed_at: None,
+                    });
+                entry.chunk_count += 1;
+
+                let title = title_path.value(row);
+                if entry.title.is_empty() && !title.is_empty() {
+                    // 取一级标题作为文档标题
+                    entry.title = title.split(" / ").next().unwrap_or(title).to_string();
+                }
+                if entry.hash.is_empty()
+                    && let Some(hash) = optional(doc_hash)
+                {
+                    entry.hash = hash;
+                }
+                if let Some(at) = optional(indexed_at)
+                    && entry
+                        .indexed_at
+                        .as_ref()
+                        .is_none_or(|current| *current < at)
+                {
+                    entry.indexed_at = Some(at);
+                }
+            }
+        }
+
+        Ok(documents
+            .into_values()
+            .map(|mut doc| {
+                if doc.title.is_empty() {
+                    doc.title = doc.path.clone();
+                }
+                doc
+            })
+            .collect())
+    }
+
+    async fn list_chunks(&self, doc_id: &str) -> VectorStoreResult<Vec<ChunkRecord>> {
+        let batches = self
+            .query_columns(
+                &["chunk_id", "title_path", "section", "text", "hash"],
+                Some(Self::doc_id_predicate(doc_id)),
+            )
+            .await?;
+        let mut chunks = Vec::new();
+
+        for batch in batches {
+            let chunk_id = self.column_as_string(&batch, "chunk_id")?;
+            let title_path = self.column_as_string(&batch, "title_path")?;
+            let section = self.column_as_string(&batch, "section")?;
+            let text = self.column_as_string(&batch, "text")?;
+            let hash = self.column_as_string(&batch, "hash")?;
+
+            for row in 0..batch.num_rows() {
+                chunks.push(ChunkRecord {
+                    chunk_id: chunk_id.value(row).to_string(),
+                    title_path: title_path.value(row).to_string(),
+                    section: section.value(row).to_string(),
+                    text: text.value(row).to_string(),
+                    hash: hash.value(row).to_string(),
+                });
+            }
+        }
+
+        chunks.sort_by_key(|chunk| chunk_ordinal(&chunk.chunk_id));
+        Ok(chunks)
+    }
+
     async fn count(&self) -> VectorStoreResult<usize> {
         let table = self.open_table().await?;
         let count = table.count_rows(None).await?;
         Ok(count)
     }
+
+    fn spec(&self) -> &IndexSpec {
+        &self.spec
+    }
+
+    async fn stored_spec(&self) -> VectorStoreResult<IndexSpec> {
+        Ok(self
+            .stored_spec
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone())
+    }
+
+    async fn optimize(&self, options: &MaintenanceOptions) -> VectorStoreResult<MaintenanceReport> {
+        let started_at = chrono::Utc::now();
+        let table_name = self.table_name();
+        let table = self.open_table().await?;
+
+        let compaction = if options.compact {
+            let stats = table
+                .optimize(OptimizeAction::Compact {
+                    options: CompactionOptions::default(),
+                    remap_options: None,
+                })
+                .await?;
+            stats.compaction.map(|metrics| CompactionStats {
+                fragments_removed: metrics.fragments_removed,
+                fragments_added: metrics.fragments_added,
+                files_removed: metrics.files_removed,
+                files_added: metrics.files_added,
+            })
+        } else {
+            None
+        };
+
+        let prune = match options.prune_older_than_secs {
+            Some(secs) => {
+                let older_than = i64::try_from(secs)
+                    .ok()
+                    .and_then(chrono::TimeDelta::try_seconds)
+                    .ok_or_else(|| {
+                        VectorStoreError::InvalidPayload(format!(
+                            "prune_older_than_secs out of range: {secs}"
+                        ))
+                    })?;
+                let stats = table
+                    .optimize(OptimizeAction::Prune {
+                        older_than: Some(older_than),
+                        delete_unverified: Some(false),
+                        error_if_tagged_old_versions: Some(false),
+                    })
+                    .await?;
+                stats.prune.map(|removal| PruneStats {
+                    old_versions: removal.old_versions,
+                    bytes_removed: removal.bytes_removed,
+                })
+            }
+            None => None,
+        };
+
+        // 重建会重新训练索引；仅优化时把未索引的新数据并入已有索引
+        let index_rebuilt = options.rebuild_index && table.count_rows(None).await? > 0;
+        if index_rebuilt {
+            self.create_vector_index(&table, true).await?;
+        } else if options.optimize_index && self.vector_index_stats(&table).await?.is_some() {
+            table
+                .optimize(OptimizeAction::Index(OptimizeOptions::new()))
+                .await?;
+        } else if options.optimize_index
+            && let Err(err) = self.try_ensure_vector_index(&table).await
+        {
+            tracing::warn!(table = %table_name, error = %err, "failed to create vector index");
+        }
+
+        let vector_index = self.vector_index_stats(&table).await?;
+        let duration_ms = (chrono::Utc::now() - started_at).num_milliseconds().max(0) as u64;
+        tracing::info!(
+            table = %table_name,
+            duration_ms,
+            index_rebuilt,
+            "vector table maintenance finished"
+        );
+
+        Ok(MaintenanceReport {
+            table: table_name,
+            started_at: started_at.to_rfc3339(),
+            duration_ms,
+            compaction,
+            prune,
+            index_rebuilt,
+            vector_index,
+        })
+    }
+
+    async fn create_version(&self) -> VectorStoreResult<(String, Arc<dyn VectorStore>)> {
+        let name = format!(
+            "{}{VERSION_MARKER}{}",
+            self.base_table,
+            chrono::Utc::now().format(VERSION_TIME_FORMAT)
+        );
+        self.connection
+            .create_empty_table(&name, self.schema())
+            .execute()
+            .await?;
+        tracing::info!(table = %name, "created index version");
+
+        let staging = Self {
+            connection: self.connection.clone(),
+            base_table: self.base_table.clone(),
+            active_table: Arc::new(RwLock::new(name.clone())),
+            stored_spec: RwLock::new(self.spec.clone()),
+            spec: self.spec.clone(),
+            index: self.index.clone(),
+        };
+        Ok((name, Arc::new(staging)))
+    }
+
+    async fn activate_version(&self, version: &str) -> VectorStoreResult<()> {
+        let names = self.connection.table_names().execute().await?;
+        if !self.is_version_table(version) || !names.iter().any(|name| name == version) {
+            return Err(VectorStoreError::VersionNotFound(version.to_string()));
+        }
+
+        let table = self.connection.open_table(version).execute().await?;
+        let stored = Self::read_spec(&table).await?;
+        // 按其他模型或维度构建的版本无法用当前的查询向量检索
+        if stored != self.spec {
+            return Err(VectorStoreError::VersionSpecMismatch {
+                version: version.to_string(),
+                stored,
+                expected: self.spec.clone(),
+            });
+        }
+        if let Err(err) = self.try_ensure_vector_index(&table).await {
+            tracing::warn!(table = %version, error = %err, "failed to create or reuse vector index");
+        }
+
+        self.write_pointer(version).await?;
+        self.set_stored_spec(stored);
+        let previous = std::mem::replace(
+            &mut *self.active_table.write().unwrap_or_else(|e| e.into_inner()),
+            version.to_string(),
+        );
+        tracing::info!(from = %previous, to = %version, "switched active index version");
+        Ok(())
+    }
+
+    async fn list_versions(&self) -> VectorStoreResult<Vec<IndexVersion>> {
+        let active = self.table_name();
+        let mut names: Vec<String> = self
+            .connection
+            .table_names()
+            .execute()
+            .await?
+            .into_iter()
+            .filter(|name| self.is_version_table(name))
+            .collect();
+        // 旧版单表排在最前，其余按名称中的时间排序
+        names.sort_by_key(|name| (name != &self.base_table, name.clone()));
+
+        let mut versions = Vec::with_capacity(names.len());
+        for name in names {
+            let table = self.connection.open_table(&name).execute().await?;
+            versions.push(IndexVersion {
+                created_at: self.version_created_at(&name),
+                chunk_count: table.count_rows(None).await?,
+                active: name == active,
+                name,
+            });
+        }
+        Ok(versions)
+    }
+
+    async fn drop_version(&self, version: &str) -> VectorStoreResult<()> {
+        if version == self.table_name() {
+            return Err(VectorStoreError::ActiveVersion(version.to_string()));
+        }
+        if !self.is_version_table(version) {
+            return Err(VectorStoreError::VersionNotFound(version.to_string()));
+        }
+
+        self.connection.drop_table(version, &[]).await?;
+        tracing::info!(table = %version, "dropped index version");
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::LanceDbStore;
+    use crate::{
+        config::{DistanceMetric, VectorIndexConfig},
+        vector_store::{
+            IndexSpec, MaintenanceOptions, VectorStore, VectorStoreError, spec_mismatch,
+            test_support::{check_conformance, spec, stored_chunk},
+        },
+    };
+
+    #[tokio::test]
+    async fn conforms_to_the_vector_store_contract() {
+        let dir = std::env::temp_dir().join(format!("engineqa-lance-{}", uuid::Uuid::new_v4()));
+        let store = LanceDbStore::new(
+            dir.to_str().unwrap(),
+            "chunks",
+            spec("m", 2),
+            VectorIndexConfig::default(),
+        )
+        .await
+        .expect("store should open");
+
+        check_conformance(&store).await;
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn switches_between_index_versions() {
+        let dir = std::env::temp_dir().join(format!("engineqa-lance-{}", uuid::Uuid::new_v4()));
+        let uri = dir.to_str().unwrap();
+        let store = LanceDbStore::new(uri, "chunks", spec("m", 2), VectorIndexConfig::default())
+            .await
+            .unwrap();
+        store
+            .upsert_chunks(vec![stored_chunk("a", 0, vec![1.0, 0.0])])
+            .await
+            .unwrap();
+
+        let (version, staging) = store.create_version().await.unwrap();
+        staging
+            .upsert_chunks(vec![
+                stored_chunk("b", 0, vec![0.0, 1.0]),
+                stored_chunk("b", 1, vec![1.0, 1.0]),
+            ])
+            .await
+            .unwrap();
+        assert_eq!(store.count().await.unwrap(), 1);
+
+        store.activate_version(&version).await.unwrap();
+        assert_eq!(store.count().await.unwrap(), 2);
+        assert!(store.drop_version(&version).await.is_err());
+
+        let versions = store.list_versions().await.unwrap();
+        assert_eq!(versions.len(), 2);
+        assert_eq!(versions[0].name, "chunks");
+        assert!(versions[0].created_at.is_none());
+        assert!(versions[1].active);
+        assert!(versions[1].created_at.is_some());
+
+        // 重新打开时沿用指针表记录的版本
+        let reopened = LanceDbStore::new(uri, "chunks", spec("m", 2), VectorIndexConfig::default())
+            .await
+            .unwrap();
+        assert_eq!(reopened.count().await.unwrap(), 2);
+
+        reopened.activate_version("chunks").await.unwrap();
+        reopened.drop_version(&version).await.unwrap();
+        assert_eq!(reopened.list_versions().await.unwrap().len(), 1);
+        assert!(reopened.activate_version(&version).await.is_err());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn records_spec_and_detects_model_change() {
+        let dir = std::env::temp_dir().join(format!("engineqa-lance-{}", uuid::Uuid::new_v4()));
+        let uri = dir.to_str().unwrap();
+        let store = LanceDbStore::new(uri, "chunks", spec("m1", 2), VectorIndexConfig::default())
+            .await
+            .unwrap();
+        store
+            .upsert_chunks(vec![stored_chunk("a", 0, vec![1.0, 0.0])])
+            .await
+            .unwrap();
+        assert_eq!(store.stored_spec().await.unwrap(), spec("m1", 2));
+        assert!(spec_mismatch(&store).await.unwrap().is_none());
+
+        // 仅距离度量变化也视为不一致
+        let l2 = IndexSpec {
+            metric: DistanceMetric::L2,
+            ..spec("m1", 2)
+        };
+        let reopened = LanceDbStore::new(uri, "chunks", l2, VectorIndexConfig::default())
+            .await
+            .unwrap();
+        assert_eq!(spec_mismatch(&reopened).await.unwrap(), Some(spec("m1", 2)));
+
+        let switched =
+            LanceDbStore::new(uri, "chunks", spec("m2", 3), VectorIndexConfig::default())
+                .await
+                .unwrap();
+        assert_eq!(spec_mismatch(&switched).await.unwrap(), Some(spec("m1", 2)));
+
+        // 新版本按新参数建表，切换后不再不一致
+        let (version, staging) = switched.create_version().await.unwrap();
+        staging
+            .upsert_chunks(vec![stored_chunk("a", 0, vec![1.0, 0.0, 0.0])])
+            .await
+            .unwrap();
+        switched.activate_version(&version).await.unwrap();
+        assert!(spec_mismatch(&switched).await.unwrap().is_none());
+
+        // 不能回滚到按旧参数构建的版本
+        assert!(matches!(
+            switched.activate_version("chunks").await,
+            Err(VectorStoreError::VersionSpecMismatch { .. })
+        ));
+        assert_eq!(switched.stored_spec().await.unwrap(), spec("m2", 3));
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn compacts_and_prunes_fragmented_table() {
+        let dir = std::env::temp_dir().join(format!("engineqa-lance-{}", uuid::Uuid::new_v4()));
+        let store = LanceDbStore::new(
+            dir.to_str().unwrap(),
+            "chunks",
+            spec("m", 2),
+            VectorIndexConfig::default(),
+        )
+        .await
+        .unwrap();
+
+        // 每次写入产生一个小文件，再删除其中一个文档
+        for (n, doc_id) in ["a", "b", "c", "d"].into_iter().enumerate() {
+            store
+                .upsert_chunks(vec![stored_chunk(doc_id, 0, vec![1.0, n as f32])])
+                .await
+                .unwrap();
+        }
+        store.delete_by_doc_id("a").await.unwrap();
+
+        let report = store
+            .optimize(&MaintenanceOptions {
+                prune_older_than_secs: Some(0),
+                ..MaintenanceOptions::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(report.table, "chunks");
+        assert!(report.compaction.unwrap().fragments_removed > 1);
+        assert!(report.prune.is_some());
+        assert!(!report.index_rebuilt);
+        assert_eq!(store.count().await.unwrap(), 3);
 
-    #[test]
-    fn distance_to_score_converts_and_clamps() {
-        assert_eq!(LanceDbStore::distance_to_score(0.0), 1.0);
-        assert_eq!(LanceDbStore::distance_to_score(2.0), 0.0);
-        assert_eq!(LanceDbStore::distance_to_score(1.0), 0.5);
-        assert_eq!(LanceDbStore::distance_to_score(-1.0), 1.0);
-        assert_eq!(LanceDbStore::distance_to_score(3.0), 0.0);
+        let _ = std::fs::remove_dir_all(dir);
     }
 }
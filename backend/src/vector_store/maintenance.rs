@@ -0,0 +1,116 @@
+use std::{sync::Arc, time::Duration};
+use tokio::sync::{Mutex, RwLock};
+use tracing::{info, warn};
+
+use crate::{
+    AppState,
+    api::reindex::ReindexError,
+    config::IndexMaintenanceConfig,
+    vector_store::{MaintenanceOptions, MaintenanceReport, VectorStoreError},
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum MaintenanceError {
+    #[error("Table maintenance is already running")]
+    AlreadyRunning,
+
+    #[error("A reindex job is in progress")]
+    JobInProgress,
+
+    #[error(transparent)]
+    VectorStore(#[from] VectorStoreError),
+}
+
+/// 向量表维护：按间隔在后台执行，也可由管理接口手动触发；同一时间只运行一次，
+/// 运行期间占用 JobManager 的写入槽，与索引任务互斥
+pub struct IndexMaintainer {
+    interval_secs: u64,
+    prune_older_than_secs: u64,
+    lock: Mutex<()>,
+    last_report: RwLock<Option<MaintenanceReport>>,
+}
+
+impl IndexMaintainer {
+    pub fn new(config: &IndexMaintenanceConfig) -> Self {
+        Self {
+            interval_secs: config.interval_secs,
+            prune_older_than_secs: config.prune_older_than_secs,
+            lock: Mutex::new(()),
+            last_report: RwLock::new(None),
+        }
+    }
+
+    /// 后台维护间隔，0 表示未启用
+    pub fn interval_secs(&self) -> u64 {
+        self.interval_secs
+    }
+
+    /// 配置中的历史版本保留时长
+    pub fn prune_older_than_secs(&self) -> u64 {
+        self.prune_older_than_secs
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.lock.try_lock().is_err()
+    }
+
+    /// 最近一次成功维护的结果
+    pub async fn last_report(&self) -> Option<MaintenanceReport> {
+        self.last_report.read().await.clone()
+    }
+
+    /// 对当前版本执行一次维护；已有维护或索引任务在运行时立即返回而不排队
+    pub async fn run(
+        &self,
+        state: &AppState,
+        options: &MaintenanceOptions,
+    ) -> Result<MaintenanceReport, MaintenanceError> {
+        let _guard = self
+            .lock
+            .try_lock()
+            .map_err(|_| MaintenanceError::AlreadyRunning)?;
+        let _slot = state
+            .job_manager
+            .reserve_maintenance()
+            .await
+            .map_err(|e| match e {
+                ReindexError::MaintenanceInProgress => MaintenanceError::AlreadyRunning,
+                _ => MaintenanceError::JobInProgress,
+            })?;
+
+        let report = state.vector_store.optimize(options).await?;
+        *self.last_report.write().await = Some(report.clone());
+        Ok(report)
+    }
+
+    /// 启动后台维护循环；到点时若有索引任务在运行则跳过本次
+    pub fn spawn(state: Arc<AppState>) {
+        let interval_secs = state.maintainer.interval_secs;
+        if interval_secs == 0 {
+            return;
+        }
+
+        let options = MaintenanceOptions {
+            prune_older_than_secs: Some(state.maintainer.prune_older_than_secs),
+            ..MaintenanceOptions::default()
+        };
+
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(Duration::from_secs(interval_secs)).await;
+
+                match state.maintainer.run(&state, &options).await {
+                    Ok(_) | Err(MaintenanceError::AlreadyRunning) => {}
+                    Err(MaintenanceError::JobInProgress) => {
+                        info!("reindex job running, skipping scheduled table maintenance");
+                    }
+                    Err(MaintenanceError::VectorStore(VectorStoreError::Unsupported(_))) => {
+                        info!("vector store has no table maintenance, stopping scheduler");
+                        break;
+                    }
+                    Err(e) => warn!(error = %e, "scheduled table maintenance failed"),
+                }
+            }
+        });
+    }
+}
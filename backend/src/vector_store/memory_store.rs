@@ -0,0 +1,610 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        Arc, RwLock,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+use tokio::sync::Mutex;
+
+use crate::{
+    config::DistanceMetric,
+    vector_store::{
+        ChunkFilter, ChunkRecord, DocumentSummary, IndexSpec, IndexVersion, SearchHit, StoredChunk,
+        VectorStore, VectorStoreError, VectorStoreResult, chunk_ordinal, distance_to_score,
+    },
+};
+
+const BASE_TABLE: &str = "memory";
+const VERSION_MARKER: &str = "__v";
+
+/// 一个版本的全部片段，按 `point_id` 存放
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct MemoryTable {
+    seq: u64,
+    created_at: Option<String>,
+    spec: IndexSpec,
+    chunks: BTreeMap<String, StoredChunk>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Tables {
+    active: String,
+    next_seq: u64,
+    tables: BTreeMap<String, MemoryTable>,
+}
+
+struct Shared {
+    tables: RwLock<Tables>,
+    snapshot_path: Option<PathBuf>,
+    /// 串行化快照写入，保证最后落盘的是最新状态
+    persist_lock: Mutex<()>,
+    /// 有尚未落盘的片段写入
+    dirty: AtomicBool,
+}
+
+/// 暴力检索的内存向量存储，用于测试与小规模部署
+///
+/// 配置快照路径时整体落盘为 JSON，启动时从快照恢复。片段写入只标记为脏，
+/// 由 `flush` 在一批写入（一次索引任务或删除请求）结束后统一落盘；
+/// 版本的创建、切换与删除立即落盘。蓝绿版本同样保存在内存中，
+/// 与 LanceDB 的版本语义一致。
+pub struct MemoryStore {
+    shared: Arc<Shared>,
+    /// 蓝绿构建的新版本句柄固定写入该版本，否则跟随当前版本
+    pinned: Option<String>,
+    spec: IndexSpec,
+}
+
+impl MemoryStore {
+    pub fn new(spec: IndexSpec, snapshot_path: Option<&str>) -> VectorStoreResult<Self> {
+        let snapshot_path = snapshot_path.map(PathBuf::from);
+        let tables = match &snapshot_path {
+            Some(path) => load_snapshot(path)?,
+            None => None,
+        };
+        let tables = tables.unwrap_or_else(|| Tables {
+            active: BASE_TABLE.to_string(),
+            next_seq: 1,
+            tables: BTreeMap::from([(
+                BASE_TABLE.to_string(),
+                MemoryTable {
+                    seq: 0,
+                    created_at: None,
+                    spec: spec.clone(),
+                    chunks: BTreeMap::new(),
+                },
+            )]),
+        });
+
+        Ok(Self {
+            shared: Arc::new(Shared {
+                tables: RwLock::new(tables),
+                snapshot_path,
+                persist_lock: Mutex::new(()),
+                dirty: AtomicBool::new(false),
+            }),
+            pinned: None,
+            spec,
+        })
+    }
+
+    fn table_name(&self) -> String {
+        match &self.pinned {
+            Some(name) => name.clone(),
+            None => self.read().active.clone(),
+        }
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, Tables> {
+        self.shared.tables.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, Tables> {
+        self.shared
+            .tables
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 在当前版本上读取
+    fn with_table<T>(&self, f: impl FnOnce(&MemoryTable) -> T) -> VectorStoreResult<T> {
+        let name = self.table_name();
+        let tables = self.read();
+        let table = tables
+            .tables
+            .get(&name)
+            .ok_or(VectorStoreError::VersionNotFound(name))?;
+        Ok(f(table))
+    }
+
+    /// 在当前版本上修改，只标记为脏，等待 `flush` 落盘
+    fn update_table<T>(&self, f: impl FnOnce(&mut MemoryTable) -> T) -> VectorStoreResult<T> {
+        let name = self.table_name();
+        let mut tables = self.write();
+        let table = tables
+            .tables
+            .get_mut(&name)
+            .ok_or(VectorStoreError::VersionNotFound(name))?;
+        let result = f(table);
+        self.shared.dirty.store(true, Ordering::Relaxed);
+        Ok(result)
+    }
+
+    async fn persist(&self) -> VectorStoreResult<()> {
+        let Some(path) = self.shared.snapshot_path.clone() else {
+            return Ok(());
+        };
+
+        let _guard = self.shared.persist_lock.lock().await;
+        // 先清除标记：克隆快照之后的写入会重新置位，由下一次 flush 落盘
+        self.shared.dirty.store(false, Ordering::Relaxed);
+        // 只在锁内克隆，序列化与写文件都放到阻塞线程，避免占住运行时线程和读锁
+        let tables = self.read().clone();
+        let result: VectorStoreResult<()> = async {
+            tokio::task::spawn_blocking(move || -> VectorStoreResult<()> {
+                let bytes = serde_json::to_vec(&tables)
+                    .map_err(|e| VectorStoreError::InvalidPayload(e.to_string()))?;
+                write_snapshot(&path, &bytes)?;
+                Ok(())
+            })
+            .await
+            .map_err(std::io::Error::other)?
+        }
+        .await;
+
+        if result.is_err() {
+            self.shared.dirty.store(true, Ordering::Relaxed);
+        }
+        result
+    }
+}
+
+/// 距离与 LanceDB 的定义一致：cosine/dot 为 1 - 相似度，L2 为欧氏距离的平方
+fn distance(metric: DistanceMetric, a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    match metric {
+        DistanceMetric::Cosine => {
+            let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+            let denom = norm(a) * norm(b);
+            if denom == 0.0 { 1.0 } else { 1.0 - dot / denom }
+        }
+        DistanceMetric::L2 => a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum(),
+        DistanceMetric::Dot => 1.0 - dot,
+    }
+}
+
+fn load_snapshot(path: &Path) -> VectorStoreResult<Option<Tables>> {
+    let raw = match fs::read(path) {
+        Ok(raw) => raw,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+
+    // 快照损坏时拒绝启动，避免静默丢失索引
+    let tables: Tables = serde_json::from_slice(&raw).map_err(|e| {
+        VectorStoreError::InvalidPayload(format!(
+            "corrupt memory store snapshot {}: {e}",
+            path.display()
+        ))
+    })?;
+    let chunks = tables
+        .tables
+        .get(&tables.active)
+        .map(|table| table.chunks.len())
+        .unwrap_or_default();
+    tracing::info!(path = %path.display(), active = %tables.active, chunks, "memory store loaded");
+    Ok(Some(tables))
+}
+
+fn write_snapshot(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, bytes)?;
+    fs::rename(tmp, path)
+}
+
+#[async_trait::async_trait]
+impl VectorStore for MemoryStore {
+    async fn ensure_ready(&self) -> VectorStoreResult<()> {
+        self.with_table(|_| ())
+    }
+
+    async fn search(
+        &self,
+        query_vector: Vec<f32>,
+        top_k: u64,
+    ) -> VectorStoreResult<Vec<SearchHit>> {
+        self.search_filtered(query_vector, top_k, &ChunkFilter::default())
+            .await
+    }
+
+    async fn search_filtered(
+        &self,
+        query_vector: Vec<f32>,
+        top_k: u64,
+        filter: &ChunkFilter,
+    ) -> VectorStoreResult<Vec<SearchHit>> {
+        if query_vector.len() != self.spec.vector_size {
+            return Err(VectorStoreError::InvalidPayload(format!(
+                "vector size mismatch: got {}, expected {}",
+                query_vector.len(),
+                self.spec.vector_size
+            )));
+        }
+
+        let metric = self.spec.metric;
+        let mut scored = self.with_table(|table| {
+            table
+                .chunks
+                .values()
+                .filter(|chunk| filter.matches(&chunk.doc_id, &chunk.path))
+                .map(|chunk| {
+                    (
+                        distance(metric, &query_vector, &chunk.vector),
+                        chunk.clone(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        })?;
+        scored.sort_by(|a, b| a.0.total_cmp(&b.0));
+        scored.truncate(top_k as usize);
+
+        Ok(scored
+            .into_iter()
+            .map(|(distance, chunk)| SearchHit {
+                doc_id: chunk.doc_id,
+                chunk_id: chunk.chunk_id,
+                path: chunk.path,
+                title_path: chunk.title_path,
+                section: chunk.section,
+                snippet: chunk.text,
+                score: distance_to_score(metric, distance),
+            })
+            .collect())
+    }
+
+    async fn upsert_chunks(&self, chunks: Vec<StoredChunk>) -> VectorStoreResult<()> {
+        if chunks.is_empty() {
+            return Ok(());
+        }
+        if let Some(chunk) = chunks
+            .iter()
+            .find(|chunk| chunk.vector.len() != self.spec.vector_size)
+        {
+            return Err(VectorStoreError::InvalidPayload(format!(
+                "vector size mismatch: got {}, expected {}",
+                chunk.vector.len(),
+                self.spec.vector_size
+            )));
+        }
+
+        self.update_table(|table| {
+            for chunk in chunks {
+                table.chunks.insert(chunk.point_id.clone(), chunk);
+            }
+        })
+    }
+
+    async fn delete_by_doc_id(&self, doc_id: &str) -> VectorStoreResult<usize> {
+        self.update_table(|table| {
+            let before = table.chunks.len();
+            table.chunks.retain(|_, chunk| chunk.doc_id != doc_id);
+            before - table.chunks.len()
+        })
+    }
+
+    async fn delete_stale_chunks(
+        &self,
+        doc_id: &str,
+        keep_point_ids: &[String],
+    ) -> VectorStoreResult<usize> {
+        self.update_table(|table| {
+            let before = table.chunks.len();
+            table.chunks.retain(|point_id, chunk| {
+                chunk.doc_id != doc_id || keep_point_ids.contains(point_id)
+            });
+            before - table.chunks.len()
+        })
+    }
+
+    async fn chunk_vectors(&self, doc_id: &str) -> VectorStoreResult<HashMap<String, Vec<f32>>> {
+        self.with_table(|table| {
+            table
+                .chunks
+                .values()
+                .filter(|chunk| chunk.doc_id == doc_id)
+                .map(|chunk| (chunk.hash.clone(), chunk.vector.clone()))
+                .collect()
+        })
+    }
+
+    async fn list_doc_hashes(&self) -> VectorStoreResult<HashMap<String, String>> {
+        self.with_table(|table| {
+            table
+                .chunks
+                .values()
+                .map(|chunk| (chunk.doc_id.clone(), chunk.doc_hash.clone()))
+                .collect()
+        })
+    }
+
+    async fn list_documents(&self) -> VectorStoreResult<Vec<DocumentSummary>> {
+        let documents = self.with_table(|table| {
+            let mut documents: BTreeMap<String, DocumentSummary> = BTreeMap::new();
+            for chunk in table.chunks.values() {
+                let entry =
+                    documents
+                        .entry(chunk.doc_id.clone())
+                        .or_insert_with(|| DocumentSummary {
+                            doc_id: chunk.doc_id.clone(),
+                            path: chunk.path.clone(),
+                            title: String::new(),
+                            chunk_count: 0,
+                            hash: String::new(),
+                            indexed_at: None,
+                        });
+                entry.chunk_count += 1;
+
+                if entry.title.is_empty() && !chunk.title_path.is_empty() {
+                    // 取一级标题作为文档标题
+                    let title = &chunk.title_path;
+                    entry.title = title.split(" / ").next().unwrap_or(title).to_string();
+                }
+                if entry.hash.is_empty() {
+                    entry.hash = chunk.doc_hash.clone();
+                }
+                if !chunk.indexed_at.is_empty()
+                    && entry
+                        .indexed_at
+                        .as_ref()
+                        .is_none_or(|current| *current < chunk.indexed_at)
+                {
+                    entry.indexed_at = Some(chunk.indexed_at.clone());
+                }
+            }
+            documents
+        })?;
+
+        Ok(documents
+            .into_values()
+            .map(|mut doc| {
+                if doc.title.is_empty() {
+                    doc.title = doc.path.clone();
+                }
+                doc
+            })
+            .collect())
+    }
+
+    async fn list_chunks(&self, doc_id: &str) -> VectorStoreResult<Vec<ChunkRecord>> {
+        let mut chunks = self.with_table(|table| {
+            table
+                .chunks
+                .values()
+                .filter(|chunk| chunk.doc_id == doc_id)
+                .map(|chunk| ChunkRecord {
+                    chunk_id: chunk.chunk_id.clone(),
+                    title_path: chunk.title_path.clone(),
+                    section: chunk.section.clone(),
+                    text: chunk.text.clone(),
+                    hash: chunk.hash.clone(),
+                })
+                .collect::<Vec<_>>()
+        })?;
+        chunks.sort_by_key(|chunk| chunk_ordinal(&chunk.chunk_id));
+        Ok(chunks)
+    }
+
+    async fn count(&self) -> VectorStoreResult<usize> {
+        self.with_table(|table| table.chunks.len())
+    }
+
+    async fn flush(&self) -> VectorStoreResult<()> {
+        if self.shared.dirty.load(Ordering::Relaxed) {
+            self.persist().await?;
+        }
+        Ok(())
+    }
+
+    fn spec(&self) -> &IndexSpec {
+        &self.spec
+    }
+
+    async fn stored_spec(&self) -> VectorStoreResult<IndexSpec> {
+        self.with_table(|table| table.spec.clone())
+    }
+
+    async fn create_version(&self) -> VectorStoreResult<(String, Arc<dyn VectorStore>)> {
+        let name = {
+            let mut tables = self.write();
+            let seq = tables.next_seq;
+            tables.next_seq += 1;
+            let name = format!("{BASE_TABLE}{VERSION_MARKER}{seq}");
+            tables.tables.insert(
+                name.clone(),
+                MemoryTable {
+                    seq,
+                    created_at: Some(chrono::Utc::now().to_rfc3339()),
+                    spec: self.spec.clone(),
+                    chunks: BTreeMap::new(),
+                },
+            );
+            name
+        };
+        self.persist().await?;
+        tracing::info!(table = %name, "created index version");
+
+        let staging = Self {
+            shared: self.shared.clone(),
+            pinned: Some(name.clone()),
+            spec: self.spec.clone(),
+        };
+        Ok((name, Arc::new(staging)))
+    }
+
+    async fn activate_version(&self, version: &str) -> VectorStoreResult<()> {
+        let previous = {
+            let mut tables = self.write();
+            let Some(table) = tables.tables.get(version) else {
+                return Err(VectorStoreError::VersionNotFound(version.to_string()));
+            };
+            if table.spec != self.spec {
+                return Err(VectorStoreError::VersionSpecMismatch {
+                    version: version.to_string(),
+                    stored: table.spec.clone(),
+                    expected: self.spec.clone(),
+                });
+            }
+            std::mem::replace(&mut tables.active, version.to_string())
+        };
+        self.persist().await?;
+        tracing::info!(from = %previous, to = %version, "switched active index version");
+        Ok(())
+    }
+
+    async fn list_versions(&self) -> VectorStoreResult<Vec<IndexVersion>> {
+        let tables = self.read();
+        let mut versions: Vec<(u64, IndexVersion)> = tables
+            .tables
+            .iter()
+            .map(|(name, table)| {
+                (
+                    table.seq,
+                    IndexVersion {
+                        name: name.clone(),
+                        created_at: table.created_at.clone(),
+                        chunk_count: table.chunks.len(),
+                        active: *name == tables.active,
+                    },
+                )
+            })
+            .collect();
+        versions.sort_by_key(|(seq, _)| *seq);
+        Ok(versions.into_iter().map(|(_, version)| version).collect())
+    }
+
+    async fn drop_version(&self, version: &str) -> VectorStoreResult<()> {
+        {
+            let mut tables = self.write();
+            if version == tables.active {
+                return Err(VectorStoreError::ActiveVersion(version.to_string()));
+            }
+            if tables.tables.remove(version).is_none() {
+                return Err(VectorStoreError::VersionNotFound(version.to_string()));
+            }
+        }
+        self.persist().await?;
+        tracing::info!(table = %version, "dropped index version");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vector_store::test_support::{check_conformance, spec, stored_chunk};
+
+    #[tokio::test]
+    async fn conforms_to_the_vector_store_contract() {
+        let store = MemoryStore::new(spec("m", 2), None).unwrap();
+        check_conformance(&store).await;
+        assert!(store.search(vec![1.0], 2).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn switches_versions_and_restores_from_snapshot() {
+        let path =
+            std::env::temp_dir().join(format!("engineqa-memory-{}.json", uuid::Uuid::new_v4()));
+        let path_str = path.to_str().unwrap();
+        let store = MemoryStore::new(spec("m", 2), Some(path_str)).unwrap();
+        store
+            .upsert_chunks(vec![stored_chunk("a", 0, vec![1.0, 0.0])])
+            .await
+            .unwrap();
+
+        let (version, staging) = store.create_version().await.unwrap();
+        staging
+            .upsert_chunks(vec![
+                stored_chunk("a", 0, vec![1.0, 0.0]),
+                stored_chunk("b", 0, vec![0.0, 1.0]),
+            ])
+            .await
+            .unwrap();
+        assert_eq!(store.count().await.unwrap(), 1);
+
+        store.activate_version(&version).await.unwrap();
+        assert_eq!(store.count().await.unwrap(), 2);
+        assert!(matches!(
+            store.drop_version(&version).await,
+            Err(VectorStoreError::ActiveVersion(_))
+        ));
+
+        let reopened = MemoryStore::new(spec("m", 2), Some(path_str)).unwrap();
+        let versions = reopened.list_versions().await.unwrap();
+        assert_eq!(versions.len(), 2);
+        assert_eq!(versions[0].name, "memory");
+        assert!(versions[1].active);
+        assert_eq!(reopened.list_chunks("b").await.unwrap().len(), 1);
+
+        reopened.drop_version("memory").await.unwrap();
+        assert_eq!(reopened.list_versions().await.unwrap().len(), 1);
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn refuses_to_activate_versions_built_with_another_spec() {
+        let path =
+            std::env::temp_dir().join(format!("engineqa-memory-{}.json", uuid::Uuid::new_v4()));
+        let path_str = path.to_str().unwrap();
+        let store = MemoryStore::new(spec("m", 2), Some(path_str)).unwrap();
+        let (old_version, _) = store.create_version().await.unwrap();
+
+        let reopened = MemoryStore::new(spec("m", 3), Some(path_str)).unwrap();
+        assert!(matches!(
+            reopened.activate_version(&old_version).await,
+            Err(VectorStoreError::VersionSpecMismatch { .. })
+        ));
+        let (new_version, _) = reopened.create_version().await.unwrap();
+        reopened.activate_version(&new_version).await.unwrap();
+        assert_eq!(reopened.stored_spec().await.unwrap(), spec("m", 3));
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn buffers_chunk_writes_until_flush() {
+        let path =
+            std::env::temp_dir().join(format!("engineqa-memory-{}.json", uuid::Uuid::new_v4()));
+        let path_str = path.to_str().unwrap();
+        let store = MemoryStore::new(spec("m", 2), Some(path_str)).unwrap();
+        store
+            .upsert_chunks(vec![
+                stored_chunk("a", 0, vec![1.0, 0.0]),
+                stored_chunk("a", 1, vec![0.0, 1.0]),
+            ])
+            .await
+            .unwrap();
+        store.delete_stale_chunks("a", &[]).await.unwrap();
+        store
+            .upsert_chunks(vec![stored_chunk("b", 0, vec![0.6, 0.8])])
+            .await
+            .unwrap();
+        assert!(!path.exists());
+
+        store.flush().await.unwrap();
+        let reopened = MemoryStore::new(spec("m", 2), Some(path_str)).unwrap();
+        assert_eq!(reopened.count().await.unwrap(), 1);
+
+        // 没有新的写入时不重复落盘
+        std::fs::remove_file(&path).unwrap();
+        store.flush().await.unwrap();
+        assert!(!path.exists());
+    }
+}
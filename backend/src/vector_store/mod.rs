@@ -1,9 +1,34 @@
 use async_trait::async_trait;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt, sync::Arc};
+
+use crate::config::{DistanceMetric, IndexSpecPolicy};
 
 pub mod lancedb_store;
+pub mod maintenance;
+pub mod memory_store;
+#[cfg(feature = "pgvector")]
+pub mod pgvector_store;
+pub mod qdrant_store;
+/// 各向量存储共用的测试数据与行为一致性测试
+#[cfg(test)]
+pub(crate) mod test_support;
 
-#[derive(Debug, Clone)]
+pub const LANCEDB: &str = "lancedb";
+pub const MEMORY: &str = "memory";
+pub const QDRANT: &str = "qdrant";
+pub const PGVECTOR: &str = "pgvector";
+
+/// `VECTOR_STORE` 可选的实现
+#[cfg(feature = "pgvector")]
+pub const KNOWN_VECTOR_STORES: &[&str] = &[LANCEDB, MEMORY, QDRANT, PGVECTOR];
+#[cfg(not(feature = "pgvector"))]
+pub const KNOWN_VECTOR_STORES: &[&str] = &[LANCEDB, MEMORY, QDRANT];
+
+/// 默认的过滤检索向存储多取的倍数，过滤后仍尽量凑满 top_k
+const FILTER_OVERFETCH: u64 = 4;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StoredChunk {
     pub point_id: String,
     pub doc_id: String,
@@ -13,12 +38,17 @@ pub struct StoredChunk {
     pub section: String,
     pub text: String,
     pub hash: String,
+    /// 整个源文件的内容哈希，用于增量索引判断
+    pub doc_hash: String,
+    /// 写入时间（RFC3339）
+    pub indexed_at: String,
     pub vector: Vec<f32>,
 }
 
 #[derive(Debug, Clone)]
 pub struct SearchHit {
     pub doc_id: String,
+    pub chunk_id: String,
     pub path: String,
     pub title_path: String,
     pub section: String,
@@ -26,6 +56,150 @@ pub struct SearchHit {
     pub score: f32,
 }
 
+/// 检索时的元数据过滤条件，未设置的项不过滤
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChunkFilter {
+    pub doc_ids: Vec<String>,
+    /// 只检索该路径前缀下的文档，如 `runbooks/`
+    pub path_prefix: Option<String>,
+}
+
+impl ChunkFilter {
+    pub fn is_empty(&self) -> bool {
+        self.doc_ids.is_empty() && self.path_prefix.is_none()
+    }
+
+    pub fn matches(&self, doc_id: &str, path: &str) -> bool {
+        (self.doc_ids.is_empty() || self.doc_ids.iter().any(|id| id == doc_id))
+            && self
+                .path_prefix
+                .as_deref()
+                .is_none_or(|prefix| path.starts_with(prefix))
+    }
+}
+
+/// 已索引文档的汇总信息
+#[derive(Debug, Clone, Serialize)]
+pub struct DocumentSummary {
+    pub doc_id: String,
+    pub path: String,
+    pub title: String,
+    pub chunk_count: usize,
+    pub hash: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub indexed_at: Option<String>,
+}
+
+/// 单个片段的文本与元数据（不含向量）
+#[derive(Debug, Clone, Serialize)]
+pub struct ChunkRecord {
+    pub chunk_id: String,
+    pub title_path: String,
+    pub section: String,
+    pub text: String,
+    pub hash: String,
+}
+
+/// 生成向量时的参数，随表一起记录，用于发现模型或维度变化
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexSpec {
+    /// 旧表没有记录模型时为空字符串
+    pub embed_model: String,
+    pub vector_size: usize,
+    /// 旧表没有记录度量时视为 cosine（此前唯一支持的度量）
+    pub metric: DistanceMetric,
+}
+
+impl fmt::Display for IndexSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let model = if self.embed_model.is_empty() {
+            "unknown"
+        } else {
+            &self.embed_model
+        };
+        write!(f, "{model} ({} dims, {})", self.vector_size, self.metric)
+    }
+}
+
+/// 蓝绿构建中的一个索引版本（LanceDB 中对应一张表）
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexVersion {
+    pub name: String,
+    /// 版本创建时间（RFC3339），旧版单表没有该信息
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+    pub chunk_count: usize,
+    pub active: bool,
+}
+
+/// 历史版本保留时长的上限（10 年），配置与管理接口超出时拒绝
+pub const MAX_PRUNE_OLDER_THAN_SECS: u64 = 10 * 365 * 86_400;
+
+/// 一次表维护要执行的操作
+#[derive(Debug, Clone)]
+pub struct MaintenanceOptions {
+    /// 合并小文件
+    pub compact: bool,
+    /// 清理早于该秒数的数据集历史版本，None 表示不清理
+    pub prune_older_than_secs: Option<u64>,
+    /// 将新增数据并入已有向量索引
+    pub optimize_index: bool,
+    /// 按当前配置的索引类型与参数重建向量索引
+    pub rebuild_index: bool,
+}
+
+impl Default for MaintenanceOptions {
+    fn default() -> Self {
+        Self {
+            compact: true,
+            prune_older_than_secs: None,
+            optimize_index: true,
+            rebuild_index: false,
+        }
+    }
+}
+
+/// 文件合并结果
+#[derive(Debug, Clone, Serialize)]
+pub struct CompactionStats {
+    pub fragments_removed: usize,
+    pub fragments_added: usize,
+    pub files_removed: usize,
+    pub files_added: usize,
+}
+
+/// 历史版本清理结果
+#[derive(Debug, Clone, Serialize)]
+pub struct PruneStats {
+    pub old_versions: u64,
+    pub bytes_removed: u64,
+}
+
+/// 向量索引覆盖情况
+#[derive(Debug, Clone, Serialize)]
+pub struct VectorIndexStats {
+    pub name: String,
+    pub index_type: String,
+    pub indexed_rows: usize,
+    pub unindexed_rows: usize,
+}
+
+/// 一次表维护的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct MaintenanceReport {
+    /// 维护的版本表
+    pub table: String,
+    pub started_at: String,
+    pub duration_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compaction: Option<CompactionStats>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prune: Option<PruneStats>,
+    pub index_rebuilt: bool,
+    /// 维护后的向量索引状态，尚未建索引时为 None
+    pub vector_index: Option<VectorIndexStats>,
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum VectorStoreError {
     #[error("LanceDB error: {0}")]
@@ -36,6 +210,59 @@ pub enum VectorStoreError {
 
     #[error("Invalid vector store payload: {0}")]
     InvalidPayload(String),
+
+    #[error("Vector store I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Qdrant request failed: {0}")]
+    Qdrant(String),
+
+    #[cfg(feature = "pgvector")]
+    #[error("PostgreSQL error: {0}")]
+    Postgres(#[from] sqlx::Error),
+
+    #[error("Index version not found: {0}")]
+    VersionNotFound(String),
+
+    #[error("Cannot drop the active index version: {0}")]
+    ActiveVersion(String),
+
+    #[error(
+        "Index was built with {stored}, but the service is configured for {expected}; \
+         set INDEX_SPEC_MISMATCH=migrate to rebuild into a new table"
+    )]
+    SpecMismatch {
+        stored: IndexSpec,
+        expected: IndexSpec,
+    },
+
+    #[error(
+        "Index was built with {stored}, but migrating to {expected} needs index versions, \
+         which this vector store does not support; rebuild the index manually instead"
+    )]
+    MigrationUnsupported {
+        stored: IndexSpec,
+        expected: IndexSpec,
+    },
+
+    #[error(
+        "Index version {version} was built with {stored}, but the service is configured for \
+         {expected}; only versions built with the current embedding spec can be activated"
+    )]
+    VersionSpecMismatch {
+        version: String,
+        stored: IndexSpec,
+        expected: IndexSpec,
+    },
+
+    #[error(
+        "Index vectors have {stored} dimensions but queries are embedded with {expected}; \
+         search is unavailable until the index is rebuilt"
+    )]
+    DimensionMismatch { stored: usize, expected: usize },
+
+    #[error("Operation not supported by this vector store: {0}")]
+    Unsupported(&'static str),
 }
 
 pub type VectorStoreResult<T> = Result<T, VectorStoreError>;
@@ -47,11 +274,195 @@ pub trait VectorStore: Send + Sync {
     async fn search(&self, query_vector: Vec<f32>, top_k: u64)
     -> VectorStoreResult<Vec<SearchHit>>;
 
+    /// 带元数据过滤的向量检索
+    ///
+    /// 默认实现多取若干倍结果后在内存中过滤，符合条件的片段排名靠后时可能不足 `top_k`；
+    /// 能按条件预过滤的存储应覆盖该方法。
+    async fn search_filtered(
+        &self,
+        query_vector: Vec<f32>,
+        top_k: u64,
+        filter: &ChunkFilter,
+    ) -> VectorStoreResult<Vec<SearchHit>> {
+        if filter.is_empty() {
+            return self.search(query_vector, top_k).await;
+        }
+        let hits = self
+            .search(query_vector, top_k.saturating_mul(FILTER_OVERFETCH))
+            .await?;
+        Ok(hits
+            .into_iter()
+            .filter(|hit| filter.matches(&hit.doc_id, &hit.path))
+            .take(top_k as usize)
+            .collect())
+    }
+
+    /// 按 `point_id` 写入：已存在的记录整行覆盖，不存在的新增
     async fn upsert_chunks(&self, chunks: Vec<StoredChunk>) -> VectorStoreResult<()>;
 
-    async fn delete_by_doc_id(&self, doc_id: &str) -> VectorStoreResult<()>;
+    /// 删除文档的全部片段，返回删除的片段数
+    async fn delete_by_doc_id(&self, doc_id: &str) -> VectorStoreResult<usize>;
+
+    /// 删除文档中 `point_id` 不在 `keep_point_ids` 内的片段，返回删除的片段数
+    async fn delete_stale_chunks(
+        &self,
+        doc_id: &str,
+        keep_point_ids: &[String],
+    ) -> VectorStoreResult<usize>;
+
+    /// 按片段内容哈希返回文档已有片段的向量，内容未变化的片段据此复用 embedding
+    async fn chunk_vectors(&self, doc_id: &str) -> VectorStoreResult<HashMap<String, Vec<f32>>>;
+
+    /// 将缓冲的写入持久化；一批写入结束后调用，默认写入即持久化无需处理
+    async fn flush(&self) -> VectorStoreResult<()> {
+        Ok(())
+    }
 
     async fn list_doc_hashes(&self) -> VectorStoreResult<HashMap<String, String>>;
 
+    async fn list_documents(&self) -> VectorStoreResult<Vec<DocumentSummary>>;
+
+    /// 按片段顺序返回文档的全部片段；文档不存在时返回空列表
+    async fn list_chunks(&self, doc_id: &str) -> VectorStoreResult<Vec<ChunkRecord>>;
+
     async fn count(&self) -> VectorStoreResult<usize>;
+
+    /// 本实例写入时使用的生成参数
+    fn spec(&self) -> &IndexSpec;
+
+    /// 当前版本记录的生成参数；实现在启动与切换版本时读取并缓存，查询路径每次都会调用
+    async fn stored_spec(&self) -> VectorStoreResult<IndexSpec>;
+
+    /// 对当前版本执行文件合并、历史版本清理与索引优化
+    async fn optimize(
+        &self,
+        _options: &MaintenanceOptions,
+    ) -> VectorStoreResult<MaintenanceReport> {
+        Err(VectorStoreError::Unsupported("table maintenance"))
+    }
+
+    /// 创建一个空的新版本，返回版本名和只写入该版本的句柄；查询仍走当前版本
+    async fn create_version(&self) -> VectorStoreResult<(String, Arc<dyn VectorStore>)> {
+        Err(VectorStoreError::Unsupported("index versions"))
+    }
+
+    /// 原子地将查询切换到指定版本；版本的生成参数必须与本实例一致
+    async fn activate_version(&self, _version: &str) -> VectorStoreResult<()> {
+        Err(VectorStoreError::Unsupported("index versions"))
+    }
+
+    /// 全部版本，按创建时间升序
+    async fn list_versions(&self) -> VectorStoreResult<Vec<IndexVersion>> {
+        Err(VectorStoreError::Unsupported("index versions"))
+    }
+
+    /// 删除非当前版本
+    async fn drop_version(&self, _version: &str) -> VectorStoreResult<()> {
+        Err(VectorStoreError::Unsupported("index versions"))
+    }
+}
+
+/// 将距离归一化到 [0, 1]；对单位向量三种度量得到相同的分数 (1 + cos) / 2，
+/// 因此 `VECTOR_SCORE_THRESHOLD` 不随度量改变含义
+pub(crate) fn distance_to_score(metric: DistanceMetric, distance: f32) -> f32 {
+    let score = match metric {
+        // 1 - cos，取值 [0, 2]
+        DistanceMetric::Cosine | DistanceMetric::Dot => 1.0 - distance / 2.0,
+        // 欧氏距离的平方，单位向量时为 2 - 2cos，取值 [0, 4]
+        DistanceMetric::L2 => 1.0 - distance / 4.0,
+    };
+    score.clamp(0.0, 1.0)
+}
+
+/// 片段按 `<doc_id>_chunk_<n>` 中的序号排序
+pub(crate) fn chunk_ordinal(chunk_id: &str) -> usize {
+    chunk_id
+        .rsplit_once("_chunk_")
+        .and_then(|(_, n)| n.parse().ok())
+        .unwrap_or(usize::MAX)
+}
+
+/// 当前版本的生成参数与配置不一致时返回记录的参数
+pub async fn spec_mismatch(store: &dyn VectorStore) -> VectorStoreResult<Option<IndexSpec>> {
+    let stored = store.stored_spec().await?;
+    Ok((stored != *store.spec()).then_some(stored))
+}
+
+/// 启动检查：按策略拒绝启动或仅告警；维度或度量不一致时除迁移外一律拒绝，
+/// 不支持索引版本的向量库无法迁移，同样拒绝
+pub async fn verify_index_spec(
+    store: &dyn VectorStore,
+    policy: IndexSpecPolicy,
+) -> VectorStoreResult<()> {
+    let Some(stored) = spec_mismatch(store).await? else {
+        return Ok(());
+    };
+    let expected = store.spec().clone();
+
+    match policy {
+        IndexSpecPolicy::Migrate => {
+            // 用 list_versions 探测是否支持版本，避免启动检查就建出空表
+            if let Err(VectorStoreError::Unsupported(_)) = store.list_versions().await {
+                return Err(VectorStoreError::MigrationUnsupported { stored, expected });
+            }
+            tracing::warn!(
+                stored = %stored,
+                expected = %expected,
+                "index spec changed, a full rebuild into a new table will be started"
+            );
+            Ok(())
+        }
+        IndexSpecPolicy::Warn
+            if stored.vector_size == expected.vector_size && stored.metric == expected.metric =>
+        {
+            tracing::warn!(
+                stored = %stored,
+                expected = %expected,
+                "index spec mismatch, vectors from different models will be mixed"
+            );
+            Ok(())
+        }
+        IndexSpecPolicy::Warn | IndexSpecPolicy::Refuse => {
+            Err(VectorStoreError::SpecMismatch { stored, expected })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distance_to_score_converts_and_clamps() {
+        let cosine = DistanceMetric::Cosine;
+        assert_eq!(distance_to_score(cosine, 0.0), 1.0);
+        assert_eq!(distance_to_score(cosine, 2.0), 0.0);
+        assert_eq!(distance_to_score(cosine, 1.0), 0.5);
+        assert_eq!(distance_to_score(cosine, -1.0), 1.0);
+        assert_eq!(distance_to_score(cosine, 3.0), 0.0);
+    }
+
+    #[test]
+    fn unit_vector_scores_match_across_metrics() {
+        // 两个夹角为 60° 的单位向量：cos = 0.5
+        let cos = 0.5_f32;
+        let expected = (1.0 + cos) / 2.0;
+        let cases = [
+            (DistanceMetric::Cosine, 1.0 - cos),
+            (DistanceMetric::Dot, 1.0 - cos),
+            (DistanceMetric::L2, 2.0 - 2.0 * cos),
+        ];
+        for (metric, distance) in cases {
+            let score = distance_to_score(metric, distance);
+            assert!((score - expected).abs() < 1e-6, "{metric}: {score}");
+        }
+        assert_eq!(distance_to_score(DistanceMetric::L2, 9.0), 0.0);
+    }
+
+    #[test]
+    fn chunk_ordinal_sorts_numerically() {
+        assert_eq!(chunk_ordinal("runbook.md_chunk_10"), 10);
+        assert_eq!(chunk_ordinal("a_chunk_b.md_chunk_2"), 2);
+        assert_eq!(chunk_ordinal("legacy"), usize::MAX);
+    }
 }
@@ -0,0 +1,525 @@
+use std::collections::HashMap;
+
+use pgvector::Vector;
+use sqlx::{Executor, PgPool, postgres::PgPoolOptions};
+
+use crate::{
+    config::{DistanceMetric, PgVectorConfig, VectorIndexConfig},
+    vector_store::{
+        ChunkFilter, ChunkRecord, DocumentSummary, IndexSpec, SearchHit, StoredChunk, VectorStore,
+        VectorStoreError, VectorStoreResult, chunk_ordinal, distance_to_score,
+    },
+};
+
+/// index_meta 中记录生成参数的键
+const META_EMBED_MODEL: &str = "embed_model";
+const META_VECTOR_SIZE: &str = "vector_size";
+const META_DISTANCE: &str = "distance";
+
+const HNSW_INDEX: &str = "knowledge_chunks_embedding_hnsw";
+const DEFAULT_HNSW_M: u32 = 16;
+const DEFAULT_HNSW_EF_CONSTRUCTION: u32 = 64;
+
+/// PostgreSQL + pgvector 向量存储
+///
+/// 表结构由 `migrations/pgvector` 管理；向量列的维度在首次启动时按配置固定，
+/// 与模型、度量一起记录在 `index_meta`，之后由启动检查比对。HNSW 索引按记录的度量建立。
+/// 不支持蓝绿版本与表维护，全量重建在原表上进行。
+pub struct PgVectorStore {
+    pool: PgPool,
+    spec: IndexSpec,
+    /// `index_meta` 记录的生成参数，启动时读取，查询时直接比对
+    stored_spec: IndexSpec,
+    index: VectorIndexConfig,
+}
+
+impl PgVectorStore {
+    pub async fn new(
+        config: &PgVectorConfig,
+        spec: IndexSpec,
+        index: VectorIndexConfig,
+    ) -> VectorStoreResult<Self> {
+        let search_path = format!("SET search_path TO \"{}\", public", config.schema);
+        let pool = PgPoolOptions::new()
+            .max_connections(config.max_connections)
+            .after_connect(move |conn, _meta| {
+                let search_path = search_path.clone();
+                Box::pin(async move {
+                    conn.execute(search_path.as_str()).await?;
+                    Ok(())
+                })
+            })
+            .connect(&config.database_url)
+            .await?;
+
+        pool.execute(format!("CREATE SCHEMA IF NOT EXISTS \"{}\"", config.schema).as_str())
+            .await?;
+        sqlx::migrate!("./migrations/pgvector")
+            .run(&pool)
+            .await
+            .map_err(sqlx::Error::from)?;
+
+        let mut store = Self {
+            pool,
+            stored_spec: spec.clone(),
+            spec,
+            index,
+        };
+        store.record_spec_if_absent().await?;
+        store.stored_spec = store.read_spec().await?;
+        store.create_hnsw_index().await?;
+        Ok(store)
+    }
+
+    async fn read_meta(&self) -> VectorStoreResult<HashMap<String, String>> {
+        let rows: Vec<(String, String)> = sqlx::query_as("SELECT key, value FROM index_meta")
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows.into_iter().collect())
+    }
+
+    async fn read_spec(&self) -> VectorStoreResult<IndexSpec> {
+        let meta = self.read_meta().await?;
+        let vector_size = meta
+            .get(META_VECTOR_SIZE)
+            .and_then(|size| size.parse().ok())
+            .ok_or_else(|| {
+                VectorStoreError::InvalidPayload("index_meta has no vector_size".to_string())
+            })?;
+        let metric = match meta.get(META_DISTANCE) {
+            Some(name) => DistanceMetric::from_name(name).ok_or_else(|| {
+                VectorStoreError::InvalidPayload(format!("unknown distance metric: {name}"))
+            })?,
+            None => DistanceMetric::Cosine,
+        };
+
+        Ok(IndexSpec {
+            embed_model: meta.get(META_EMBED_MODEL).cloned().unwrap_or_default(),
+            vector_size,
+            metric,
+        })
+    }
+
+    /// 新表尚未记录参数：按当前配置固定向量维度并记录生成参数
+    async fn record_spec_if_absent(&self) -> VectorStoreResult<()> {
+        if self.read_meta().await?.contains_key(META_VECTOR_SIZE) {
+            return Ok(());
+        }
+
+        let mut tx = self.pool.begin().await?;
+        (&mut *tx)
+            .execute(
+                format!(
+                    "ALTER TABLE knowledge_chunks ALTER COLUMN embedding TYPE public.vector({})",
+                    self.spec.vector_size
+                )
+                .as_str(),
+            )
+            .await?;
+        for (key, value) in [
+            (META_EMBED_MODEL, self.spec.embed_model.clone()),
+            (META_VECTOR_SIZE, self.spec.vector_size.to_string()),
+            (META_DISTANCE, self.spec.metric.as_str().to_string()),
+        ] {
+            sqlx::query(
+                "INSERT INTO index_meta (key, value) VALUES ($1, $2) \
+                 ON CONFLICT (key) DO UPDATE SET value = EXCLUDED.value",
+            )
+            .bind(key)
+            .bind(value)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        tracing::info!(spec = %self.spec, "recorded embedding spec for pgvector table");
+        Ok(())
+    }
+
+    /// 按表记录的度量建立 HNSW 索引，已存在时跳过
+    async fn create_hnsw_index(&self) -> VectorStoreResult<()> {
+        let m = self.index.hnsw_m.unwrap_or(DEFAULT_HNSW_M);
+        let ef_construction = self
+            .index
+            .hnsw_ef_construction
+            .unwrap_or(DEFAULT_HNSW_EF_CONSTRUCTION);
+
+        self.pool
+            .execute(
+                format!(
+                    "CREATE INDEX IF NOT EXISTS {HNSW_INDEX} ON knowledge_chunks \
+                     USING hnsw (embedding {}) WITH (m = {m}, ef_construction = {ef_construction})",
+                    operator_class(self.stored_spec.metric)
+                )
+                .as_str(),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+fn operator_class(metric: DistanceMetric) -> &'static str {
+    match metric {
+        DistanceMetric::Cosine => "vector_cosine_ops",
+        DistanceMetric::L2 => "vector_l2_ops",
+        DistanceMetric::Dot => "vector_ip_ops",
+    }
+}
+
+fn operator(metric: DistanceMetric) -> &'static str {
+    match metric {
+        DistanceMetric::Cosine => "<=>",
+        DistanceMetric::L2 => "<->",
+        DistanceMetric::Dot => "<#>",
+    }
+}
+
+/// 换算成与 LanceDB 一致的距离：L2 取平方，`<#>` 返回负内积
+fn distance_expr(metric: DistanceMetric) -> &'static str {
+    match metric {
+        DistanceMetric::Cosine => "(embedding <=> $1)",
+        DistanceMetric::L2 => "power(embedding <-> $1, 2)",
+        DistanceMetric::Dot => "(1 + (embedding <#> $1))",
+    }
+}
+
+#[async_trait::async_trait]
+impl VectorStore for PgVectorStore {
+    async fn ensure_ready(&self) -> VectorStoreResult<()> {
+        self.pool.execute("SELECT 1").await?;
+        Ok(())
+    }
+
+    async fn search(
+        &self,
+        query_vector: Vec<f32>,
+        top_k: u64,
+    ) -> VectorStoreResult<Vec<SearchHit>> {
+        self.search_filtered(query_vector, top_k, &ChunkFilter::default())
+            .await
+    }
+
+    /// 过滤条件在 SQL 中与向量排序一起执行
+    async fn search_filtered(
+        &self,
+        query_vector: Vec<f32>,
+        top_k: u64,
+        filter: &ChunkFilter,
+    ) -> VectorStoreResult<Vec<SearchHit>> {
+        if query_vector.len() != self.spec.vector_size {
+            return Err(VectorStoreError::InvalidPayload(format!(
+                "vector size mismatch: got {}, expected {}",
+                query_vector.len(),
+                self.spec.vector_size
+            )));
+        }
+
+        let metric = self.spec.metric;
+        let sql = format!(
+            "SELECT doc_id, chunk_id, path, title_path, section, text, {} AS distance \
+             FROM knowledge_chunks \
+             WHERE (cardinality($2::text[]) = 0 OR doc_id = ANY($2)) \
+               AND ($3::text IS NULL OR starts_with(path, $3)) \
+             ORDER BY embedding {} $1 \
+             LIMIT $4",
+            distance_expr(metric),
+            operator(metric)
+        );
+
+        let mut tx = self.pool.begin().await?;
+        if let Some(ef) = self.index.ef {
+            (&mut *tx)
+                .execute(format!("SET LOCAL hnsw.ef_search = {ef}").as_str())
+                .await?;
+        }
+        if !filter.is_empty() {
+            // HNSW 默认只扫描 ef_search 个候选再过滤，结果可能不足 top_k；
+            // 迭代扫描（pgvector 0.8+）会继续扫描直到凑满
+            (&mut *tx)
+                .execute("SET LOCAL hnsw.iterative_scan = strict_order")
+                .await?;
+        }
+        let rows: Vec<(String, String, String, String, String, String, f64)> = sqlx::query_as(&sql)
+            .bind(Vector::from(query_vector))
+            .bind(&filter.doc_ids)
+            .bind(filter.path_prefix.as_deref())
+            .bind(top_k as i64)
+            .fetch_all(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        Ok(rows
+            .into_iter()
+            .map(
+                |(doc_id, chunk_id, path, title_path, section, text, distance)| SearchHit {
+                    doc_id,
+                    chunk_id,
+                    path,
+                    title_path,
+                    section,
+                    snippet: text,
+                    score: distance_to_score(metric, distance as f32),
+                },
+            )
+            .collect())
+    }
+
+    async fn upsert_chunks(&self, chunks: Vec<StoredChunk>) -> VectorStoreResult<()> {
+        if chunks.is_empty() {
+            return Ok(());
+        }
+
+        let mut tx = self.pool.begin().await?;
+        for chunk in chunks {
+            if chunk.vector.len() != self.spec.vector_size {
+                return Err(VectorStoreError::InvalidPayload(format!(
+                    "vector size mismatch: got {}, expected {}",
+                    chunk.vector.len(),
+                    self.spec.vector_size
+                )));
+            }
+
+            sqlx::query(
+                "INSERT INTO knowledge_chunks \
+                 (point_id, doc_id, chunk_id, path, title_path, section, text, hash, doc_hash, \
+                  indexed_at, embedding) \
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11) \
+                 ON CONFLICT (point_id) DO UPDATE SET \
+                 doc_id = EXCLUDED.doc_id, chunk_id = EXCLUDED.chunk_id, path = EXCLUDED.path, \
+                 title_path = EXCLUDED.title_path, section = EXCLUDED.section, \
+                 text = EXCLUDED.text, hash = EXCLUDED.hash, doc_hash = EXCLUDED.doc_hash, \
+                 indexed_at = EXCLUDED.indexed_at, embedding = EXCLUDED.embedding",
+            )
+            .bind(chunk.point_id)
+            .bind(chunk.doc_id)
+            .bind(chunk.chunk_id)
+            .bind(chunk.path)
+            .bind(chunk.title_path)
+            .bind(chunk.section)
+            .bind(chunk.text)
+            .bind(chunk.hash)
+            .bind(chunk.doc_hash)
+            .bind(chunk.indexed_at)
+            .bind(Vector::from(chunk.vector))
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn delete_by_doc_id(&self, doc_id: &str) -> VectorStoreResult<usize> {
+        let result = sqlx::query("DELETE FROM knowledge_chunks WHERE doc_id = $1")
+            .bind(doc_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() as usize)
+    }
+
+    async fn delete_stale_chunks(
+        &self,
+        doc_id: &str,
+        keep_point_ids: &[String],
+    ) -> VectorStoreResult<usize> {
+        let result = sqlx::query(
+            "DELETE FROM knowledge_chunks WHERE doc_id = $1 AND NOT (point_id = ANY($2))",
+        )
+        .bind(doc_id)
+        .bind(keep_point_ids)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() as usize)
+    }
+
+    async fn chunk_vectors(&self, doc_id: &str) -> VectorStoreResult<HashMap<String, Vec<f32>>> {
+        let rows: Vec<(String, Vector)> =
+            sqlx::query_as("SELECT hash, embedding FROM knowledge_chunks WHERE doc_id = $1")
+                .bind(doc_id)
+                .fetch_all(&self.pool)
+                .await?;
+        Ok(rows
+            .into_iter()
+            .map(|(hash, vector)| (hash, vector.to_vec()))
+            .collect())
+    }
+
+    async fn list_doc_hashes(&self) -> VectorStoreResult<HashMap<String, String>> {
+        // 旧数据没有文档哈希，视为需要重新索引
+        // 同一文档的片段应带相同哈希，不一致时以最近写入的为准
+        let rows: Vec<(String, String)> = sqlx::query_as(
+            "SELECT DISTINCT ON (doc_id) doc_id, doc_hash FROM knowledge_chunks \
+             ORDER BY doc_id, indexed_at DESC",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().collect())
+    }
+
+    async fn list_documents(&self) -> VectorStoreResult<Vec<DocumentSummary>> {
+        // 取一级标题作为文档标题
+        let rows: Vec<(String, String, i64, String, Option<String>, String)> = sqlx::query_as(
+            "SELECT doc_id, min(path), count(*), coalesce(max(NULLIF(doc_hash, '')), ''), \
+                    max(NULLIF(indexed_at, '')), \
+                    coalesce(split_part(min(NULLIF(title_path, '')), ' / ', 1), '') \
+             FROM knowledge_chunks GROUP BY doc_id ORDER BY doc_id",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(
+                |(doc_id, path, chunk_count, hash, indexed_at, title)| DocumentSummary {
+                    title: if title.is_empty() {
+                        path.clone()
+                    } else {
+                        title
+                    },
+                    doc_id,
+                    path,
+                    chunk_count: chunk_count as usize,
+                    hash,
+                    indexed_at,
+                },
+            )
+            .collect())
+    }
+
+    async fn list_chunks(&self, doc_id: &str) -> VectorStoreResult<Vec<ChunkRecord>> {
+        let rows: Vec<(String, String, String, String, String)> = sqlx::query_as(
+            "SELECT chunk_id, title_path, section, text, hash \
+             FROM knowledge_chunks WHERE doc_id = $1",
+        )
+        .bind(doc_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut chunks: Vec<ChunkRecord> = rows
+            .into_iter()
+            .map(|(chunk_id, title_path, section, text, hash)| ChunkRecord {
+                chunk_id,
+                title_path,
+                section,
+                text,
+                hash,
+            })
+            .collect();
+        chunks.sort_by_key(|chunk| chunk_ordinal(&chunk.chunk_id));
+        Ok(chunks)
+    }
+
+    async fn count(&self) -> VectorStoreResult<usize> {
+        let (count,): (i64,) = sqlx::query_as("SELECT count(*) FROM knowledge_chunks")
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(count as usize)
+    }
+
+    fn spec(&self) -> &IndexSpec {
+        &self.spec
+    }
+
+    async fn stored_spec(&self) -> VectorStoreResult<IndexSpec> {
+        Ok(self.stored_spec.clone())
+    }
+}
+
+/// 需要本地 PostgreSQL（带 pgvector 扩展），通过 PGVECTOR_TEST_DATABASE_URL 指定；
+/// 每个测试使用独立 schema，结束后删除
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vector_store::test_support::{check_conformance, spec, stored_chunk};
+
+    struct TestDb {
+        config: PgVectorConfig,
+    }
+
+    impl TestDb {
+        fn new() -> Self {
+            let database_url = std::env::var("PGVECTOR_TEST_DATABASE_URL")
+                .expect("PGVECTOR_TEST_DATABASE_URL must point to a Postgres with pgvector");
+            Self {
+                config: PgVectorConfig {
+                    database_url,
+                    schema: format!("engineqa_test_{}", uuid::Uuid::new_v4().simple()),
+                    max_connections: 2,
+                },
+            }
+        }
+
+        async fn open(&self, spec: IndexSpec) -> PgVectorStore {
+            PgVectorStore::new(&self.config, spec, VectorIndexConfig::default())
+                .await
+                .unwrap()
+        }
+
+        async fn drop_schema(self) {
+            let pool = PgPool::connect(&self.config.database_url).await.unwrap();
+            pool.execute(format!("DROP SCHEMA \"{}\" CASCADE", self.config.schema).as_str())
+                .await
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "requires PGVECTOR_TEST_DATABASE_URL"]
+    async fn conforms_to_the_vector_store_contract() {
+        let db = TestDb::new();
+        let store = db.open(spec("m", 2)).await;
+        check_conformance(&store).await;
+        db.drop_schema().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "requires PGVECTOR_TEST_DATABASE_URL"]
+    async fn filtered_search_fills_top_k_past_the_hnsw_candidates() {
+        let db = TestDb::new();
+        let index = VectorIndexConfig {
+            ef: Some(10),
+            ..VectorIndexConfig::default()
+        };
+        let store = PgVectorStore::new(&db.config, spec("m", 2), index)
+            .await
+            .unwrap();
+
+        // 离查询最近的候选都属于文档 a，过滤后只剩文档 b
+        let mut chunks: Vec<StoredChunk> = (0..300)
+            .map(|n| stored_chunk("a", n, vec![1.0, n as f32 / 1000.0]))
+            .collect();
+        chunks.extend((0..5).map(|n| stored_chunk("b", n, vec![n as f32 / 10.0, 1.0])));
+        store.upsert_chunks(chunks).await.unwrap();
+
+        let filter = ChunkFilter {
+            doc_ids: vec!["b".to_string()],
+            ..ChunkFilter::default()
+        };
+        let hits = store
+            .search_filtered(vec![1.0, 0.0], 5, &filter)
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 5);
+        assert!(hits.iter().all(|hit| hit.doc_id == "b"));
+
+        db.drop_schema().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "requires PGVECTOR_TEST_DATABASE_URL"]
+    async fn records_spec_and_detects_model_change() {
+        let db = TestDb::new();
+        db.open(spec("model-a", 2)).await;
+
+        let reopened = db.open(spec("model-b", 2)).await;
+        let stored = reopened.stored_spec().await.unwrap();
+        assert_eq!(stored, spec("model-a", 2));
+        assert!(
+            crate::vector_store::spec_mismatch(&reopened)
+                .await
+                .unwrap()
+                .is_some()
+        );
+
+        db.drop_schema().await;
+    }
+}
@@ -0,0 +1,870 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::Duration,
+};
+
+use reqwest::{Client, Method, StatusCode};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    config::{DistanceMetric, QdrantConfig},
+    vector_store::{
+        ChunkRecord, DocumentSummary, IndexSpec, SearchHit, StoredChunk, VectorStore,
+        VectorStoreError, VectorStoreResult, chunk_ordinal, distance_to_score,
+    },
+};
+
+/// 单次 upsert / scroll 的点数
+const BATCH_SIZE: usize = 256;
+
+/// 与 Python 后端共用的 payload；Python 写入的点只有 doc_id、path、title_path、
+/// section、text 与 hash，缺少的字段按空字符串读取
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct ChunkPayload {
+    point_id: String,
+    doc_id: String,
+    chunk_id: String,
+    path: String,
+    title_path: String,
+    section: String,
+    text: String,
+    hash: String,
+    doc_hash: String,
+    indexed_at: String,
+}
+
+impl ChunkPayload {
+    /// Python 写入的点没有 chunk_id，用点 ID 代替，保证引用与多路融合能区分片段
+    fn with_point_id(mut self, id: &Value) -> Self {
+        if self.chunk_id.is_empty() {
+            self.chunk_id = match id {
+                Value::String(id) => id.clone(),
+                other => other.to_string(),
+            };
+        }
+        self
+    }
+}
+
+impl From<&StoredChunk> for ChunkPayload {
+    fn from(chunk: &StoredChunk) -> Self {
+        Self {
+            point_id: chunk.point_id.clone(),
+            doc_id: chunk.doc_id.clone(),
+            chunk_id: chunk.chunk_id.clone(),
+            path: chunk.path.clone(),
+            title_path: chunk.title_path.clone(),
+            section: chunk.section.clone(),
+            text: chunk.text.clone(),
+            hash: chunk.hash.clone(),
+            doc_hash: chunk.doc_hash.clone(),
+            indexed_at: chunk.indexed_at.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Envelope<T> {
+    result: T,
+}
+
+#[derive(Debug, Deserialize)]
+struct ScoredPoint {
+    #[serde(default)]
+    id: Value,
+    score: f32,
+    #[serde(default)]
+    payload: ChunkPayload,
+}
+
+#[derive(Debug, Deserialize)]
+struct ScrollPoint {
+    #[serde(default)]
+    id: Value,
+    #[serde(default)]
+    payload: ChunkPayload,
+    #[serde(default)]
+    vector: Option<Vec<f32>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ScrollPage {
+    points: Vec<ScrollPoint>,
+    next_page_offset: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CountResult {
+    count: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct CollectionInfo {
+    config: CollectionConfig,
+}
+
+#[derive(Debug, Deserialize)]
+struct CollectionConfig {
+    params: CollectionParams,
+}
+
+#[derive(Debug, Deserialize)]
+struct CollectionParams {
+    vectors: VectorParams,
+}
+
+#[derive(Debug, Deserialize)]
+struct VectorParams {
+    size: usize,
+    distance: String,
+}
+
+/// Qdrant REST 向量存储
+///
+/// 集合可与 Python 后端共用：doc_id 同为相对路径的 SHA-256，payload 是 Python 字段的超集。
+/// 本服务的点 ID 由 `point_id` 派生为 UUID；Python 写入的点没有 chunk_id 与文档哈希，
+/// 读取时以点 ID 作为 chunk_id，增量索引时按内容变化重新索引并替换为本服务的点。
+/// Python 后端重建索引会删除整个集合，两边不要同时写入。
+/// Qdrant 集合不记录 embedding 模型名，启动检查只能发现维度与度量的变化；
+/// 不支持蓝绿版本与表维护，全量重建在原集合上进行；元数据过滤使用默认的多取后过滤。
+pub struct QdrantStore {
+    client: Client,
+    base_url: String,
+    collection: String,
+    api_key: Option<String>,
+    spec: IndexSpec,
+    /// 集合的向量参数，启动时读取，查询时直接比对
+    stored_spec: IndexSpec,
+}
+
+impl QdrantStore {
+    pub async fn new(config: &QdrantConfig, spec: IndexSpec) -> VectorStoreResult<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_millis(config.timeout_ms))
+            .build()
+            .map_err(|e| VectorStoreError::Qdrant(e.to_string()))?;
+
+        let mut store = Self {
+            client,
+            base_url: config.url.trim_end_matches('/').to_string(),
+            collection: config.collection.clone(),
+            api_key: config.api_key.clone(),
+            stored_spec: spec.clone(),
+            spec,
+        };
+        store.ensure_ready().await?;
+        store.stored_spec = store.read_spec().await?;
+        Ok(store)
+    }
+
+    /// 从集合配置读取向量参数；集合不记录模型名，沿用当前配置
+    async fn read_spec(&self) -> VectorStoreResult<IndexSpec> {
+        let info = self.collection_info().await?.ok_or_else(|| {
+            VectorStoreError::Qdrant(format!("collection not found: {}", self.collection))
+        })?;
+        let vectors = info.config.params.vectors;
+        let metric = metric_from_qdrant(&vectors.distance).ok_or_else(|| {
+            VectorStoreError::InvalidPayload(format!(
+                "unsupported Qdrant distance: {}",
+                vectors.distance
+            ))
+        })?;
+
+        Ok(IndexSpec {
+            embed_model: self.spec.embed_model.clone(),
+            vector_size: vectors.size,
+            metric,
+        })
+    }
+
+    fn collection_path(&self, suffix: &str) -> String {
+        format!("/collections/{}{suffix}", self.collection)
+    }
+
+    /// 发送请求，返回 404 时为 None，其余非 2xx 状态转为错误
+    async fn send<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<Value>,
+    ) -> VectorStoreResult<Option<T>> {
+        let mut request = self
+            .client
+            .request(method, format!("{}{path}", self.base_url));
+        if let Some(api_key) = &self.api_key {
+            request = request.header("api-key", api_key);
+        }
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| VectorStoreError::Qdrant(e.to_string()))?;
+        let status = response.status();
+        if status == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            let message = serde_json::from_str::<Value>(&body)
+                .ok()
+                .and_then(|value| value["status"]["error"].as_str().map(str::to_string))
+                .unwrap_or(body);
+            return Err(VectorStoreError::Qdrant(format!("{status}: {message}")));
+        }
+
+        let envelope: Envelope<T> = response
+            .json()
+            .await
+            .map_err(|e| VectorStoreError::InvalidPayload(e.to_string()))?;
+        Ok(Some(envelope.result))
+    }
+
+    async fn request<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<Value>,
+    ) -> VectorStoreResult<T> {
+        self.send(method, path, body).await?.ok_or_else(|| {
+            VectorStoreError::Qdrant(format!("collection not found: {}", self.collection))
+        })
+    }
+
+    async fn collection_info(&self) -> VectorStoreResult<Option<CollectionInfo>> {
+        self.send(Method::GET, &self.collection_path(""), None)
+            .await
+    }
+
+    async fn count_matching(&self, filter: Value) -> VectorStoreResult<usize> {
+        let result: CountResult = self
+            .request(
+                Method::POST,
+                &self.collection_path("/points/count"),
+                Some(json!({ "filter": filter, "exact": true })),
+            )
+            .await?;
+        Ok(result.count)
+    }
+
+    async fn delete_matching(&self, filter: Value) -> VectorStoreResult<usize> {
+        let existing = self.count_matching(filter.clone()).await?;
+        if existing > 0 {
+            self.request::<Value>(
+                Method::POST,
+                &self.collection_path("/points/delete?wait=true"),
+                Some(json!({ "filter": filter })),
+            )
+            .await?;
+        }
+        Ok(existing)
+    }
+
+    /// 按页读取全部点的 payload（不含向量）
+    async fn scroll(
+        &self,
+        filter: Option<Value>,
+        fields: &[&str],
+    ) -> VectorStoreResult<Vec<ChunkPayload>> {
+        Ok(self
+            .scroll_points(filter, fields, false)
+            .await?
+            .into_iter()
+            .map(|point| point.payload.with_point_id(&point.id))
+            .collect())
+    }
+
+    /// 按页读取全部点，`with_vector` 为真时一并返回向量
+    async fn scroll_points(
+        &self,
+        filter: Option<Value>,
+        fields: &[&str],
+        with_vector: bool,
+    ) -> VectorStoreResult<Vec<ScrollPoint>> {
+        let mut points = Vec::new();
+        let mut offset = None;
+
+        loop {
+            let mut body = json!({
+                "limit": BATCH_SIZE,
+                "with_payload": fields,
+                "with_vector": with_vector,
+            });
+            if let Some(filter) = &filter {
+                body["filter"] = filter.clone();
+            }
+            if let Some(offset) = offset.take() {
+                body["offset"] = offset;
+            }
+
+            let page: ScrollPage = self
+                .request(
+                    Method::POST,
+                    &self.collection_path("/points/scroll"),
+                    Some(body),
+                )
+                .await?;
+            points.extend(page.points);
+
+            match page.next_page_offset {
+                Some(next) if !next.is_null() => offset = Some(next),
+                _ => break,
+            }
+        }
+
+        Ok(points)
+    }
+}
+
+/// Qdrant 点 ID 只能是整数或 UUID，由 `point_id` 的 SHA-256 派生
+fn point_uuid(point_id: &str) -> String {
+    let digest = Sha256::digest(point_id.as_bytes());
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&digest[..16]);
+    uuid::Builder::from_custom_bytes(bytes)
+        .into_uuid()
+        .to_string()
+}
+
+fn qdrant_distance(metric: DistanceMetric) -> &'static str {
+    match metric {
+        DistanceMetric::Cosine => "Cosine",
+        DistanceMetric::L2 => "Euclid",
+        DistanceMetric::Dot => "Dot",
+    }
+}
+
+fn metric_from_qdrant(name: &str) -> Option<DistanceMetric> {
+    match name {
+        "Cosine" => Some(DistanceMetric::Cosine),
+        "Euclid" => Some(DistanceMetric::L2),
+        "Dot" => Some(DistanceMetric::Dot),
+        _ => None,
+    }
+}
+
+/// Qdrant 返回相似度（cosine/dot）或欧氏距离，先换算成与 LanceDB 一致的距离再归一化
+fn score_from_qdrant(metric: DistanceMetric, score: f32) -> f32 {
+    let distance = match metric {
+        DistanceMetric::Cosine | DistanceMetric::Dot => 1.0 - score,
+        DistanceMetric::L2 => score * score,
+    };
+    distance_to_score(metric, distance)
+}
+
+fn doc_id_filter(doc_id: &str) -> Value {
+    json!({ "must": [{ "key": "doc_id", "match": { "value": doc_id } }] })
+}
+
+#[async_trait::async_trait]
+impl VectorStore for QdrantStore {
+    async fn ensure_ready(&self) -> VectorStoreResult<()> {
+        if self.collection_info().await?.is_some() {
+            return Ok(());
+        }
+
+        self.request::<Value>(
+            Method::PUT,
+            &self.collection_path(""),
+            Some(json!({
+                "vectors": {
+                    "size": self.spec.vector_size,
+                    "distance": qdrant_distance(self.spec.metric),
+                }
+            })),
+        )
+        .await?;
+        // 按文档删除、列出片段都按 doc_id 过滤
+        self.request::<Value>(
+            Method::PUT,
+            &self.collection_path("/index?wait=true"),
+            Some(json!({ "field_name": "doc_id", "field_schema": "keyword" })),
+        )
+        .await?;
+        tracing::info!(collection = %self.collection, spec = %self.spec, "created Qdrant collection");
+        Ok(())
+    }
+
+    async fn search(
+        &self,
+        query_vector: Vec<f32>,
+        top_k: u64,
+    ) -> VectorStoreResult<Vec<SearchHit>> {
+        let points: Vec<ScoredPoint> = self
+            .request(
+                Method::POST,
+                &self.collection_path("/points/search"),
+                Some(json!({
+                    "vector": query_vector,
+                    "limit": top_k,
+                    "with_payload": true,
+                })),
+            )
+            .await?;
+
+        Ok(points
+            .into_iter()
+            .map(|point| {
+                let payload = point.payload.with_point_id(&point.id);
+                SearchHit {
+                    doc_id: payload.doc_id,
+                    chunk_id: payload.chunk_id,
+                    path: payload.path,
+                    title_path: payload.title_path,
+                    section: payload.section,
+                    snippet: payload.text,
+                    score: score_from_qdrant(self.spec.metric, point.score),
+                }
+            })
+            .collect())
+    }
+
+    async fn upsert_chunks(&self, chunks: Vec<StoredChunk>) -> VectorStoreResult<()> {
+        if let Some(chunk) = chunks
+            .iter()
+            .find(|chunk| chunk.vector.len() != self.spec.vector_size)
+        {
+            return Err(VectorStoreError::InvalidPayload(format!(
+                "vector size mismatch: got {}, expected {}",
+                chunk.vector.len(),
+                self.spec.vector_size
+            )));
+        }
+
+        for batch in chunks.chunks(BATCH_SIZE) {
+            let points: Vec<Value> = batch
+                .iter()
+                .map(|chunk| {
+                    json!({
+                        "id": point_uuid(&chunk.point_id),
+                        "vector": chunk.vector,
+                        "payload": ChunkPayload::from(chunk),
+                    })
+                })
+                .collect();
+            self.request::<Value>(
+                Method::PUT,
+                &self.collection_path("/points?wait=true"),
+                Some(json!({ "points": points })),
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    async fn delete_by_doc_id(&self, doc_id: &str) -> VectorStoreResult<usize> {
+        self.delete_matching(doc_id_filter(doc_id)).await
+    }
+
+    async fn delete_stale_chunks(
+        &self,
+        doc_id: &str,
+        keep_point_ids: &[String],
+    ) -> VectorStoreResult<usize> {
+        let mut filter = doc_id_filter(doc_id);
+        if !keep_point_ids.is_empty() {
+            let keep: Vec<String> = keep_point_ids.iter().map(|id| point_uuid(id)).collect();
+            filter["must_not"] = json!([{ "has_id": keep }]);
+        }
+        self.delete_matching(filter).await
+    }
+
+    async fn chunk_vectors(&self, doc_id: &str) -> VectorStoreResult<HashMap<String, Vec<f32>>> {
+        Ok(self
+            .scroll_points(Some(doc_id_filter(doc_id)), &["hash"], true)
+            .await?
+            .into_iter()
+            .filter_map(|point| Some((point.payload.hash, point.vector?)))
+            .collect())
+    }
+
+    async fn list_doc_hashes(&self) -> VectorStoreResult<HashMap<String, String>> {
+        // 旧数据没有文档哈希，视为需要重新索引
+        Ok(self
+            .scroll(None, &["doc_id", "doc_hash"])
+            .await?
+            .into_iter()
+            .map(|payload| (payload.doc_id, payload.doc_hash))
+            .collect())
+    }
+
+    async fn list_documents(&self) -> VectorStoreResult<Vec<DocumentSummary>> {
+        let payloads = self
+            .scroll(
+                None,
+                &["doc_id", "path", "title_path", "doc_hash", "indexed_at"],
+            )
+            .await?;
+        let mut documents: BTreeMap<String, DocumentSummary> = BTreeMap::new();
+
+        for payload in payloads {
+            let entry =
+                documents
+                    .entry(payload.doc_id.clone())
+                    .or_insert_with(|| DocumentSummary {
+                        doc_id: payload.doc_id.clone(),
+                        path: payload.path.clone(),
+                        title: String::new(),
+                        chunk_count: 0,
+                        hash: String::new(),
+                        indexed_at: None,
+                    });
+            entry.chunk_count += 1;
+
+            let title = &payload.title_path;
+            if entry.title.is_empty() && !title.is_empty() {
+                // 取一级标题作为文档标题
+                entry.title = title.split(" / ").next().unwrap_or(title).to_string();
+            }
+            if entry.hash.is_empty() {
+                entry.hash = payload.doc_hash;
+            }
+            if !payload.indexed_at.is_empty()
+                && entry
+                    .indexed_at
+                    .as_ref()
+                    .is_none_or(|current| *current < payload.indexed_at)
+            {
+                entry.indexed_at = Some(payload.indexed_at);
+            }
+        }
+
+        Ok(documents
+            .into_values()
+            .map(|mut doc| {
+                if doc.title.is_empty() {
+                    doc.title = doc.path.clone();
+                }
+                doc
+            })
+            .collect())
+    }
+
+    async fn list_chunks(&self, doc_id: &str) -> VectorStoreResult<Vec<ChunkRecord>> {
+        let mut chunks: Vec<ChunkRecord> = self
+            .scroll(
+                Some(doc_id_filter(doc_id)),
+                &["chunk_id", "title_path", "section", "text", "hash"],
+            )
+            .await?
+            .into_iter()
+            .map(|payload| ChunkRecord {
+                chunk_id: payload.chunk_id,
+                title_path: payload.title_path,
+                section: payload.section,
+                text: payload.text,
+                hash: payload.hash,
+            })
+            .collect();
+        chunks.sort_by_key(|chunk| chunk_ordinal(&chunk.chunk_id));
+        Ok(chunks)
+    }
+
+    async fn count(&self) -> VectorStoreResult<usize> {
+        self.count_matching(json!({})).await
+    }
+
+    fn spec(&self) -> &IndexSpec {
+        &self.spec
+    }
+
+    async fn stored_spec(&self) -> VectorStoreResult<IndexSpec> {
+        Ok(self.stored_spec.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::IndexSpecPolicy,
+        vector_store::test_support::{check_conformance, spec},
+    };
+    use axum::{
+        Json, Router,
+        extract::{Path, State},
+        routing::{get, post, put},
+    };
+    use std::sync::{Arc, Mutex};
+
+    /// 测试用的 Qdrant 替身：只实现本存储用到的接口与过滤条件
+    #[derive(Default)]
+    struct FakeQdrant {
+        collections: HashMap<String, Value>,
+        points: BTreeMap<String, (Vec<f32>, Value)>,
+    }
+
+    type Fake = Arc<Mutex<FakeQdrant>>;
+
+    fn ok(result: Value) -> Json<Value> {
+        Json(json!({ "result": result, "status": "ok" }))
+    }
+
+    fn matches(filter: &Value, id: &str, payload: &Value) -> bool {
+        let must = filter["must"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .all(|cond| payload[cond["key"].as_str().unwrap()] == cond["match"]["value"]);
+        let must_not = filter["must_not"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .any(|cond| {
+                cond["has_id"]
+                    .as_array()
+                    .is_some_and(|ids| ids.iter().any(|keep| keep == id))
+            });
+        must && !must_not
+    }
+
+    async fn fake_qdrant() -> String {
+        let state: Fake = Arc::default();
+        let app = Router::new()
+            .route(
+                "/collections/{name}",
+                get(
+                    |State(fake): State<Fake>, Path(name): Path<String>| async move {
+                        let fake = fake.lock().unwrap();
+                        match fake.collections.get(&name) {
+                            Some(vectors) => Ok(ok(json!({
+                                "config": { "params": { "vectors": vectors } }
+                            }))),
+                            None => Err(StatusCode::NOT_FOUND),
+                        }
+                    },
+                )
+                .put(
+                    |State(fake): State<Fake>,
+                     Path(name): Path<String>,
+                     Json(body): Json<Value>| async move {
+                        let mut fake = fake.lock().unwrap();
+                        fake.collections.insert(name, body["vectors"].clone());
+                        ok(json!(true))
+                    },
+                ),
+            )
+            .route(
+                "/collections/{name}/index",
+                put(|| async { ok(json!({ "status": "completed" })) }),
+            )
+            .route(
+                "/collections/{name}/points",
+                put(
+                    |State(fake): State<Fake>, Json(body): Json<Value>| async move {
+                        let mut fake = fake.lock().unwrap();
+                        for point in body["points"].as_array().unwrap() {
+                            let vector = serde_json::from_value(point["vector"].clone()).unwrap();
+                            fake.points.insert(
+                                point["id"].as_str().unwrap().to_string(),
+                                (vector, point["payload"].clone()),
+                            );
+                        }
+                        ok(json!({ "status": "completed" }))
+                    },
+                ),
+            )
+            .route(
+                "/collections/{name}/points/search",
+                post(
+                    |State(fake): State<Fake>, Json(body): Json<Value>| async move {
+                        let query: Vec<f32> =
+                            serde_json::from_value(body["vector"].clone()).unwrap();
+                        let fake = fake.lock().unwrap();
+                        let mut hits: Vec<(f32, &String, &Value)> = fake
+                            .points
+                            .iter()
+                            .map(|(id, (vector, payload))| {
+                                let dot: f32 = query.iter().zip(vector).map(|(a, b)| a * b).sum();
+                                (dot, id, payload)
+                            })
+                            .collect();
+                        hits.sort_by(|a, b| b.0.total_cmp(&a.0));
+                        hits.truncate(body["limit"].as_u64().unwrap() as usize);
+                        ok(hits
+                            .into_iter()
+                            .map(|(score, id, payload)| {
+                                json!({ "id": id, "score": score, "payload": payload })
+                            })
+                            .collect())
+                    },
+                ),
+            )
+            .route(
+                "/collections/{name}/points/scroll",
+                post(
+                    |State(fake): State<Fake>, Json(body): Json<Value>| async move {
+                        let fake = fake.lock().unwrap();
+                        let limit = body["limit"].as_u64().unwrap() as usize;
+                        let offset = body["offset"].as_str().unwrap_or("");
+                        let mut matched = fake
+                            .points
+                            .range(offset.to_string()..)
+                            .filter(|(id, (_, payload))| matches(&body["filter"], id, payload));
+                        let points: Vec<Value> = matched
+                            .by_ref()
+                            .take(limit)
+                            .map(|(id, (vector, payload))| {
+                                let mut point = json!({ "id": id, "payload": payload });
+                                if body["with_vector"] == json!(true) {
+                                    point["vector"] = json!(vector);
+                                }
+                                point
+                            })
+                            .collect();
+                        let next = matched.next().map(|(id, _)| json!(id));
+                        ok(json!({ "points": points, "next_page_offset": next }))
+                    },
+                ),
+            )
+            .route(
+                "/collections/{name}/points/count",
+                post(
+                    |State(fake): State<Fake>, Json(body): Json<Value>| async move {
+                        let fake = fake.lock().unwrap();
+                        let count = fake
+                            .points
+                            .iter()
+                            .filter(|(id, (_, payload))| matches(&body["filter"], id, payload))
+                            .count();
+                        ok(json!({ "count": count }))
+                    },
+                ),
+            )
+            .route(
+                "/collections/{name}/points/delete",
+                post(
+                    |State(fake): State<Fake>, Json(body): Json<Value>| async move {
+                        let mut fake = fake.lock().unwrap();
+                        fake.points
+                            .retain(|id, (_, payload)| !matches(&body["filter"], id, payload));
+                        ok(json!({ "status": "completed" }))
+                    },
+                ),
+            )
+            .with_state(state);
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        format!("http://{addr}")
+    }
+
+    fn config(url: String) -> QdrantConfig {
+        QdrantConfig {
+            url,
+            api_key: None,
+            collection: "knowledge_chunks".to_string(),
+            timeout_ms: 5000,
+        }
+    }
+
+    #[test]
+    fn derives_stable_uuid_point_ids() {
+        let id = point_uuid("a|a_chunk_0|h0");
+        assert_eq!(id, point_uuid("a|a_chunk_0|h0"));
+        assert_ne!(id, point_uuid("a|a_chunk_0|h1"));
+        assert!(uuid::Uuid::parse_str(&id).is_ok());
+    }
+
+    #[test]
+    fn normalises_qdrant_scores() {
+        assert_eq!(score_from_qdrant(DistanceMetric::Cosine, 1.0), 1.0);
+        assert_eq!(score_from_qdrant(DistanceMetric::Cosine, -1.0), 0.0);
+        assert_eq!(score_from_qdrant(DistanceMetric::L2, 0.0), 1.0);
+        assert_eq!(score_from_qdrant(DistanceMetric::L2, 2.0), 0.0);
+    }
+
+    #[tokio::test]
+    async fn conforms_to_the_vector_store_contract() {
+        let url = fake_qdrant().await;
+        let store = QdrantStore::new(&config(url), spec("m", 2)).await.unwrap();
+        check_conformance(&store).await;
+    }
+
+    #[tokio::test]
+    async fn reads_points_written_by_the_python_backend() {
+        let url = fake_qdrant().await;
+        let store = QdrantStore::new(&config(url.clone()), spec("m", 2))
+            .await
+            .unwrap();
+
+        // Python 的写法：doc_id = sha256(相对路径)，点 ID 为 uuid5，payload 没有 chunk_id 与文档哈希
+        let path = "runbooks/cpm.md";
+        let doc_id = format!("{:x}", Sha256::digest(path.as_bytes()));
+        let point_id = "6f1c1f4e-3b5a-5d2e-9a57-8f0f1c2d3e4a";
+        Client::new()
+            .put(format!(
+                "{url}/collections/knowledge_chunks/points?wait=true"
+            ))
+            .json(&json!({ "points": [{
+                "id": point_id,
+                "vector": [0.6, 0.8],
+                "payload": {
+                    "doc_id": doc_id,
+                    "path": path,
+                    "title_path": "CPM 告警 / 排查",
+                    "section": "排查",
+                    "text": "检查底价配置",
+                    "hash": "h0",
+                },
+            }] }))
+            .send()
+            .await
+            .unwrap()
+            .error_for_status()
+            .unwrap();
+
+        let hits = store.search(vec![0.6, 0.8], 1).await.unwrap();
+        assert_eq!(hits[0].chunk_id, point_id);
+        assert_eq!(hits[0].doc_id, doc_id);
+        assert_eq!(
+            store.list_chunks(&doc_id).await.unwrap()[0].chunk_id,
+            point_id
+        );
+
+        // 文档哈希为空，增量索引会重新索引而不是当作已删除的文档
+        let documents = store.list_documents().await.unwrap();
+        assert_eq!(documents[0].doc_id, doc_id);
+        assert_eq!(documents[0].path, path);
+        assert_eq!(store.list_doc_hashes().await.unwrap()[&doc_id], "");
+        assert_eq!(
+            store.chunk_vectors(&doc_id).await.unwrap()["h0"],
+            vec![0.6, 0.8]
+        );
+    }
+
+    #[tokio::test]
+    async fn reports_collection_spec() {
+        let url = fake_qdrant().await;
+        let l2 = IndexSpec {
+            metric: DistanceMetric::L2,
+            ..spec("m", 2)
+        };
+        QdrantStore::new(&config(url.clone()), l2).await.unwrap();
+
+        let reopened = QdrantStore::new(&config(url), spec("m", 3)).await.unwrap();
+        let stored = reopened.stored_spec().await.unwrap();
+        assert_eq!(stored.vector_size, 2);
+        assert_eq!(stored.metric, DistanceMetric::L2);
+        assert!(
+            crate::vector_store::spec_mismatch(&reopened)
+                .await
+                .unwrap()
+                .is_some()
+        );
+
+        // Qdrant 集合没有版本，无法迁移到新表
+        let migrate =
+            crate::vector_store::verify_index_spec(&reopened, IndexSpecPolicy::Migrate).await;
+        assert!(matches!(
+            migrate,
+            Err(VectorStoreError::MigrationUnsupported { .. })
+        ));
+    }
+}
@@ -0,0 +1,112 @@
+use crate::{
+    config::DistanceMetric,
+    vector_store::{ChunkFilter, IndexSpec, StoredChunk, VectorStore},
+};
+
+pub(crate) fn spec(model: &str, vector_size: usize) -> IndexSpec {
+    IndexSpec {
+        embed_model: model.to_string(),
+        vector_size,
+        metric: DistanceMetric::Cosine,
+    }
+}
+
+/// 文档 `doc_id` 的第 `n` 个片段，位于 `<doc_id>/doc.md`，ID 格式与索引器一致
+pub(crate) fn stored_chunk(doc_id: &str, n: usize, vector: Vec<f32>) -> StoredChunk {
+    StoredChunk {
+        point_id: format!("{doc_id}|{doc_id}_chunk_{n}|h{n}"),
+        doc_id: doc_id.to_string(),
+        chunk_id: format!("{doc_id}_chunk_{n}"),
+        path: format!("{doc_id}/doc.md"),
+        title_path: format!("{doc_id} / s{n}"),
+        section: format!("s{n}"),
+        text: format!("text {n}"),
+        hash: format!("h{n}"),
+        doc_hash: format!("{doc_id}-hash"),
+        indexed_at: format!("2024-01-{:02}T00:00:00Z", n + 1),
+        vector,
+    }
+}
+
+/// 对一个空的、二维 cosine 存储执行 `VectorStore` 约定的读写行为检查
+pub(crate) async fn check_conformance(store: &dyn VectorStore) {
+    store
+        .upsert_chunks(vec![
+            stored_chunk("a", 2, vec![1.0, 0.0]),
+            stored_chunk("a", 10, vec![0.0, 1.0]),
+            stored_chunk("b", 0, vec![0.6, 0.8]),
+        ])
+        .await
+        .unwrap();
+    store.flush().await.unwrap();
+    assert_eq!(store.count().await.unwrap(), 3);
+
+    // 分数按度量归一化，方向相同时为 1
+    let hits = store.search(vec![0.0, 1.0], 2).await.unwrap();
+    assert_eq!(hits.len(), 2);
+    assert_eq!(hits[0].chunk_id, "a_chunk_10");
+    assert!((hits[0].score - 1.0).abs() < 1e-4, "{}", hits[0].score);
+    assert_eq!(hits[1].doc_id, "b");
+    assert_eq!(hits[1].path, "b/doc.md");
+
+    let by_doc = ChunkFilter {
+        doc_ids: vec!["a".to_string()],
+        ..ChunkFilter::default()
+    };
+    let hits = store
+        .search_filtered(vec![0.0, 1.0], 3, &by_doc)
+        .await
+        .unwrap();
+    assert_eq!(hits.len(), 2);
+    assert!(hits.iter().all(|hit| hit.doc_id == "a"));
+
+    let by_path = ChunkFilter {
+        path_prefix: Some("b/".to_string()),
+        ..ChunkFilter::default()
+    };
+    let hits = store
+        .search_filtered(vec![1.0, 0.0], 1, &by_path)
+        .await
+        .unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].doc_id, "b");
+
+    let documents = store.list_documents().await.unwrap();
+    assert_eq!(documents.len(), 2);
+    assert_eq!(documents[0].doc_id, "a");
+    assert_eq!(documents[0].path, "a/doc.md");
+    assert_eq!(documents[0].title, "a");
+    assert_eq!(documents[0].chunk_count, 2);
+    assert_eq!(documents[0].hash, "a-hash");
+    assert_eq!(
+        documents[0].indexed_at.as_deref(),
+        Some("2024-01-11T00:00:00Z")
+    );
+
+    // 片段按序号而不是字典序排列
+    let chunks = store.list_chunks("a").await.unwrap();
+    let ids: Vec<&str> = chunks.iter().map(|c| c.chunk_id.as_str()).collect();
+    assert_eq!(ids, vec!["a_chunk_2", "a_chunk_10"]);
+    assert!(store.list_chunks("missing").await.unwrap().is_empty());
+
+    assert_eq!(store.list_doc_hashes().await.unwrap()["b"], "b-hash");
+    assert_eq!(
+        store.chunk_vectors("b").await.unwrap()["h0"],
+        vec![0.6, 0.8]
+    );
+
+    // 相同 point_id 覆盖写入
+    let mut edited = stored_chunk("a", 10, vec![0.0, 1.0]);
+    edited.text = "edited".to_string();
+    store.upsert_chunks(vec![edited]).await.unwrap();
+    assert_eq!(store.count().await.unwrap(), 3);
+    assert_eq!(store.list_chunks("a").await.unwrap()[1].text, "edited");
+
+    let keep = vec![stored_chunk("a", 2, vec![]).point_id];
+    assert_eq!(store.delete_stale_chunks("a", &keep).await.unwrap(), 1);
+    assert_eq!(store.delete_stale_chunks("a", &keep).await.unwrap(), 0);
+    assert_eq!(store.delete_by_doc_id("b").await.unwrap(), 1);
+    assert_eq!(store.delete_by_doc_id("b").await.unwrap(), 0);
+    store.flush().await.unwrap();
+    assert_eq!(store.count().await.unwrap(), 1);
+}
@@ -7,6 +7,7 @@ import type {
   ReindexRequest,
   ReindexResponse,
   ReindexStatusResponse,
+  ApiErrorBody,
 } from './types';
 
 const API_BASE_URL = (import.meta.env.VITE_API_BASE_URL || '').trim();
@@ -43,6 +44,18 @@ export class ApiClient {
     }
   }
 
+  private async failure(response: Response, label: string): Promise<Error> {
+    try {
+      const body = (await response.json()) as ApiErrorBody;
+      if (body?.error_code) {
+        return new Error(`${label}: [${body.error_code}] ${body.message} (trace_id: ${body.trace_id})`);
+      }
+    } catch {
+      // 非 JSON 响应体，退回状态文本
+    }
+    return new Error(`${label}: ${response.statusText}`);
+  }
+
   async query(request: QueryRequest): Promise<QueryResponse> {
     const response = await this.request('/api/query', {
       method: 'POST',
@@ -56,7 +69,7 @@ export class ApiClient {
     });
 
     if (!response.ok) {
-      throw new Error(`Query failed: ${response.statusText}`);
+      throw await this.failure(response, 'Query failed');
     }
 
     return response.json();
@@ -72,7 +85,7 @@ export class ApiClient {
     });
 
     if (!response.ok) {
-      throw new Error(`Feedback failed: ${response.statusText}`);
+      throw await this.failure(response, 'Feedback failed');
     }
 
     return response.json();
@@ -82,7 +95,7 @@ export class ApiClient {
     const response = await this.request('/api/status');
 
     if (!response.ok) {
-      throw new Error(`Status check failed: ${response.statusText}`);
+      throw await this.failure(response, 'Status check failed');
     }
 
     return response.json();
@@ -98,7 +111,7 @@ export class ApiClient {
     });
 
     if (!response.ok) {
-      throw new Error(`Reindex failed: ${response.statusText}`);
+      throw await this.failure(response, 'Reindex failed');
     }
 
     return response.json();
@@ -108,7 +121,7 @@ export class ApiClient {
     const response = await this.request('/api/reindex');
 
     if (!response.ok) {
-      throw new Error(`Reindex status check failed: ${response.statusText}`);
+      throw await this.failure(response, 'Reindex status check failed');
     }
 
     return response.json();
@@ -118,7 +131,7 @@ export class ApiClient {
     const response = await this.request('/health');
 
     if (!response.ok) {
-      throw new Error(`Health check failed: ${response.statusText}`);
+      throw await this.failure(response, 'Health check failed');
     }
 
     return response.json();
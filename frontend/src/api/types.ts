@@ -1,23 +1,79 @@
 export interface QueryRequest {
   question: string;
   top_k?: number;
+  score_threshold?: number;
+  max_per_doc?: number;
+  retrieval_mode?: 'vector' | 'multi_query';
+  bypass_cache?: boolean;
+  prompt_template?: string;
+  explain?: boolean;
+  doc_ids?: string[];
+  path_prefix?: string;
 }
 
 export interface QuerySource {
+  chunk_id: string;
   title: string;
   path: string;
   snippet: string;
   score: number;
+  cited: boolean;
+}
+
+export interface Citation {
+  number: number;
+  source_index?: number;
+  chunk_id?: string;
+  valid: boolean;
+  span_start: number;
+  span_end: number;
+  text: string;
+}
+
+export interface GroundingReport {
+  mode: 'embedding' | 'llm' | 'hybrid';
+  score: number;
+  checked_sentences: number;
+  unsupported: {
+    text: string;
+    similarity?: number;
+  }[];
+}
+
+export interface QueryExplain {
+  queries: {
+    kind: 'original' | 'paraphrase' | 'hyde';
+    text: string;
+    hits: number;
+  }[];
+  rewrite_fallback: boolean;
+  fused_hits: number;
 }
 
 export interface QueryResponse {
   answer: string;
   sources: QuerySource[];
+  citations: Citation[];
+  invalid_citations: number[];
+  grounding?: GroundingReport;
   degraded: boolean;
   error_code?: string;
+  explain?: QueryExplain;
+  served_by?: {
+    provider: string;
+    model: string;
+  };
+  cached?: boolean;
   trace_id: string;
 }
 
+export interface ApiErrorBody {
+  error_code: string;
+  message: string;
+  trace_id: string;
+  details?: Record<string, unknown>;
+}
+
 export interface FeedbackRequest {
   question: string;
   answer: string;
@@ -37,6 +93,7 @@ export interface StatusResponse {
   model: string;
   index_size: number;
   last_index_time?: string;
+  next_scheduled_reindex?: string | null;
   upstream_health: 'ok' | 'degraded' | 'unavailable';
   rate_limit_state: {
     rpm_limit: number;
@@ -45,7 +102,21 @@ export interface StatusResponse {
   qdrant_connected: boolean;
 }
 
-export interface ReindexRequest {}
+export interface ReindexRequest {
+  full?: boolean;
+  paths?: string[];
+  prefix?: string;
+}
+
+export interface FileOutcome {
+  path: string;
+  doc_id: string;
+  status: 'indexed' | 'unchanged' | 'deleted' | 'failed' | 'not_found';
+  chunks: number;
+  reused_chunks: number;
+  failed_chunks: number;
+  error?: string;
+}
 
 export interface ReindexResponse {
   job_id: string;
@@ -58,11 +129,21 @@ export interface JobStatus {
   failed: boolean;
 }
 
+export interface IndexProgress {
+  files_total: number;
+  files_done: number;
+  chunks_embedded: number;
+  current_file?: string | null;
+  eta_secs?: number | null;
+}
+
 export interface JobInfo {
   job_id: string;
-  status: 'running' | 'completed' | 'failed';
+  status: 'running' | 'completed' | 'failed' | 'cancelled';
   started_at: string;
   ended_at?: string;
+  progress?: IndexProgress | null;
+  cancel_requested: boolean;
   result?: {
     total_files: number;
     indexed_files: number;
@@ -73,10 +154,128 @@ export interface JobInfo {
     failed_chunks: number;
     deleted_chunks: number;
     duration_ms: number;
+    files: FileOutcome[];
+    version?: string;
+    cancelled?: boolean;
   };
   error?: string;
 }
 
+export interface JobListResponse {
+  jobs: JobInfo[];
+}
+
 export interface ReindexStatusResponse {
   job?: JobInfo;
 }
+
+export interface DocumentSummary {
+  doc_id: string;
+  path: string;
+  title: string;
+  chunk_count: number;
+  hash: string;
+  indexed_at?: string;
+}
+
+export interface DocumentListResponse {
+  total: number;
+  documents: DocumentSummary[];
+}
+
+export interface ChunkRecord {
+  chunk_id: string;
+  title_path: string;
+  section: string;
+  text: string;
+  hash: string;
+}
+
+export interface DocumentChunksResponse {
+  doc_id: string;
+  chunks: ChunkRecord[];
+}
+
+export interface DeleteDocumentResponse {
+  doc_id: string;
+  deleted_chunks: number;
+}
+
+export interface UploadDocumentRequest {
+  path: string;
+  content: string;
+  overwrite?: boolean;
+}
+
+export interface UploadDocumentResponse {
+  doc_id: string;
+  path: string;
+  chunk_ids: string[];
+  reused_chunks: number;
+  total_chunks: number;
+  failed_chunks: number;
+  created: boolean;
+}
+
+export interface IndexVersion {
+  name: string;
+  created_at?: string;
+  chunk_count: number;
+  active: boolean;
+}
+
+export interface IndexVersionsResponse {
+  versions: IndexVersion[];
+}
+
+export interface RollbackRequest {
+  version?: string;
+}
+
+export interface RollbackResponse {
+  previous: string;
+  active: string;
+}
+
+export interface MaintenanceRequest {
+  compact?: boolean;
+  prune?: boolean;
+  prune_older_than_secs?: number;
+  optimize_index?: boolean;
+  rebuild_index?: boolean;
+}
+
+export interface CompactionStats {
+  fragments_removed: number;
+  fragments_added: number;
+  files_removed: number;
+  files_added: number;
+}
+
+export interface PruneStats {
+  old_versions: number;
+  bytes_removed: number;
+}
+
+export interface VectorIndexStats {
+  name: string;
+  index_type: string;
+  indexed_rows: number;
+  unindexed_rows: number;
+}
+
+export interface MaintenanceReport {
+  table: string;
+  started_at: string;
+  duration_ms: number;
+  compaction?: CompactionStats;
+  prune?: PruneStats;
+  index_rebuilt: boolean;
+  vector_index: VectorIndexStats | null;
+}
+
+export interface MaintenanceStatusResponse {
+  running: boolean;
+  interval_secs: number;
+  last_report: MaintenanceReport | null;
+}